@@ -0,0 +1,74 @@
+//! Measures how quickly programs with hundreds of live variables run, which is dominated by the
+//! cost of updating the `Environment`. Alongside, the same updates are made to a `HashMap` cloned
+//! for each one, as environments used to be, as a baseline. Run with `cargo bench`.
+use std::collections::HashMap;
+use std::hint;
+use std::time::{Duration, Instant};
+use uc::big_step::Statement;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::{Environment, Value};
+
+const VARIABLES: usize = 500;
+const ITERATIONS: usize = 2_000;
+
+fn main() {
+    let mut source: Vec<String> = (0..VARIABLES).map(|i| format!("v{} = {}", i, i)).collect();
+    source.push(format!(
+        "i = 0; while (i < {}) {{ v0 = v0 + i; v{} = v{} * 1; i = i + 1 }}",
+        ITERATIONS,
+        VARIABLES - 1,
+        VARIABLES - 1
+    ));
+    let program = parse_statement(&source.join("; ")).unwrap();
+
+    let start = Instant::now();
+    let steps = Machine::new(program.clone()).trace().unwrap().len();
+    let elapsed = start.elapsed();
+    println!(
+        "small-step: {} steps with {} variables in {:?} ({:.0} steps/s)",
+        steps,
+        VARIABLES,
+        elapsed,
+        steps as f64 / elapsed.as_secs_f64()
+    );
+
+    let start = Instant::now();
+    program.evaluate(&Environment::empty()).unwrap();
+    println!("big-step: evaluated in {:?}", start.elapsed());
+
+    let names: Vec<String> = (0..VARIABLES).map(|i| format!("v{}", i)).collect();
+    let updates = |elapsed: Duration| {
+        format!(
+            "{} updates with {} variables in {:?} ({:.0} updates/s)",
+            ITERATIONS,
+            VARIABLES,
+            elapsed,
+            ITERATIONS as f64 / elapsed.as_secs_f64()
+        )
+    };
+
+    let mut environment: Environment = names
+        .iter()
+        .map(|name| (name.clone(), Value::Number(0)))
+        .collect();
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        environment = environment.update(&names[i % VARIABLES], Value::Number(i as i64));
+    }
+    hint::black_box(&environment);
+    println!("persistent: {}", updates(start.elapsed()));
+
+    let mut map: HashMap<String, Value> = names
+        .iter()
+        .map(|name| (name.clone(), Value::Number(0)))
+        .collect();
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        let mut updated = map.clone();
+        updated.insert(names[i % VARIABLES].clone(), Value::Number(i as i64));
+        map = updated;
+    }
+    hint::black_box(&map);
+    println!("cloned HashMap: {}", updates(start.elapsed()));
+}
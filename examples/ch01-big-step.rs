@@ -1,32 +1,24 @@
-use uc::{big_step::*, Environment, Printable, Value::*};
+use uc::{ast::*, big_step::*, Environment, EvalError, Printable, Value::*};
 
-fn main() {
-    println!(
-        "{}",
-        LessThan::new(Number(5), Number(8))
-            .evaluate(&Environment::empty())
-            .inspect()
-    );
-    println!(
-        "{}",
-        LessThan::new(Number(2), Number(2))
-            .evaluate(&Environment::empty())
-            .inspect()
-    );
-    println!(
-        "{}",
-        LessThan::new(Number(18), Number(2))
-            .evaluate(&Environment::empty())
-            .inspect()
-    );
+fn main() -> Result<(), EvalError> {
+    let comparisons: [Expr; 3] = [
+        LessThan::new(Number(5), Number(8)).into(),
+        LessThan::new(Number(2), Number(2)).into(),
+        LessThan::new(Number(18), Number(2)).into(),
+    ];
+    for comparison in &comparisons {
+        println!("{}", comparison.evaluate(&Environment::empty())?.inspect());
+    }
 
-    let seq = Sequence::new(
+    let seq: Stmt = Sequence::new(
         Assign::new("x", Number(0)),
         While::new(
             LessThan::new(Variable::new("x"), Number(60_001)),
             Assign::new("x", Add::new(Variable::new("x"), Number(2))),
         ),
-    );
+    )
+    .into();
     println!("{}", seq.inspect());
-    println!("{}", seq.evaluate(&Environment::empty()));
+    println!("{}", seq.evaluate(&Environment::empty())?);
+    Ok(())
 }
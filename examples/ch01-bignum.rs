@@ -0,0 +1,50 @@
+//! Numbers outgrow an `i64` and carry on as `Value::BigNumber`s, in every semantics.
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::Statement as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::Machine;
+use uc::{Environment, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let factorial = parse_statement("f = 1; n = 30; while (n > 1) { f = f * n; n = n - 1 }")?;
+    let fibonacci = parse_statement(
+        "a = 0; b = 1; n = 0; while (n < 100) { t = a + b; a = b; b = t; n = n + 1 }",
+    )?;
+
+    for (program, name) in &[(&factorial, "f"), (&fibonacci, "a")] {
+        let big_step = program.evaluate(&Environment::empty())?;
+        let denotational = program.to_closure()(&Environment::empty())?;
+        let small_step = Machine::new((*program).clone())
+            .last()
+            .unwrap()?
+            .environment;
+        for environment in &[&big_step, &denotational, &small_step] {
+            println!("{} = {}", name, environment.get(name).unwrap().to_s());
+        }
+    }
+
+    // Results come back down to `Value::Number` as soon as they fit again.
+    let environment = parse_statement("x = 9223372036854775807 * 9223372036854775807")?
+        .evaluate(&Environment::empty())?;
+    for source in &[
+        "x / 9223372036854775807",
+        "x - x + 1",
+        "-x < 0 && x > 9223372036854775807",
+        "x % 10",
+        "x == 85070591730234615847396907784232501249",
+        "x - x",
+    ] {
+        let value = parse_expression(source)?.evaluate(&environment)?;
+        println!("{} = {:?}", source, value);
+    }
+
+    // Big literals print in full and read back the same.
+    for source in &[
+        "x = 123456789012345678901234567890",
+        "y = -(123456789012345678901234567890)",
+        "z = -123456789012345678901234567890 + 1",
+    ] {
+        println!("{}", parse_statement(source)?.inspect());
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! `break` and `continue`: leaving a loop early, or skipping to its next pass, in every semantics.
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::signs::{AbstractEnvironment, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::Environment;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let programs = [
+        // A search which stops at the first match, with no flag variable.
+        "xs = [4, 8, 15, 16, 23, 42]; i = 0; found = -1; while (i < xs.length) { \
+         if (xs[i] > 10) { found = xs[i]; break } else { do-nothing }; i = i + 1 }",
+        "i = 0; total = 0; while (i < 10) { i = i + 1; \
+         if (i % 2 == 0) { continue } else { do-nothing }; total = total + i }",
+        // A `break` leaves only the innermost loop.
+        "primes = []; n = 2; while (n < 30) { d = 2; prime = true; while (d * d <= n) { \
+         if (n % d == 0) { prime = false; break } else { d = d + 1 } }; \
+         if (prime) { primes = primes.append(n) } else { do-nothing }; n = n + 1 }",
+        // Jumping out of a block still forgets the variables it declared.
+        "n = 0; while (true) { let (square = n * n) { \
+         if (square > 50) { break } else { n = n + 1; continue } } }",
+        // A loop inside a function is the function's own.
+        "def first(xs, limit) { r = -1; i = 0; while (i < xs.length) { \
+         if (xs[i] > limit) { r = i; break } else { i = i + 1 } }; return r }; \
+         i = 0; hits = []; while (i < 3) { i = i + 1; \
+         hits = hits.append(first([1, 5, 10], i * 4)) }",
+    ];
+    for source in &programs {
+        let program = parse_statement(source)?;
+        println!("{}", program.evaluate(&Environment::empty())?);
+        println!("{}", program.to_closure()(&Environment::empty())?);
+        println!("{}", Machine::new(program).last().unwrap()?.environment);
+    }
+
+    // The machine carries a jump outwards, statement by statement, until it reaches its loop.
+    let program = parse_statement("while (true) { x = 1; break; x = 2 }")?;
+    Machine::new(program).run()?;
+
+    // Had the analysis ignored the `break`, it would claim `x` ends up negative.
+    let program = parse_statement("x = -1; while (x < 0) { x = 1; break; x = -1 }")?;
+    println!(
+        "{}",
+        program.abstract_evaluate(&AbstractEnvironment::empty())
+    );
+
+    // Outside a loop there is nothing to jump to, which the checker catches before running.
+    for source in &[
+        "x = 1; if (x > 0) { break } else { continue }",
+        "def stop() { break; return 0 }; while (true) { x = stop() }",
+    ] {
+        let program = parse_statement(source)?;
+        for error in types::check(&program, &TypeContext::empty()).unwrap_err() {
+            println!("{}", error);
+        }
+        println!("{}", program.evaluate(&Environment::empty()).unwrap_err());
+    }
+    Ok(())
+}
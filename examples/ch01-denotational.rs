@@ -0,0 +1,35 @@
+use uc::ast::*;
+use uc::denotational::*;
+use uc::{Environment, EvalError, Printable, Value::*};
+
+fn main() -> Result<(), EvalError> {
+    let expression: Expr = LessThan::new(Add::new(Variable::new("x"), Number(2)), Number(5)).into();
+    println!("{}", expression.inspect());
+    let meaning = expression.to_closure();
+    for x in 0..5 {
+        let environment = Environment::from([("x", Number(x))]);
+        println!("{}: {}", environment, meaning(&environment)?.inspect());
+    }
+
+    // Translated once, then run as many times as we like without walking the tree again.
+    let program: Stmt = Sequence::new(
+        Assign::new("total", Number(0)),
+        While::new(
+            LessThan::new(Number(0), Variable::new("n")),
+            Sequence::new(
+                Assign::new(
+                    "total",
+                    Add::new(Variable::new("total"), Variable::new("n")),
+                ),
+                Assign::new("n", Add::new(Variable::new("n"), Number(-1))),
+            ),
+        ),
+    )
+    .into();
+    println!("{}", program.inspect());
+    let run = program.to_closure();
+    for n in &[1, 10, 100, 30_000] {
+        println!("{}", run(&Environment::from([("n", Number(*n))]))?);
+    }
+    Ok(())
+}
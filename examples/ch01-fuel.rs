@@ -0,0 +1,34 @@
+use uc::big_step::Evaluator;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::{Environment, Outcome};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let forever = parse_statement("x = 0; while (true) { x = x + 1 }")?;
+
+    match Machine::new(forever.clone()).run_with_limit(20)? {
+        Outcome::Finished(environment) => println!("finished: {}", environment),
+        Outcome::OutOfFuel { steps, environment } => {
+            println!("gave up after {} steps: {}", steps, environment)
+        }
+    }
+
+    // Without printing each step, a far bigger budget takes no time.
+    println!(
+        "{:?}",
+        Machine::new(forever.clone()).finish_with_limit(100_000)?
+    );
+
+    let mut evaluator = Evaluator::new().with_fuel(1_000);
+    match evaluator.evaluate(&forever, &Environment::empty())? {
+        Outcome::Finished(environment) => println!("finished: {}", environment),
+        Outcome::OutOfFuel { steps, environment } => {
+            println!("gave up after {} steps: {}", steps, environment)
+        }
+    }
+
+    // A program which finishes within its budget is unaffected.
+    let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }")?;
+    println!("{:?}", evaluator.evaluate(&program, &Environment::empty())?);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! Functions: definitions, calls with their own local variables, and recursion.
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let programs = [
+        (
+            "def fact(n) { if (n < 2) { r = 1 } else { r = n * fact(n - 1) }; return r }; \
+             x = fact(20)",
+            "x",
+        ),
+        (
+            "def fib(n) { if (n < 2) { r = n } else { r = fib(n - 1) + fib(n - 2) }; return r }; \
+             x = fib(15)",
+            "x",
+        ),
+        // Each function can call the others, whichever was defined first.
+        (
+            "def even(n) { if (n == 0) { r = true } else { r = odd(n - 1) }; return r }; \
+             def odd(n) { if (n == 0) { r = false } else { r = even(n - 1) }; return r }; \
+             x = even(10) && odd(7)",
+            "x",
+        ),
+        (
+            "def sum(xs) { i = 0; total = 0; while (i < xs.length) { total = total + xs[i]; \
+             i = i + 1 }; return total }; def square(x) { return x * x }; \
+             x = sum([square(1), square(2), square(3)])",
+            "x",
+        ),
+        // A call's variables stay inside it, including ones named like the caller's.
+        (
+            "x = 1; def set(y) { x = y * 2; return x }; z = set(5) + x",
+            "x",
+        ),
+    ];
+    for (source, variable) in &programs {
+        let program = parse_statement(source)?;
+        let context = types::check(&program, &TypeContext::empty()).unwrap();
+        let big_step = program.evaluate(&Environment::empty())?;
+        let denotational = program.to_closure()(&Environment::empty())?;
+        let small_step = Machine::new(program).last().unwrap()?.environment;
+        println!(
+            "{} = {} (a {}), {} by translation, {} by reduction",
+            variable,
+            big_step.get(variable).unwrap().to_s(),
+            context.get(variable).unwrap(),
+            denotational.get(variable).unwrap().to_s(),
+            small_step.get(variable).unwrap().to_s()
+        );
+    }
+
+    // The trace shows each call as a frame holding its own variables, nested in the caller's.
+    let program = parse_statement(
+        "def double(n) { m = n * 2; return m }; def quadruple(n) { return double(double(n)) }; \
+         x = quadruple(5)",
+    )?;
+    Machine::new(program).run()?;
+
+    for source in &[
+        "def f(a, b) { return a + b }; x = f(1)",
+        "y = 3; x = y(1)",
+        "y = 3; def f() { return y }; x = f()",
+    ] {
+        let error = parse_statement(source)?
+            .evaluate(&Environment::empty())
+            .unwrap_err();
+        println!("{}: {}", source, error);
+    }
+
+    for source in &[
+        "def f(a, b) { return a + b }; x = f(1)",
+        "y = 3; x = y(1)",
+        "def length(s) { return s.length }; x = length(3)",
+        "def f(n) { return n }; x = f(1) + f(true)",
+        "def f() { return 1 }; f = 2",
+    ] {
+        let errors = types::check(&parse_statement(source)?, &TypeContext::empty()).unwrap_err();
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        println!("{}: {}", source, errors.join("; "));
+    }
+    for source in &["def f(n) { n = 1 }", "def f(1) { return 1 }", "x = f(1, )"] {
+        println!("{}", parse_statement(source).unwrap_err());
+    }
+    Ok(())
+}
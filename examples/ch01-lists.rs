@@ -0,0 +1,118 @@
+//! Lists: literals, `.length`, indexing, slicing, `.append` and assigning to an element, used to
+//! sort and search in SIMPLE.
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::Expression as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::signs::{AbstractEnvironment, Statement as _};
+use uc::small_step::{Expression as _, Machine};
+use uc::types::{self, TypeContext, Typed};
+use uc::{Environment, Printable, Value};
+
+fn list(numbers: &[i64]) -> Value {
+    Value::List(numbers.iter().copied().map(Value::Number).collect())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let environment = Environment::from([("xs", list(&[3, 1, 2]))]);
+    let context = TypeContext::from(&environment);
+    for source in &[
+        "xs.length",
+        "xs[0] + xs[2]",
+        "xs[1:3]",
+        "xs[2:1].length",
+        "xs.append(xs.length)",
+        "[xs[2], xs[1] * 10, -xs[0]]",
+        "[[1], []][0].append(2)",
+        "[].length == 0",
+    ] {
+        let expression = parse_expression(source)?;
+        let mut reduced = expression.clone();
+        while reduced.is_reducible() {
+            reduced = reduced.reduce(&environment)?;
+        }
+        println!(
+            "{} = {}, {} by translation, {} by reduction: a {}",
+            source,
+            expression.evaluate(&environment)?.inspect(),
+            expression.to_closure()(&environment)?.inspect(),
+            reduced.inspect(),
+            expression.type_of(&context).unwrap()
+        );
+    }
+
+    // Out of range is an error, not a panic, and short-circuiting keeps `xs[j]` safe below.
+    for source in &["xs[3]", "xs[-1]", "[][0]"] {
+        let error = parse_expression(source)?
+            .evaluate(&environment)
+            .unwrap_err();
+        println!("{}: {}", source, error);
+    }
+    let error = parse_statement("xs = [3, 1, 2]; xs[3] = 0")?
+        .evaluate(&Environment::empty())
+        .unwrap_err();
+    println!("xs[3] = 0: {}", error);
+
+    // `.append` makes a new list; only assignment changes a variable.
+    let environment = parse_statement("xs = [1]; ys = xs.append(2); xs[0] = 5")?
+        .evaluate(&Environment::empty())?;
+    println!("{}", environment);
+
+    let programs = [
+        (
+            "insertion sort",
+            "xs = [5, 2, 9, 1, 5, 6]; i = 1; \
+             while (i < xs.length) { \
+             key = xs[i]; j = i - 1; \
+             while (j >= 0 && xs[j] > key) { xs[j + 1] = xs[j]; j = j - 1 }; \
+             xs[j + 1] = key; i = i + 1 }",
+            "xs",
+        ),
+        (
+            "binary search",
+            "xs = [1, 3, 5, 7, 9, 11]; target = 7; lo = 0; hi = xs.length; found = -1; \
+             while (lo < hi) { mid = (lo + hi) / 2; \
+             if (xs[mid] == target) { found = mid; lo = hi } \
+             else { if (xs[mid] < target) { lo = mid + 1 } else { hi = mid } } }",
+            "found",
+        ),
+        (
+            "squares",
+            "squares = []; n = 0; while (n < 5) { squares = squares.append(n * n); n = n + 1 }",
+            "squares",
+        ),
+    ];
+    for (name, source, variable) in &programs {
+        let program = parse_statement(source)?;
+        let context = types::check(&program, &TypeContext::empty()).unwrap();
+        let big_step = program.evaluate(&Environment::empty())?;
+        let signs = program.abstract_evaluate(&AbstractEnvironment::empty());
+        println!(
+            "{}: {}\n  {} is a {}\n  signs {}",
+            name,
+            big_step,
+            variable,
+            context.get(variable).unwrap(),
+            signs
+        );
+    }
+
+    for source in &[
+        "xs = [1, true]",
+        "xs = [1]; xs[0] = \"one\"",
+        "n = 1; n[0] = 2",
+        "s = \"abc\"; t = s.append(\"d\")",
+        "xs = [[1]]; ys = xs[0] + 1",
+    ] {
+        let errors = types::check(&parse_statement(source)?, &TypeContext::empty()).unwrap_err();
+        println!("{}: {}", source, errors[0]);
+    }
+    for source in &["xs = [1, 2", "xs = [1 2]", "xs = [].push(1)"] {
+        println!("{}", parse_statement(source).unwrap_err());
+    }
+
+    Machine::new(parse_statement(
+        "xs = [3, 1]; t = xs[0]; xs[0] = xs[1]; xs[1] = t",
+    )?)
+    .run()?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! The full set of operators, run under every semantics.
+use uc::ast::Expr;
+use uc::big_step::Expression as _;
+use uc::denotational::Expression as _;
+use uc::parser::parse_expression;
+use uc::small_step::Expression;
+use uc::types::{TypeContext, Typed};
+use uc::{Environment, EvalError, Printable, Value, Value::*};
+
+/// Reduces an expression all the way with the small-step semantics.
+fn reduce(mut expression: Expr, environment: &Environment) -> Result<Value, EvalError> {
+    while expression.is_reducible() {
+        expression = expression.reduce(environment)?;
+    }
+    Ok(expression.as_value().cloned().unwrap())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let environment =
+        Environment::from([("x", Number(7)), ("y", Number(-2)), ("t", Boolean(true))]);
+    let context = TypeContext::from(&environment);
+    for source in &[
+        "x - y - 1",
+        "x / y",
+        "x % y",
+        "-x % 3",
+        "-(x + y) * 2",
+        "x == 7 && y != 7",
+        "x > y == y < x",
+        "x <= 7 && x >= 8",
+        "!t || x / y < -3",
+        "t == !false",
+        // The right operand of `&&` and `||` is skipped once the left settles the answer.
+        "false && x / 0 == 1",
+        "t || x % 0 == 1",
+    ] {
+        let expression = parse_expression(source)?;
+        println!(
+            "{} = {}, {} by translation, {} by reduction: a {}",
+            expression.to_s(),
+            expression.evaluate(&environment)?.to_s(),
+            expression.to_closure()(&environment)?.to_s(),
+            reduce(expression.clone(), &environment)?.to_s(),
+            expression.type_of(&context).unwrap()
+        );
+    }
+
+    for source in &["x / (y + 2)", "x % 0", "t && x / 0 == 1"] {
+        let error = parse_expression(source)?
+            .evaluate(&environment)
+            .unwrap_err();
+        println!("{}: {}", source, error);
+    }
+
+    // `-` is a negative literal straight before a digit, unless it follows an operand.
+    for source in &[
+        "-3",
+        "x-3",
+        "x - -3",
+        "- 3",
+        "--3",
+        "--x",
+        "-9223372036854775808",
+    ] {
+        println!("{} reads as {:?}", source, parse_expression(source)?);
+    }
+
+    for source in &["x == t", "-t", "!x", "x && t"] {
+        let errors = parse_expression(source)?.type_of(&context).unwrap_err();
+        println!("{}: {}", source, errors[0]);
+    }
+    Ok(())
+}
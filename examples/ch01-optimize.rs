@@ -0,0 +1,78 @@
+//! Optimising programs: folding constants, applying identities like `x * 1`, and taking the branch
+//! an `If` is sure to take, so the machine has fewer steps to make and ends up in the same place.
+use uc::optimize::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::{EvalError, Output, Overflow, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let programs = [
+        "x = 3 * 4; y = x * 1 + 0; z = 0 + y * (2 - 1); w = z * 0",
+        "n = 0; while (n < 2 * 5) { if (1 > 2 && n > 0) { print n } else { do-nothing }; \
+         n = n + 1 * 1 }",
+        "xs = [1 + 1, 2 * 3].append(4 - 0); first = xs[0] * 1; l = \"hello\"[1:3].length",
+        // Function bodies are left alone, and a call is never left out, even when multiplied by
+        // zero.
+        "def sq(n) { return n * n * 1 }; if (2 > 1) { a = sq(3 + 0) } else { a = 0 }; \
+         b = sq(a) * 0",
+        "for (i = 0 * 5; i < 3; i += 1 * 1) { print i * 1 + 0 }; \
+         unless (1 == 1) { print \"never\" }",
+        "let (k = 2 * 2) { while (true) { k += 0 + 1; \
+         if (k > 3 + 3) { break } else { continue } } }; done = !false",
+    ];
+    for source in &programs {
+        let program = parse_statement(source)?;
+        let optimized = program.optimize();
+        let mut unoptimized = Machine::new(program).with_output(Output::buffer());
+        let steps = unoptimized.trace()?.len();
+        let mut machine = Machine::new(optimized.clone()).with_output(Output::buffer());
+        let trace = machine.trace()?;
+        let environment = &trace.last().unwrap().environment;
+        println!(
+            "{}\n  {} steps instead of {}, ending with {}",
+            optimized.to_s(),
+            trace.len(),
+            steps,
+            environment
+        );
+    }
+
+    // Anything which would fail is left for the run to report, so it fails in the same way.
+    for source in &[
+        "x = 10 / (5 - 5)",
+        "x = [1, 2][1 + 1]",
+        "if (1 + 1) { y = 1 } else { y = 2 }",
+        "y = (10 / 0) * 0",
+    ] {
+        let optimized = parse_statement(source)?.optimize();
+        let error = Machine::new(optimized.clone()).trace().unwrap_err();
+        println!("{} fails as before: {}", optimized.to_s(), error);
+    }
+
+    // What overflow does is up to the run, so arithmetic that overflows is not folded.
+    let program = parse_statement("big = 9223372036854775807 + 2 * 1; small = -big")?;
+    let optimized = program.optimize();
+    println!("{}", optimized.to_s());
+    for overflow in &[
+        Overflow::Promote,
+        Overflow::Wrapping,
+        Overflow::Saturating,
+        Overflow::Checked,
+    ] {
+        let run = |program| {
+            Machine::new(program)
+                .with_overflow(*overflow)
+                .last()
+                .unwrap()
+                .map(|step| step.environment)
+        };
+        match run(optimized.clone()) {
+            Ok(environment) => println!("{:?}: {}", overflow, environment),
+            Err(EvalError::Overflow(operation)) => {
+                println!("{:?}: overflow in {}", overflow, operation)
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(())
+}
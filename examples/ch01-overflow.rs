@@ -0,0 +1,52 @@
+//! The same overflowing program under each `Overflow` policy, in both operational semantics.
+use uc::big_step::{Evaluator, Expression as _};
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::{Expression, Machine};
+use uc::{Environment, Outcome, Overflow, Runtime, Value::Number};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Ten to the seventieth power is far beyond the nineteen digits an `i64` can hold.
+    let program = parse_statement("x = 1; n = 0; while (n < 70) { x = x * 10; n = n + 1 }")?;
+
+    for overflow in &[
+        Overflow::Promote,
+        Overflow::Wrapping,
+        Overflow::Saturating,
+        Overflow::Checked,
+    ] {
+        match Evaluator::new()
+            .with_overflow(*overflow)
+            .evaluate(&program, &Environment::empty())
+        {
+            Ok(Outcome::Finished(environment)) => println!("{:?}: {}", overflow, environment),
+            Ok(outcome) => unreachable!("no fuel limit was set: {:?}", outcome),
+            Err(error) => println!("{:?}: {}", overflow, error),
+        }
+        let small_step = Machine::new(program.clone())
+            .with_overflow(*overflow)
+            .trace()
+            .map(|trace| trace.last().unwrap().environment.clone());
+        match small_step {
+            Ok(environment) => println!("  by reduction: {}", environment),
+            Err(error) => println!("  by reduction: {}", error),
+        }
+    }
+
+    // Checked arithmetic fails the same way whichever way this example was compiled.
+    let environment = Environment::empty().update("x", Number(i64::MIN));
+    for source in &["9223372036854775807 + 1", "-9223372036854775808 / -1", "-x"] {
+        let expression = parse_expression(source)?;
+        let error = expression
+            .evaluate_with(&environment, &mut Runtime::from(Overflow::Checked))
+            .unwrap_err();
+        let mut reduced = expression;
+        let reduction = loop {
+            match reduced.reduce_with(&environment, &mut Runtime::from(Overflow::Checked)) {
+                Ok(next) => reduced = next,
+                Err(error) => break error,
+            }
+        };
+        println!("{}: {}, and by reduction {}", source, error, reduction);
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use uc::big_step::Statement;
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::Machine;
+use uc::{Environment, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // One parsed program runs under either semantics.
+    let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }")?;
+    println!("{}", program.evaluate(&Environment::empty())?);
+    Machine::new(program).run()?;
+
+    // Anything printed by `to_s` reads back as the same program.
+    for source in &[
+        "do-nothing",
+        "x = -3",
+        "if (x < 1) { y = true } else { y = false }",
+        "x = 0; while (x < 5) { x = x + 2 }",
+        "x = (1 + 2) * 3 + 4 * (5 + 6)",
+        "b = 1 < 2 < (3 < 4)",
+    ] {
+        let program = parse_statement(source)?;
+        println!("{}", program.to_s());
+    }
+    println!("{}", parse_expression("1 + (2 + 3) * x")?.inspect());
+
+    for source in &[
+        "x = 1 +",
+        "while (x) {\n  x = 1 y = 2\n}",
+        "if (true) { x = 1 }",
+    ] {
+        match parse_statement(source) {
+            Ok(program) => println!("unexpectedly parsed {}", program.inspect()),
+            Err(error) => println!("{}", error),
+        }
+    }
+    Ok(())
+}
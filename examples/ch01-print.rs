@@ -0,0 +1,57 @@
+//! `print`: each semantics writes the same lines, in the same order, to the `Output` it is given,
+//! and none of it is mixed into the machine's trace.
+use std::{env, fs};
+use uc::big_step::Evaluator;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Output, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let programs = [
+        "n = 1; while (n <= 15) { if (n % 15 == 0) { print \"FizzBuzz\" } else { \
+         if (n % 3 == 0) { print \"Fizz\" } else { if (n % 5 == 0) { print \"Buzz\" } \
+         else { print n } } }; n = n + 1 }",
+        // Strings print as their bare text, but inside a list they keep their quotes.
+        "print [\"one\", \"two\"]; print \"a\" + \"\\tb\"; print [[-3], []]; print 3 < 2",
+        // A function prints while its caller is part way through an expression.
+        "def shout(s) { print s + \"!\"; return s.length }; print shout(\"hi\") + shout(\"yo\")",
+        "print \"\"; do-nothing",
+    ];
+    for source in &programs {
+        let program = parse_statement(source)?;
+        types::check(&program, &TypeContext::empty()).unwrap();
+
+        let mut evaluator = Evaluator::new().with_output(Output::buffer());
+        evaluator.evaluate(&program, &Environment::empty())?;
+        let mut machine = Machine::new(program.clone()).with_output(Output::buffer());
+        machine.trace()?;
+        println!(
+            "{}\n  big-step printed {:?}, small-step printed {:?}",
+            program.to_s(),
+            evaluator.output().lines().unwrap(),
+            machine.output().lines().unwrap()
+        );
+        program.to_closure()(&Environment::empty())?;
+        println!();
+    }
+
+    // Output can go to any writer, here a file.
+    let path = env::temp_dir().join("uc-print.txt");
+    let program =
+        parse_statement("x = 3; while (x > 0) { print x; x = x - 1 }; print \"liftoff\"")?;
+    let file = fs::File::create(&path)?;
+    Machine::new(program)
+        .with_output(Output::Writer(Box::new(file)))
+        .last()
+        .unwrap()?;
+    print!("{}", fs::read_to_string(&path)?);
+
+    // The trace goes to standard output, and the program's own output goes to its buffer.
+    let program = parse_statement("x = 6 * 7; print x")?;
+    let mut machine = Machine::new(program).with_output(Output::buffer());
+    machine.run()?;
+    println!("printed: {:?}", machine.output());
+    Ok(())
+}
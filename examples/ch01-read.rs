@@ -0,0 +1,71 @@
+//! `read`: one program, parsed once, run against several inputs, whether they come from a queue of
+//! values or from lines of text.
+use std::io::Cursor;
+use uc::big_step::Evaluator;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Input, Output, Value};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let program = parse_statement(
+        "read name; read n; total = 0; i = 0; while (i < n) { read x; total = total + x; \
+         i = i + 1 }; print name + \":\"; print total",
+    )?;
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    println!(
+        "total is a {:?}, name is a {:?}",
+        context.get("total"),
+        context.get("name")
+    );
+
+    let runs = [
+        (
+            vec![Value::String(String::from("small")), Value::Number(2)],
+            vec![Value::Number(3), Value::Number(4)],
+        ),
+        (
+            vec![Value::String(String::from("none")), Value::Number(0)],
+            vec![],
+        ),
+    ];
+    for (header, numbers) in &runs {
+        let input = || Input::queue(header.iter().chain(numbers).cloned());
+        let mut evaluator = Evaluator::new()
+            .with_input(input())
+            .with_output(Output::buffer());
+        evaluator.evaluate(&program, &Environment::empty())?;
+        println!("{:?}", evaluator.output());
+
+        let mut machine = Machine::new(program.clone())
+            .with_input(input())
+            .with_output(Output::buffer());
+        machine.trace()?;
+        println!("{:?}", machine.output());
+    }
+
+    // Lines of text hold literals, written just as they would be in a program.
+    let text = "\"big\"\n3\n-1\n9223372036854775807\n1\n";
+    let mut evaluator = Evaluator::new()
+        .with_input(Input::Reader(Box::new(Cursor::new(text))))
+        .with_output(Output::buffer());
+    println!("{:?}", evaluator.evaluate(&program, &Environment::empty())?);
+    println!("{:?}", evaluator.output());
+
+    let program = parse_statement("read xs; read s; ys = xs.append(s.length)")?;
+    let text = "[[1, 2], [], [-3]]\n\"two\\nlines\"\n";
+    let machine =
+        Machine::new(program.clone()).with_input(Input::Reader(Box::new(Cursor::new(text))));
+    let environment = machine.last().unwrap()?.environment;
+    println!("{}", environment);
+
+    // Running out of input, or finding something other than a literal, stops the program.
+    for text in &["[1]\n", "[1]\n\"open\n", "[1]\n1 + 1\n"] {
+        let error = Evaluator::new()
+            .with_input(Input::Reader(Box::new(Cursor::new(*text))))
+            .evaluate(&program, &Environment::empty())
+            .unwrap_err();
+        println!("{}", error);
+    }
+    Ok(())
+}
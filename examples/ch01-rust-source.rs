@@ -0,0 +1,85 @@
+//! Writes SIMPLE programs out as Rust, compiles them with `rustc`, and runs them next to big-step
+//! evaluation, given the same input.
+use std::error::Error;
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+use std::{env, fs};
+use uc::big_step::Evaluator;
+use uc::denotational::Statement;
+use uc::parser::parse_statement;
+use uc::{Environment, Input, Outcome, Output};
+
+/// What the generated `run` function needs around it: the `Value` type and a `main` which prints
+/// the variables it finishes with.
+const HARNESS: &str = include_str!("../tests/rust-source/harness.rs");
+
+/// What every compiled program is given on standard input.
+const INPUT: &str = "5\n\"ab\"\ntrue\n";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let programs = [
+        "x = 0; while (x < 5) { x = x + 2 }",
+        "n = 10; f = 1; while (1 < n) { f = f * n; n = n + -1 }",
+        "x = 3; if (x < 5) { small = true } else { small = false }; y = x * (x + 1)",
+        "s = \"stressed\"; r = \"\"; i = s.length; while (i > 0) { i = i - 1; r = r + s[i] }; \
+         m = s[2:5]; b = m < r && m != \"x\"",
+        "xs = [5, 2, 9, 1]; i = 1; while (i < xs.length) { key = xs[i]; j = i - 1; \
+         while (j >= 0 && xs[j] > key) { xs[j + 1] = xs[j]; j = j - 1 }; xs[j + 1] = key; \
+         i = i + 1 }; ys = xs[1:3].append([xs.length][0]); zs = [[true], []]",
+        "def fact(n) { if (n < 2) { r = 1 } else { r = n * fact(n - 1) }; return r }; \
+         def sum(xs) { i = 0; t = 0; while (i < xs.length) { t = t + xs[i]; i = i + 1 }; \
+         return t }; def zero() { return 0 }; x = fact(10); s = sum([1, fact(3), zero()])",
+        "def greet(name) { print \"hello, \" + name; return name.length }; n = greet(\"world\"); \
+         print [n, n * 2]; print n > 3; print \"\\\"quoted\\\"\"",
+        "read n; read s; read twice; if (twice) { t = s + s } else { t = s }; \
+         print n * t.length; print t",
+        "x = 1; let (x = 10) { y = x * 2; let (t = y) { w = t + x }; x = x + 1 }; v = x",
+        "x = 1; n = 0; while (true) { n = n + 1; let (x = n * n) { if (x % 2 == 0) { continue } \
+         else { do-nothing }; if (x > 40) { break } else { print x } } }; y = x",
+        "s = \"\"; for (i = 0; i < 6; i += 1) { unless (i % 3 != 0) { continue }; s += \"ab\"[i % 2] }; \
+         n = 1; do { n += n; if (n > 20) { break } else { do-nothing } } while (n < 100)",
+        "q = -17 / 5; r = -17 % 5; n = -q - -r; b = !(q == r) && q <= r || n != 1 && q >= 3",
+    ];
+    let directory = env::temp_dir().join("uc-rust-source");
+    fs::create_dir_all(&directory)?;
+
+    for (i, source) in programs.iter().enumerate() {
+        let program = parse_statement(source)?;
+        let rust = program.to_rust_source();
+        println!("{}\n{}", source, rust);
+
+        let file = directory.join(format!("program{}.rs", i));
+        let binary = directory.join(format!("program{}", i));
+        fs::write(&file, format!("{}\n{}", HARNESS, rust))?;
+        let status = Command::new("rustc")
+            .arg(&file)
+            .arg("-o")
+            .arg(&binary)
+            .status()?;
+        if !status.success() {
+            return Err("generated source failed to compile".into());
+        }
+        let mut child = Command::new(&binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        // A program which reads nothing may already have exited, closing its end of the pipe.
+        let _ = child.stdin.take().unwrap().write_all(INPUT.as_bytes());
+        let output = String::from_utf8(child.wait_with_output()?.stdout)?;
+
+        let mut evaluator = Evaluator::new()
+            .with_input(Input::Reader(Box::new(Cursor::new(INPUT))))
+            .with_output(Output::buffer());
+        let expected = match evaluator.evaluate(&program, &Environment::empty())? {
+            Outcome::Finished(environment) => environment,
+            Outcome::OutOfFuel { .. } => unreachable!("evaluation without a limit ran out of fuel"),
+        };
+        println!(
+            "compiled:\n{}big-step printed {:?} and finished with {}\n",
+            output,
+            evaluator.output().lines().unwrap(),
+            expected
+        );
+    }
+    Ok(())
+}
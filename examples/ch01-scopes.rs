@@ -0,0 +1,49 @@
+//! `let`: variables which exist only inside a block, hiding any of the same name until it ends.
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::signs::{AbstractEnvironment, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let programs = [
+        // The loop's counter is gone afterwards, leaving only the total behind.
+        "total = 0; let (i = 0) { while (i < 5) { total = total + i; i = i + 1 } }",
+        // Assigning to a hidden variable changes the one in the scope, not the one it hides.
+        "x = 1; let (x = 10) { y = x * 2; x = x + 1 }; z = x",
+        "let (a = 1) { let (a = a + 1) { b = a }; c = a }",
+        "x = 1; let (x = \"one\") { s = x + \"!\" }; n = x + 1",
+        "def double(n) { let (t = n * 2) { r = t }; return r }; \
+         let (t = 5) { x = double(t) + t }",
+    ];
+    for source in &programs {
+        let program = parse_statement(source)?;
+        types::check(&program, &TypeContext::empty()).unwrap();
+        println!(
+            "{}\n  big-step {}\n  denotational {}\n  small-step {}\n  signs {}",
+            program.to_s(),
+            program.evaluate(&Environment::empty())?,
+            program.to_closure()(&Environment::empty())?,
+            Machine::new(program.clone()).last().unwrap()?.environment,
+            program.abstract_evaluate(&AbstractEnvironment::empty())
+        );
+    }
+
+    // The trace shows each scope being entered, its variables alongside the globals, and left.
+    let program = parse_statement("x = 1; let (x = 2) { y = x }; z = x")?;
+    let trace = Machine::new(program).trace()?;
+    for step in &trace {
+        println!("{}", step);
+    }
+
+    // Outside its block, a variable declared by `let` is unbound.
+    let program = parse_statement("let (i = 0) { i = i + 1 }; j = i")?;
+    println!(
+        "{:?}",
+        types::check(&program, &TypeContext::empty()).unwrap_err()
+    );
+    println!("{}", program.evaluate(&Environment::empty()).unwrap_err());
+    Ok(())
+}
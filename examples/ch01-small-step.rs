@@ -1,12 +1,19 @@
-use uc::{small_step::*, Value::*};
+use uc::{ast::*, small_step::*, Environment, EvalError, Value::*};
+
+fn main() -> Result<(), EvalError> {
+    let increment = Assign::new("x", Add::new(Variable::new("x"), Number(2)));
+    let (reduced, environment) =
+        Stmt::from(increment.clone()).reduce(&Environment::empty().update("x", Number(1)))?;
+    println!("{:?} in {}", reduced, environment);
+
+    // The trace is plain data, so exact reduction sequences can be inspected.
+    let trace = Machine::new(Assign::new("x", Add::new(Number(1), Number(2)))).trace()?;
+    let statements: Vec<Stmt> = trace.into_iter().map(|step| step.statement).collect();
+    println!("{:?}", statements);
 
-fn main() {
     let mut machine = Machine::new(Sequence::new(
         Assign::new("x", Number(0)),
-        While::new(
-            LessThan::new(Variable::new("x"), Number(5)),
-            Assign::new("x", Add::new(Variable::new("x"), Number(2))),
-        ),
+        While::new(LessThan::new(Variable::new("x"), Number(5)), increment),
     ));
-    machine.run();
+    machine.run()
 }
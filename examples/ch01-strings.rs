@@ -0,0 +1,68 @@
+//! String values: literals, joining with `+`, `.length`, indexing, slicing and comparison.
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::Expression as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::{Expression, Machine};
+use uc::types::{TypeContext, Typed};
+use uc::{Environment, Printable, Value};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let greeting = Value::String(String::from("héllo"));
+    let environment = Environment::from([("s", greeting)]);
+    let context = TypeContext::from(&environment);
+
+    for source in &[
+        r#"s + ", world""#,
+        "s.length",
+        "s[1]",
+        "s[1:4]",
+        "s[3:1]",
+        r#"s[0] + s[4] == "ho""#,
+        r#""apple" < "banana" && s != "hello""#,
+        r#"(s + s).length * 2"#,
+        r#""tab\there""#,
+    ] {
+        let expression = parse_expression(source)?;
+        let mut reduced = expression.clone();
+        while reduced.is_reducible() {
+            reduced = reduced.reduce(&environment)?;
+        }
+        println!(
+            "{} = {}, {} by translation, {} by reduction: a {}",
+            source,
+            expression.evaluate(&environment)?.inspect(),
+            expression.to_closure()(&environment)?.inspect(),
+            reduced.inspect(),
+            expression.type_of(&context).unwrap()
+        );
+    }
+
+    // `inspect` quotes and escapes, so the output reads back as the same literal.
+    let tricky = Value::String(String::from("say \"hi\"\\\n"));
+    println!(
+        "{} reads back as {}",
+        tricky.inspect(),
+        parse_expression(&tricky.to_s())?.inspect()
+    );
+
+    for source in &["s[5]", "s[-1]", "s[0:6]", "s + 1", "1 + s"] {
+        let error = parse_expression(source)?
+            .evaluate(&environment)
+            .unwrap_err();
+        println!("{}: {}", source, error);
+    }
+    for source in &["s + 1", "s.length.length", "s[true]", "s < 1"] {
+        let errors = parse_expression(source)?.type_of(&context).unwrap_err();
+        println!("{}: {}", source, errors[0]);
+    }
+    for source in &[r#"s = "unterminated"#, r#"s = "bad \q escape""#] {
+        println!("{}", parse_statement(source).unwrap_err());
+    }
+
+    let program = parse_statement(
+        r#"s = "stressed"; r = ""; i = s.length; while (i > 0) { i = i - 1; r = r + s[i] }"#,
+    )?;
+    println!("{}", program.evaluate(&Environment::empty())?);
+    Machine::new(program).run()?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! `for`, `do`-`while`, `unless` and `+=`: each prints as written, and runs exactly as the core
+//! statements it stands for.
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::signs::{AbstractEnvironment, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Printable};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Each program, and the same program written without sugar.
+    let programs = [
+        (
+            "total = 0; for (i = 0; i < 10; i += 1) { \
+             if (i % 3 == 0) { continue } else { do-nothing }; total += i }",
+            "total = 0; i = 0; while (i < 10) { \
+             if (i % 3 == 0) { i = i + 1; continue } else { do-nothing }; total = total + i; \
+             i = i + 1 }",
+        ),
+        (
+            "n = 10; do { n += 1 } while (n < 5)",
+            "n = 10; n = n + 1; while (n < 5) { n = n + 1 }",
+        ),
+        (
+            "i = 0; evens = []; do { i += 1; unless (i % 2 == 0) { continue }; \
+             evens = evens.append(i); if (i >= 8) { break } else { do-nothing } } while (true)",
+            "i = 0; evens = []; while (true) { i = i + 1; \
+             if (i % 2 == 0) { do-nothing } else { continue }; evens = evens.append(i); \
+             if (i >= 8) { break } else { do-nothing } }",
+        ),
+        (
+            "s = \"\"; for (i = 0; i < 5; i += 1) { s += \"ab\"[i % 2] }; \
+             unless (s.length > 3) { short = true }; unless (s == \"\") { last = s[4] }",
+            "s = \"\"; i = 0; while (i < 5) { s = s + \"ab\"[i % 2]; i = i + 1 }; \
+             if (s.length > 3) { do-nothing } else { short = true }; \
+             if (s == \"\") { do-nothing } else { last = s[4] }",
+        ),
+        // The inner loop's `continue` is its own, and runs only its own step.
+        (
+            "pairs = []; for (a = 1; a < 4; a += 1) { for (b = 1; b < 4; b += 1) { \
+             if (a == b) { continue } else { do-nothing }; pairs = pairs.append([a, b]) } }",
+            "pairs = []; a = 1; while (a < 4) { b = 1; while (b < 4) { \
+             if (a == b) { b = b + 1; continue } else { do-nothing }; \
+             pairs = pairs.append([a, b]); b = b + 1 }; a = a + 1 }",
+        ),
+    ];
+    for (source, core) in &programs {
+        let program = parse_statement(source)?;
+        let core = parse_statement(core)?;
+        types::check(&program, &TypeContext::empty()).unwrap();
+        println!(
+            "{}\n  big-step {}\n  as core {}\n  denotational {}\n  small-step {}\n  signs {}",
+            program.to_s(),
+            program.evaluate(&Environment::empty())?,
+            core.evaluate(&Environment::empty())?,
+            program.to_closure()(&Environment::empty())?,
+            Machine::new(program.clone()).last().unwrap()?.environment,
+            program.abstract_evaluate(&AbstractEnvironment::empty())
+        );
+    }
+
+    // The machine shows the sugar as written, until the step which swaps it for the core.
+    let program = parse_statement("x = 1; unless (x > 2) { x += 2 }")?;
+    let trace = Machine::new(program).trace()?;
+    for step in &trace {
+        println!("{}", step);
+    }
+
+    // A `do` body runs at least once, so what it assigns is sure to exist afterwards.
+    let program = parse_statement("do { read x; n = x * 2 } while (x < 0); y = n + 1")?;
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    println!("y is a {:?}", context.get("y"));
+    let after = parse_statement("x = -1; do { x += 3 } while (x < 0)")?
+        .abstract_evaluate(&AbstractEnvironment::empty());
+    println!("{}", after);
+
+    // Problems are reported against the program as written, and only once, even though a `do`
+    // body appears twice in the core statement.
+    let program = parse_statement("x = 1; do { x += \"!\" } while (x < 3); unless (x) { break }")?;
+    let errors = types::check(&program, &TypeContext::empty()).unwrap_err();
+    for error in &errors {
+        println!("{}", error);
+    }
+    Ok(())
+}
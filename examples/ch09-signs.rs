@@ -0,0 +1,36 @@
+use uc::parser::{parse_expression, parse_statement};
+use uc::signs::{AbstractEnvironment, AbstractValue, Expression, Sign, Statement};
+use uc::{Environment, Printable, Value::*};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", Sign::Positive * Sign::Negative);
+    println!("{}", Sign::Positive + Sign::Negative);
+
+    let environment = AbstractEnvironment::from(&Environment::empty().update("x", Number(-3)));
+    for source in &[
+        "x * x",
+        "x * x + 7",
+        "x + 7",
+        "x < 0",
+        "x * x < 0",
+        "-x",
+        "x - 7",
+        "x / 2",
+        "x == 0 || x > 0",
+    ] {
+        let expression = parse_expression(source)?;
+        println!(
+            "{} is {}",
+            expression.inspect(),
+            expression.abstract_evaluate(&environment)
+        );
+    }
+
+    // Without knowing `n`, can we say anything about `total` afterwards?
+    let program =
+        parse_statement("total = 1; i = 0; while (i < n) { total = total * 2; i = i + 1 }")?;
+    let unknown_n = AbstractEnvironment::empty().update("n", AbstractValue::Number(Sign::Unknown));
+    let after = program.abstract_evaluate(&unknown_n);
+    println!("{} => {}", program.inspect(), after);
+    Ok(())
+}
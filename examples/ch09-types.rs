@@ -0,0 +1,36 @@
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{Type, TypeContext, Typed};
+use uc::Printable;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let context = TypeContext::empty().update("x", Type::Number);
+    for source in &["x + 1", "x < 1", "x + true", "(true + 1) * (y < false)"] {
+        let expression = uc::parser::parse_expression(source)?;
+        match expression.type_of(&context) {
+            Ok(ty) => println!("{}: {}", expression.inspect(), ty),
+            Err(errors) => {
+                println!("{} is ill-typed:", expression.inspect());
+                for error in errors {
+                    println!("  {}", error);
+                }
+            }
+        }
+    }
+
+    // Ill-typed programs are turned away before a single step is taken.
+    for source in &[
+        "x = 0; while (x < 5) { x = x + 2 }",
+        "x = 0; if (x) { x = true } else { do-nothing }",
+    ] {
+        match Machine::checked(parse_statement(source)?) {
+            Ok(mut machine) => machine.run()?,
+            Err(errors) => {
+                for error in errors {
+                    println!("rejected: {}", error);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1015 @@
+//! The abstract syntax of SIMPLE. Every semantics is an interpreter over these same trees, so a
+//! program can be built (or parsed) once and then printed, reduced, evaluated or translated.
+use crate::{desugar, infix, postfix, prefix, Environment, Printable, Value};
+use std::iter;
+use std::mem;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Value(Value),
+    Add(Add),
+    Subtract(Subtract),
+    Multiply(Multiply),
+    Divide(Divide),
+    Modulo(Modulo),
+    Negate(Negate),
+    Equals(Equals),
+    NotEquals(NotEquals),
+    LessThan(LessThan),
+    GreaterThan(GreaterThan),
+    LessOrEqual(LessOrEqual),
+    GreaterOrEqual(GreaterOrEqual),
+    And(And),
+    Or(Or),
+    Not(Not),
+    Length(Length),
+    Index(Index),
+    Slice(Slice),
+    List(List),
+    Append(Append),
+    Call(Call),
+    Frame(Frame),
+    Variable(Variable),
+}
+
+impl Expr {
+    pub fn as_value(&self) -> Option<&Value> {
+        match self {
+            Expr::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Printable for Expr {
+    fn to_s(&self) -> String {
+        let precedence = self.precedence();
+        match self {
+            Expr::Value(value) => value.to_s(),
+            Expr::Add(Add(left, right)) => infix(&**left, "+", &**right, precedence),
+            Expr::Subtract(Subtract(left, right)) => infix(&**left, "-", &**right, precedence),
+            Expr::Multiply(Multiply(left, right)) => infix(&**left, "*", &**right, precedence),
+            Expr::Divide(Divide(left, right)) => infix(&**left, "/", &**right, precedence),
+            Expr::Modulo(Modulo(left, right)) => infix(&**left, "%", &**right, precedence),
+            // `-3` reads back as a literal, so a negated literal needs parentheses.
+            Expr::Negate(Negate(operand)) => match **operand {
+                Expr::Value(Value::Number(_)) | Expr::Value(Value::BigNumber(_)) => {
+                    format!("-({})", operand.to_s())
+                }
+                _ => prefix("-", &**operand, precedence),
+            },
+            Expr::Equals(Equals(left, right)) => infix(&**left, "==", &**right, precedence),
+            Expr::NotEquals(NotEquals(left, right)) => infix(&**left, "!=", &**right, precedence),
+            Expr::LessThan(LessThan(left, right)) => infix(&**left, "<", &**right, precedence),
+            Expr::GreaterThan(GreaterThan(left, right)) => {
+                infix(&**left, ">", &**right, precedence)
+            }
+            Expr::LessOrEqual(LessOrEqual(left, right)) => {
+                infix(&**left, "<=", &**right, precedence)
+            }
+            Expr::GreaterOrEqual(GreaterOrEqual(left, right)) => {
+                infix(&**left, ">=", &**right, precedence)
+            }
+            Expr::And(And(left, right)) => infix(&**left, "&&", &**right, precedence),
+            Expr::Or(Or(left, right)) => infix(&**left, "||", &**right, precedence),
+            Expr::Not(Not(operand)) => prefix("!", &**operand, precedence),
+            Expr::Length(Length(operand)) => postfix(&**operand, ".length", precedence),
+            Expr::Index(Index(operand, index)) => {
+                postfix(&**operand, &format!("[{}]", index.to_s()), precedence)
+            }
+            Expr::Slice(Slice(operand, start, end)) => postfix(
+                &**operand,
+                &format!("[{}:{}]", start.to_s(), end.to_s()),
+                precedence,
+            ),
+            Expr::List(List(elements)) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|element| element.to_s())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Append(Append(list, element)) => {
+                postfix(&**list, &format!(".append({})", element.to_s()), precedence)
+            }
+            Expr::Call(Call(name, arguments)) => format!(
+                "{}({})",
+                name,
+                arguments
+                    .iter()
+                    .map(|argument| argument.to_s())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            // Only the frame's own variables are shown; the functions it can call are not.
+            Expr::Frame(Frame(name, locals, body, result)) => format!(
+                "{}({}) {{ {} }}",
+                name,
+                locals
+                    .iter()
+                    .filter(|(_, value)| !matches!(value, Value::Function(_)))
+                    .map(|(name, value)| format!("{}={}", name, value.to_s()))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                function_body(body, result)
+            ),
+            Expr::Variable(Variable(name)) => name.clone(),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(_) => 1,
+            Expr::And(_) => 2,
+            Expr::Equals(_) | Expr::NotEquals(_) => 3,
+            Expr::LessThan(_)
+            | Expr::GreaterThan(_)
+            | Expr::LessOrEqual(_)
+            | Expr::GreaterOrEqual(_) => 4,
+            Expr::Add(_) | Expr::Subtract(_) => 5,
+            Expr::Multiply(_) | Expr::Divide(_) | Expr::Modulo(_) => 6,
+            Expr::Negate(_) | Expr::Not(_) => 7,
+            Expr::Length(_) | Expr::Index(_) | Expr::Slice(_) | Expr::Append(_) => 8,
+            Expr::Value(_) | Expr::List(_) | Expr::Call(_) | Expr::Frame(_) | Expr::Variable(_) => {
+                u8::MAX
+            }
+        }
+    }
+}
+
+impl From<Value> for Expr {
+    fn from(expression: Value) -> Self {
+        Expr::Value(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Add(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Add {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Add> for Expr {
+    fn from(expression: Add) -> Self {
+        Expr::Add(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Subtract(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Subtract {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Subtract> for Expr {
+    fn from(expression: Subtract) -> Self {
+        Expr::Subtract(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Multiply(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Multiply {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Multiply> for Expr {
+    fn from(expression: Multiply) -> Self {
+        Expr::Multiply(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Divide(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Divide {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Divide> for Expr {
+    fn from(expression: Divide) -> Self {
+        Expr::Divide(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Modulo(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Modulo {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Modulo> for Expr {
+    fn from(expression: Modulo) -> Self {
+        Expr::Modulo(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Negate(pub Rc<Expr>);
+
+impl Negate {
+    pub fn new<T: Into<Expr>>(operand: T) -> Self {
+        Self(Rc::new(operand.into()))
+    }
+}
+
+impl From<Negate> for Expr {
+    fn from(expression: Negate) -> Self {
+        Expr::Negate(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Equals(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Equals {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Equals> for Expr {
+    fn from(expression: Equals) -> Self {
+        Expr::Equals(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NotEquals(pub Rc<Expr>, pub Rc<Expr>);
+
+impl NotEquals {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<NotEquals> for Expr {
+    fn from(expression: NotEquals) -> Self {
+        Expr::NotEquals(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LessThan(pub Rc<Expr>, pub Rc<Expr>);
+
+impl LessThan {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<LessThan> for Expr {
+    fn from(expression: LessThan) -> Self {
+        Expr::LessThan(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreaterThan(pub Rc<Expr>, pub Rc<Expr>);
+
+impl GreaterThan {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<GreaterThan> for Expr {
+    fn from(expression: GreaterThan) -> Self {
+        Expr::GreaterThan(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LessOrEqual(pub Rc<Expr>, pub Rc<Expr>);
+
+impl LessOrEqual {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<LessOrEqual> for Expr {
+    fn from(expression: LessOrEqual) -> Self {
+        Expr::LessOrEqual(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreaterOrEqual(pub Rc<Expr>, pub Rc<Expr>);
+
+impl GreaterOrEqual {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<GreaterOrEqual> for Expr {
+    fn from(expression: GreaterOrEqual) -> Self {
+        Expr::GreaterOrEqual(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct And(pub Rc<Expr>, pub Rc<Expr>);
+
+impl And {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<And> for Expr {
+    fn from(expression: And) -> Self {
+        Expr::And(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Or(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Or {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
+        Self(Rc::new(left.into()), Rc::new(right.into()))
+    }
+}
+
+impl From<Or> for Expr {
+    fn from(expression: Or) -> Self {
+        Expr::Or(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Not(pub Rc<Expr>);
+
+impl Not {
+    pub fn new<T: Into<Expr>>(operand: T) -> Self {
+        Self(Rc::new(operand.into()))
+    }
+}
+
+impl From<Not> for Expr {
+    fn from(expression: Not) -> Self {
+        Expr::Not(expression)
+    }
+}
+
+/// The number of characters in a string, or of elements in a list.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Length(pub Rc<Expr>);
+
+impl Length {
+    pub fn new<T: Into<Expr>>(operand: T) -> Self {
+        Self(Rc::new(operand.into()))
+    }
+}
+
+impl From<Length> for Expr {
+    fn from(expression: Length) -> Self {
+        Expr::Length(expression)
+    }
+}
+
+/// The character at a position in a string, or the element at a position in a list, counting
+/// from zero.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Index(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Index {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(operand: T1, index: T2) -> Self {
+        Self(Rc::new(operand.into()), Rc::new(index.into()))
+    }
+}
+
+impl From<Index> for Expr {
+    fn from(expression: Index) -> Self {
+        Expr::Index(expression)
+    }
+}
+
+/// The part of a string or list from a start position up to, but not including, an end position.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Slice(pub Rc<Expr>, pub Rc<Expr>, pub Rc<Expr>);
+
+impl Slice {
+    pub fn new<T1, T2, T3>(operand: T1, start: T2, end: T3) -> Self
+    where
+        T1: Into<Expr>,
+        T2: Into<Expr>,
+        T3: Into<Expr>,
+    {
+        Self(
+            Rc::new(operand.into()),
+            Rc::new(start.into()),
+            Rc::new(end.into()),
+        )
+    }
+}
+
+impl From<Slice> for Expr {
+    fn from(expression: Slice) -> Self {
+        Expr::Slice(expression)
+    }
+}
+
+/// A list literal, whose elements are evaluated from left to right.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct List(pub Vec<Rc<Expr>>);
+
+impl List {
+    pub fn new<T: Into<Expr>, I: IntoIterator<Item = T>>(elements: I) -> Self {
+        Self(
+            elements
+                .into_iter()
+                .map(|element| Rc::new(element.into()))
+                .collect(),
+        )
+    }
+}
+
+impl From<List> for Expr {
+    fn from(expression: List) -> Self {
+        Expr::List(expression)
+    }
+}
+
+/// A copy of a list with one more element on the end. The list itself is left alone.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Append(pub Rc<Expr>, pub Rc<Expr>);
+
+impl Append {
+    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(list: T1, element: T2) -> Self {
+        Self(Rc::new(list.into()), Rc::new(element.into()))
+    }
+}
+
+impl From<Append> for Expr {
+    fn from(expression: Append) -> Self {
+        Expr::Append(expression)
+    }
+}
+
+/// A call of the function bound to a name, with arguments evaluated from left to right.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Call(pub String, pub Vec<Rc<Expr>>);
+
+impl Call {
+    pub fn new<S, T, I>(name: S, arguments: I) -> Self
+    where
+        S: Into<String>,
+        T: Into<Expr>,
+        I: IntoIterator<Item = T>,
+    {
+        Self(
+            name.into(),
+            arguments
+                .into_iter()
+                .map(|argument| Rc::new(argument.into()))
+                .collect(),
+        )
+    }
+}
+
+impl From<Call> for Expr {
+    fn from(expression: Call) -> Self {
+        Expr::Call(expression)
+    }
+}
+
+/// A call part way through: the name of the function, its local variables, and what is left of
+/// its body and result. Only reduction creates these, so that a trace shows the call in progress.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Frame(pub String, pub Environment, pub Rc<Stmt>, pub Rc<Expr>);
+
+impl From<Frame> for Expr {
+    fn from(expression: Frame) -> Self {
+        Expr::Frame(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Variable(pub String);
+
+impl Variable {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<Variable> for Expr {
+    fn from(expression: Variable) -> Self {
+        Expr::Variable(expression)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Stmt {
+    DoNothing,
+    Assign(Assign),
+    AssignIndex(AssignIndex),
+    Break,
+    Continue,
+    FunctionDef(FunctionDef),
+    If(If),
+    Iteration(Iteration),
+    Let(Let),
+    Print(Print),
+    Read(Read),
+    Scope(Scope),
+    Sequence(Sequence),
+    Sugared(Sugared),
+    While(While),
+}
+
+impl Stmt {
+    /// Used exclusively to know when statements are "done."
+    pub fn does_nothing(&self) -> bool {
+        matches!(self, Stmt::DoNothing)
+    }
+
+    /// Whether this is a `break` or `continue`, leaving the statements around it for the innermost
+    /// loop.
+    pub fn is_jump(&self) -> bool {
+        matches!(self, Stmt::Break | Stmt::Continue)
+    }
+}
+
+impl Printable for Stmt {
+    fn to_s(&self) -> String {
+        match self {
+            Stmt::DoNothing => String::from("do-nothing"),
+            Stmt::Assign(Assign(name, expression)) => format!("{} = {}", name, expression.to_s()),
+            Stmt::AssignIndex(AssignIndex(name, index, expression)) => {
+                format!("{}[{}] = {}", name, index.to_s(), expression.to_s())
+            }
+            Stmt::Break => String::from("break"),
+            Stmt::Continue => String::from("continue"),
+            Stmt::FunctionDef(function) => function.to_s(),
+            Stmt::If(If(condition, consequence, alternative)) => format!(
+                "if ({}) {{ {} }} else {{ {} }}",
+                condition.to_s(),
+                consequence.to_s(),
+                alternative.to_s()
+            ),
+            Stmt::Iteration(Iteration(body, While(condition, original))) => format!(
+                "{{ {} }}; while ({}) {{ {} }}",
+                body.to_s(),
+                condition.to_s(),
+                original.to_s()
+            ),
+            Stmt::Let(Let(name, expression, body)) => format!(
+                "let ({} = {}) {{ {} }}",
+                name,
+                expression.to_s(),
+                body.to_s()
+            ),
+            Stmt::Scope(Scope(name, body)) => format!("let {} {{ {} }}", name, body.to_s()),
+            Stmt::Print(Print(expression)) => format!("print {}", expression.to_s()),
+            Stmt::Read(Read(name)) => format!("read {}", name),
+            Stmt::Sequence(sequence) => sequence
+                .statements()
+                .map(Printable::to_s)
+                .collect::<Vec<String>>()
+                .join("; "),
+            Stmt::Sugared(sugared) => sugared.sugar().to_s(),
+            Stmt::While(While(condition, body)) => {
+                format!("while ({}) {{ {} }}", condition.to_s(), body.to_s())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DoNothing;
+
+impl From<DoNothing> for Stmt {
+    fn from(_: DoNothing) -> Self {
+        Stmt::DoNothing
+    }
+}
+
+/// Leaves the innermost loop at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Break;
+
+impl From<Break> for Stmt {
+    fn from(_: Break) -> Self {
+        Stmt::Break
+    }
+}
+
+/// Skips the rest of the body of the innermost loop, going on to test its condition again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Continue;
+
+impl From<Continue> for Stmt {
+    fn from(_: Continue) -> Self {
+        Stmt::Continue
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Assign(pub String, pub Expr);
+
+impl Assign {
+    pub fn new<S: Into<String>, E: Into<Expr>>(name: S, expression: E) -> Self {
+        Self(name.into(), expression.into())
+    }
+}
+
+impl From<Assign> for Stmt {
+    fn from(statement: Assign) -> Self {
+        Stmt::Assign(statement)
+    }
+}
+
+/// Replaces one element of the list held by a variable, e.g. `xs[i] = e`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssignIndex(pub String, pub Expr, pub Expr);
+
+impl AssignIndex {
+    pub fn new<S, E1, E2>(name: S, index: E1, expression: E2) -> Self
+    where
+        S: Into<String>,
+        E1: Into<Expr>,
+        E2: Into<Expr>,
+    {
+        Self(name.into(), index.into(), expression.into())
+    }
+}
+
+impl From<AssignIndex> for Stmt {
+    fn from(statement: AssignIndex) -> Self {
+        Stmt::AssignIndex(statement)
+    }
+}
+
+/// Defines a function: its name, its parameters, a body to run and an expression giving its
+/// result, e.g. `def square(x) { y = x * x; return y }`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FunctionDef(pub String, pub Vec<String>, pub Rc<Stmt>, pub Rc<Expr>);
+
+impl FunctionDef {
+    pub fn new<S, P, S1, E>(name: S, parameters: P, body: S1, result: E) -> Self
+    where
+        S: Into<String>,
+        P: IntoIterator,
+        P::Item: Into<String>,
+        S1: Into<Stmt>,
+        E: Into<Expr>,
+    {
+        Self(
+            name.into(),
+            parameters.into_iter().map(Into::into).collect(),
+            Rc::new(body.into()),
+            Rc::new(result.into()),
+        )
+    }
+}
+
+impl Printable for FunctionDef {
+    fn to_s(&self) -> String {
+        let FunctionDef(name, parameters, body, result) = self;
+        format!(
+            "def {}({}) {{ {} }}",
+            name,
+            parameters.join(", "),
+            function_body(body, result)
+        )
+    }
+}
+
+impl From<FunctionDef> for Stmt {
+    fn from(statement: FunctionDef) -> Self {
+        Stmt::FunctionDef(statement)
+    }
+}
+
+/// Prints the inside of a function's braces, leaving out a body which does nothing.
+fn function_body(body: &Stmt, result: &Expr) -> String {
+    if body.does_nothing() {
+        format!("return {}", result.to_s())
+    } else {
+        format!("{}; return {}", body.to_s(), result.to_s())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct If(pub Expr, pub Rc<Stmt>, pub Rc<Stmt>);
+
+impl If {
+    pub fn new<E: Into<Expr>, S1: Into<Stmt>, S2: Into<Stmt>>(
+        condition: E,
+        consequence: S1,
+        alternative: S2,
+    ) -> Self {
+        Self(
+            condition.into(),
+            Rc::new(consequence.into()),
+            Rc::new(alternative.into()),
+        )
+    }
+}
+
+impl From<If> for Stmt {
+    fn from(statement: If) -> Self {
+        Stmt::If(statement)
+    }
+}
+
+/// Declares a variable visible only in a body, e.g. `let (i = 0) { while (i < 3) { i = i + 1 } }`.
+/// Once the body is done, the variable is gone, and any it hid is visible again.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Let(pub String, pub Expr, pub Rc<Stmt>);
+
+impl Let {
+    pub fn new<S: Into<String>, E: Into<Expr>, B: Into<Stmt>>(
+        name: S,
+        expression: E,
+        body: B,
+    ) -> Self {
+        Self(name.into(), expression.into(), Rc::new(body.into()))
+    }
+}
+
+impl From<Let> for Stmt {
+    fn from(statement: Let) -> Self {
+        Stmt::Let(statement)
+    }
+}
+
+/// The body of a `Let` part way through, its variable held by the innermost scope of the
+/// environment, e.g. `let x { y = x }`. Reduction creates these, so that a trace shows where a scope
+/// begins and ends; one parsed on its own has no scope to leave.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Scope(pub String, pub Rc<Stmt>);
+
+impl Scope {
+    pub fn new<S: Into<String>, S1: Into<Stmt>>(name: S, body: S1) -> Self {
+        Self(name.into(), Rc::new(body.into()))
+    }
+}
+
+impl From<Scope> for Stmt {
+    fn from(statement: Scope) -> Self {
+        Stmt::Scope(statement)
+    }
+}
+
+/// Writes a value to the run's `Output` on a line of its own, e.g. `print x`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Print(pub Expr);
+
+impl Print {
+    pub fn new<E: Into<Expr>>(expression: E) -> Self {
+        Self(expression.into())
+    }
+}
+
+impl From<Print> for Stmt {
+    fn from(statement: Print) -> Self {
+        Stmt::Print(statement)
+    }
+}
+
+/// Assigns the next value from the run's `Input` to a variable, e.g. `read x`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Read(pub String);
+
+impl Read {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<Read> for Stmt {
+    fn from(statement: Read) -> Self {
+        Stmt::Read(statement)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sequence(pub Rc<Stmt>, pub Rc<Stmt>);
+
+impl Sequence {
+    pub fn new<S1: Into<Stmt>, S2: Into<Stmt>>(first: S1, second: S2) -> Self {
+        Self(Rc::new(first.into()), Rc::new(second.into()))
+    }
+
+    /// The statements of a run in order, followed in a loop since each is nested in the last.
+    pub(crate) fn statements(&self) -> impl Iterator<Item = &Stmt> {
+        let mut rest = Some(&*self.1);
+        iter::once(&*self.0).chain(iter::from_fn(move || match rest.take()? {
+            Stmt::Sequence(Sequence(first, second)) => {
+                rest = Some(second);
+                Some(&**first)
+            }
+            last => Some(last),
+        }))
+    }
+}
+
+impl From<Sequence> for Stmt {
+    fn from(statement: Sequence) -> Self {
+        Stmt::Sequence(statement)
+    }
+}
+
+/// Joins statements into a `Sequence` nested to the right, as `a; b; c` is `a; (b; c)`. An empty
+/// list is `do-nothing`.
+pub(crate) fn sequence(statements: Vec<Stmt>) -> Stmt {
+    statements
+        .into_iter()
+        .rev()
+        .reduce(|rest, first| Sequence::new(first, rest).into())
+        .unwrap_or(Stmt::DoNothing)
+}
+
+/// Drops the statements after this one in a loop, since dropping them one inside another would use
+/// up the stack for a long program.
+impl Drop for Sequence {
+    fn drop(&mut self) {
+        let mut rest = mem::replace(&mut self.1, Rc::new(Stmt::DoNothing));
+        while let Ok(Stmt::Sequence(mut sequence)) = Rc::try_unwrap(rest) {
+            rest = mem::replace(&mut sequence.1, Rc::new(Stmt::DoNothing));
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct While(pub Expr, pub Rc<Stmt>);
+
+impl While {
+    pub fn new<E: Into<Expr>, S: Into<Stmt>>(condition: E, body: S) -> Self {
+        Self(condition.into(), Rc::new(body.into()))
+    }
+}
+
+impl From<While> for Stmt {
+    fn from(statement: While) -> Self {
+        Stmt::While(statement)
+    }
+}
+
+/// A pass through the body of a `While` part way through, followed by the loop itself, e.g.
+/// `{ x = x + 1 }; while (x < 5) { x = x + 1 }`. Reduction creates these, so that a `break` or
+/// `continue` knows where its loop is, and so does lowering a `DoWhile`, whose first pass comes
+/// before any test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Iteration(pub Rc<Stmt>, pub While);
+
+impl From<Iteration> for Stmt {
+    fn from(statement: Iteration) -> Self {
+        Stmt::Iteration(statement)
+    }
+}
+
+/// `for (init; condition; step) { body }`: runs `init` once, then the body followed by the step
+/// for as long as the condition holds. A `continue` in the body still runs the step.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct For(pub Rc<Stmt>, pub Expr, pub Rc<Stmt>, pub Rc<Stmt>);
+
+impl For {
+    pub fn new<S1, E, S2, S3>(init: S1, condition: E, step: S2, body: S3) -> Self
+    where
+        S1: Into<Stmt>,
+        E: Into<Expr>,
+        S2: Into<Stmt>,
+        S3: Into<Stmt>,
+    {
+        Self(
+            Rc::new(init.into()),
+            condition.into(),
+            Rc::new(step.into()),
+            Rc::new(body.into()),
+        )
+    }
+}
+
+impl From<For> for Stmt {
+    fn from(statement: For) -> Self {
+        Sugared::new(Sugar::For(statement)).into()
+    }
+}
+
+/// `do { body } while (condition)`: like `While`, but the body runs once before the first test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DoWhile(pub Rc<Stmt>, pub Expr);
+
+impl DoWhile {
+    pub fn new<S: Into<Stmt>, E: Into<Expr>>(body: S, condition: E) -> Self {
+        Self(Rc::new(body.into()), condition.into())
+    }
+}
+
+impl From<DoWhile> for Stmt {
+    fn from(statement: DoWhile) -> Self {
+        Sugared::new(Sugar::DoWhile(statement)).into()
+    }
+}
+
+/// `unless (condition) { body }`: runs the body only if the condition is false.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Unless(pub Expr, pub Rc<Stmt>);
+
+impl Unless {
+    pub fn new<E: Into<Expr>, S: Into<Stmt>>(condition: E, body: S) -> Self {
+        Self(condition.into(), Rc::new(body.into()))
+    }
+}
+
+impl From<Unless> for Stmt {
+    fn from(statement: Unless) -> Self {
+        Sugared::new(Sugar::Unless(statement)).into()
+    }
+}
+
+/// `x += e`, adding to the value of a variable.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AddAssign(pub String, pub Expr);
+
+impl AddAssign {
+    pub fn new<S: Into<String>, E: Into<Expr>>(name: S, expression: E) -> Self {
+        Self(name.into(), expression.into())
+    }
+}
+
+impl From<AddAssign> for Stmt {
+    fn from(statement: AddAssign) -> Self {
+        Sugared::new(Sugar::AddAssign(statement)).into()
+    }
+}
+
+/// Statements which are only a more convenient way of writing ones made of the core statements.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sugar {
+    AddAssign(AddAssign),
+    DoWhile(DoWhile),
+    For(For),
+    Unless(Unless),
+}
+
+impl Printable for Sugar {
+    fn to_s(&self) -> String {
+        match self {
+            Sugar::AddAssign(AddAssign(name, expression)) => {
+                format!("{} += {}", name, expression.to_s())
+            }
+            Sugar::DoWhile(DoWhile(body, condition)) => {
+                format!("do {{ {} }} while ({})", body.to_s(), condition.to_s())
+            }
+            Sugar::For(For(init, condition, step, body)) => format!(
+                "for ({}; {}; {}) {{ {} }}",
+                init.to_s(),
+                condition.to_s(),
+                step.to_s(),
+                body.to_s()
+            ),
+            Sugar::Unless(Unless(condition, body)) => {
+                format!("unless ({}) {{ {} }}", condition.to_s(), body.to_s())
+            }
+        }
+    }
+}
+
+/// A statement as it was written, along with the core statement it stands for, which is what every
+/// semantics runs in its place. The core statement is worked out once, when this is built.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sugared(Sugar, Rc<Stmt>);
+
+impl Sugared {
+    pub fn new(sugar: Sugar) -> Self {
+        let core = desugar::lower(&sugar);
+        Self(sugar, Rc::new(core))
+    }
+
+    pub fn sugar(&self) -> &Sugar {
+        &self.0
+    }
+
+    pub fn core(&self) -> &Rc<Stmt> {
+        &self.1
+    }
+}
+
+impl From<Sugared> for Stmt {
+    fn from(statement: Sugared) -> Self {
+        Stmt::Sugared(statement)
+    }
+}
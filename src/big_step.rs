@@ -3,3 +3,74 @@ mod statements;
 
 pub use expressions::*;
 pub use statements::*;
+
+use crate::ast::Stmt;
+use crate::{Environment, EvalError, Input, Outcome, Output, Overflow, Runtime};
+
+/// Big-step evaluation with its settings, for when plain `Statement::evaluate` is not enough.
+#[derive(Debug, Default)]
+pub struct Evaluator {
+    fuel: Option<usize>,
+    runtime: Runtime,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops evaluation once `fuel` steps have been taken.
+    pub fn with_fuel(mut self, fuel: usize) -> Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    /// Chooses what arithmetic does when it overflows; `Overflow::Promote` unless told otherwise.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.runtime.overflow = overflow;
+        self
+    }
+
+    /// Chooses where `Print` statements write; standard output unless told otherwise.
+    pub fn with_output(mut self, output: Output) -> Self {
+        self.runtime.output = output;
+        self
+    }
+
+    /// Chooses where `Read` statements take values from; standard input unless told otherwise.
+    pub fn with_input(mut self, input: Input) -> Self {
+        self.runtime.input = input;
+        self
+    }
+
+    /// Chooses how many calls may be unfinished at once; `DEFAULT_CALL_DEPTH` unless told
+    /// otherwise.
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.runtime.max_call_depth = depth;
+        self
+    }
+
+    /// Where `Print` statements have been writing, e.g. to read back a `Buffer`.
+    pub fn output(&self) -> &Output {
+        &self.runtime.output
+    }
+
+    pub fn evaluate(
+        &mut self,
+        statement: &Stmt,
+        environment: &Environment,
+    ) -> Result<Outcome, EvalError> {
+        let mut fuel = Fuel {
+            limit: self.fuel,
+            used: 0,
+        };
+        match execute_statement(statement, environment, &mut self.runtime, &mut fuel) {
+            Ok(environment) => Ok(Outcome::Finished(environment)),
+            Err(Halt::Error(error)) => Err(error),
+            Err(Halt::OutOfFuel(environment)) => Ok(Outcome::OutOfFuel {
+                steps: fuel.used,
+                environment,
+            }),
+        }
+    }
+}
@@ -1,151 +1,223 @@
-use crate::{Environment, Printable, Value};
+use crate::ast::Stmt;
+use crate::ast::{
+    Add, And, Append, Call, Divide, Equals, Expr, Frame, FunctionDef, GreaterOrEqual, GreaterThan,
+    Index, Length, LessOrEqual, LessThan, List, Modulo, Multiply, Negate, Not, NotEquals, Or,
+    Slice, Subtract, Variable,
+};
+use crate::big_step::statements::{execute, pop, Fuel, Halt, Work};
+use crate::operators::{self, Binary, Unary};
+use crate::{Environment, EvalError, Runtime, Value};
+use std::mem;
 use std::rc::Rc;
 
-/// Boxed version of an `Expression` (so they can be passed around generically).
-pub type Expr = Rc<Box<dyn Expression>>;
-
-pub trait Expression: Printable {
-    fn evaluate(&self, environment: &Environment) -> Expr;
-    fn as_value(&self) -> Option<&Value> {
-        None
-    }
-}
-
-impl Expression for Value {
-    fn evaluate(&self, _: &Environment) -> Expr {
-        self.clone().into()
-    }
-
-    fn as_value(&self) -> Option<&Value> {
-        Some(&self)
-    }
-}
-
-impl From<Value> for Expr {
-    fn from(expression: Value) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
-
-pub struct Add(Expr, Expr);
-
-impl Add {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
-}
-
-impl Expression for Add {
-    fn evaluate(&self, environment: &Environment) -> Expr {
-        match (
-            self.0.evaluate(environment).as_value(),
-            self.1.evaluate(environment).as_value(),
-        ) {
-            (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Number(a + b).into(),
-            _ => panic!("Unexpected values"),
+pub trait Expression {
+    /// Evaluates with a particular `Runtime`: its `Overflow` policy, its `Output` for
+    /// `Print` and its `Input` for `Read`.
+    fn evaluate_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Value, EvalError>;
+
+    fn evaluate(&self, environment: &Environment) -> Result<Value, EvalError> {
+        self.evaluate_with(environment, &mut Runtime::default())
+    }
+}
+
+/// Expression work still to be done by `execute`. Work which combines the values of operands takes
+/// them from the top of the stack of values, the last operand on top.
+pub(crate) enum Pending {
+    /// Evaluate an expression, leaving its value on the stack.
+    Evaluate(Rc<Expr>),
+    Binary(Binary),
+    Unary(Unary),
+    /// Take a slice of an operand, between a start and an end.
+    Slice,
+    /// Finish `&&` (`decisive` is false) or `||` (`decisive` is true) once the left operand has
+    /// been evaluated, evaluating the right one only if the left is not `decisive`.
+    Logical(Rc<Expr>, bool),
+    /// Insist on a boolean, for the right operand of `&&` or `||`.
+    Boolean,
+    /// Gather this many values into a list.
+    List(usize),
+    /// Call the named function, with this many arguments.
+    Call(String, usize),
+    /// Return from a call to its caller, which was in this environment, leaving the call's result.
+    Return(Environment),
+}
+
+/// Schedules whatever combines the values of some operands, after the operands themselves, to be
+/// evaluated from left to right.
+fn operands(stack: &mut Vec<Work>, combine: Pending, operands: &[&Rc<Expr>]) {
+    stack.push(Work::Expression(combine));
+    for operand in operands.iter().rev() {
+        stack.push(Work::Expression(Pending::Evaluate(Rc::clone(operand))));
+    }
+}
+
+/// Begins a call, running its body and then evaluating its result in the call's own environment,
+/// and on the caller's fuel.
+fn call(
+    name: &str,
+    locals: Environment,
+    body: &Rc<Stmt>,
+    result: &Rc<Expr>,
+    stack: &mut Vec<Work>,
+    environment: &mut Environment,
+    runtime: &mut Runtime,
+) -> Result<(), EvalError> {
+    runtime.begin_call(name)?;
+    let caller = mem::replace(environment, locals);
+    stack.push(Work::Expression(Pending::Return(caller)));
+    stack.push(Work::Expression(Pending::Evaluate(result.clone())));
+    stack.push(Work::Run(body.clone()));
+    Ok(())
+}
+
+/// Evaluates an expression, scheduling work for its operands; or does what was scheduled to follow
+/// them.
+pub(crate) fn step(
+    pending: Pending,
+    stack: &mut Vec<Work>,
+    values: &mut Vec<Value>,
+    environment: &mut Environment,
+    runtime: &mut Runtime,
+) -> Result<(), EvalError> {
+    match pending {
+        Pending::Evaluate(expression) => match &*expression {
+            Expr::Value(value) => values.push(value.clone()),
+            Expr::Add(Add(l, r)) => operands(stack, Pending::Binary(operators::add), &[l, r]),
+            Expr::Subtract(Subtract(l, r)) => {
+                operands(stack, Pending::Binary(operators::subtract), &[l, r])
+            }
+            Expr::Multiply(Multiply(l, r)) => {
+                operands(stack, Pending::Binary(operators::multiply), &[l, r])
+            }
+            Expr::Divide(Divide(l, r)) => {
+                operands(stack, Pending::Binary(operators::divide), &[l, r])
+            }
+            Expr::Modulo(Modulo(l, r)) => {
+                operands(stack, Pending::Binary(operators::modulo), &[l, r])
+            }
+            Expr::Negate(Negate(operand)) => {
+                operands(stack, Pending::Unary(operators::negate), &[operand])
+            }
+            Expr::Equals(Equals(l, r)) => {
+                operands(stack, Pending::Binary(operators::equals), &[l, r])
+            }
+            Expr::NotEquals(NotEquals(l, r)) => {
+                operands(stack, Pending::Binary(operators::not_equals), &[l, r])
+            }
+            Expr::LessThan(LessThan(l, r)) => {
+                operands(stack, Pending::Binary(operators::less_than), &[l, r])
+            }
+            Expr::GreaterThan(GreaterThan(l, r)) => {
+                operands(stack, Pending::Binary(operators::greater_than), &[l, r])
+            }
+            Expr::LessOrEqual(LessOrEqual(l, r)) => {
+                operands(stack, Pending::Binary(operators::less_or_equal), &[l, r])
+            }
+            Expr::GreaterOrEqual(GreaterOrEqual(l, r)) => {
+                operands(stack, Pending::Binary(operators::greater_or_equal), &[l, r])
+            }
+            Expr::And(And(l, r)) => operands(stack, Pending::Logical(r.clone(), false), &[l]),
+            Expr::Or(Or(l, r)) => operands(stack, Pending::Logical(r.clone(), true), &[l]),
+            Expr::Not(Not(operand)) => operands(stack, Pending::Unary(operators::not), &[operand]),
+            Expr::Length(Length(operand)) => {
+                operands(stack, Pending::Unary(operators::length), &[operand])
+            }
+            Expr::Index(Index(operand, index)) => {
+                operands(stack, Pending::Binary(operators::index), &[operand, index])
+            }
+            Expr::Slice(Slice(operand, start, end)) => {
+                operands(stack, Pending::Slice, &[operand, start, end])
+            }
+            Expr::List(List(elements)) => {
+                let elements = elements.iter().collect::<Vec<_>>();
+                operands(stack, Pending::List(elements.len()), &elements)
+            }
+            Expr::Append(Append(list, element)) => {
+                operands(stack, Pending::Binary(operators::append), &[list, element])
+            }
+            Expr::Call(Call(name, arguments)) => {
+                let arguments = arguments.iter().collect::<Vec<_>>();
+                let call = Pending::Call(name.clone(), arguments.len());
+                operands(stack, call, &arguments)
+            }
+            Expr::Frame(Frame(name, locals, body, result)) => call(
+                name,
+                locals.clone(),
+                body,
+                result,
+                stack,
+                environment,
+                runtime,
+            )?,
+            Expr::Variable(Variable(name)) => match environment.get(name) {
+                Some(value) => values.push(value.clone()),
+                None => return Err(EvalError::UnboundVariable(name.clone())),
+            },
+        },
+        Pending::Binary(operation) => {
+            let right = pop(values);
+            let left = pop(values);
+            values.push(operation(left, right, runtime.overflow)?);
         }
-    }
-}
-
-impl Printable for Add {
-    fn to_s(&self) -> String {
-        format!("{} + {}", self.0.to_s(), self.1.to_s())
-    }
-}
-
-impl From<Add> for Expr {
-    fn from(expression: Add) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
-
-pub struct Multiply(Expr, Expr);
-
-impl Multiply {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
-}
-
-impl Expression for Multiply {
-    fn evaluate(&self, environment: &Environment) -> Expr {
-        match (
-            self.0.evaluate(environment).as_value(),
-            self.1.evaluate(environment).as_value(),
-        ) {
-            (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Number(a * b).into(),
-            _ => panic!("Unexpected values"),
+        Pending::Unary(operation) => {
+            let operand = pop(values);
+            values.push(operation(operand, runtime.overflow)?);
         }
-    }
-}
-
-impl Printable for Multiply {
-    fn to_s(&self) -> String {
-        format!("{} * {}", self.0.to_s(), self.1.to_s())
-    }
-}
-
-impl From<Multiply> for Expr {
-    fn from(expression: Multiply) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
-
-pub struct LessThan(Expr, Expr);
-
-impl LessThan {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
-}
-
-impl Expression for LessThan {
-    fn evaluate(&self, environment: &Environment) -> Expr {
-        match (
-            self.0.evaluate(environment).as_value(),
-            self.1.evaluate(environment).as_value(),
-        ) {
-            (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Boolean(a < b).into(),
-            _ => panic!("Unexpected values"),
+        Pending::Slice => {
+            let end = pop(values);
+            let start = pop(values);
+            let operand = pop(values);
+            values.push(operators::slice(operand, start, end)?);
+        }
+        Pending::Logical(right, decisive) => {
+            if operators::boolean(pop(values))? == decisive {
+                values.push(Value::Boolean(decisive));
+            } else {
+                stack.push(Work::Expression(Pending::Boolean));
+                stack.push(Work::Expression(Pending::Evaluate(right)));
+            }
+        }
+        Pending::Boolean => {
+            let value = operators::boolean(pop(values))?;
+            values.push(Value::Boolean(value));
+        }
+        Pending::List(length) => {
+            let elements = values.split_off(values.len() - length);
+            values.push(Value::List(elements));
+        }
+        Pending::Call(name, count) => {
+            let arguments = values.split_off(values.len() - count);
+            let (FunctionDef(_, _, body, result), locals) =
+                operators::enter(environment, &name, arguments)?;
+            call(&name, locals, &body, &result, stack, environment, runtime)?;
+        }
+        Pending::Return(caller) => {
+            *environment = caller;
+            runtime.end_call();
         }
     }
-}
-
-impl Printable for LessThan {
-    fn to_s(&self) -> String {
-        format!("{} < {}", self.0.to_s(), self.1.to_s())
-    }
-}
-
-impl From<LessThan> for Expr {
-    fn from(expression: LessThan) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
-
-pub struct Variable(String);
-
-impl Variable {
-    pub fn new<S: Into<String>>(name: S) -> Self {
-        Self(name.into())
-    }
-}
-
-impl Expression for Variable {
-    fn evaluate(&self, environment: &Environment) -> Expr {
-        let value = &environment.0[&self.0];
-        value.clone().into()
-    }
-}
-
-impl Printable for Variable {
-    fn to_s(&self) -> String {
-        self.0.clone()
-    }
-}
-
-impl From<Variable> for Expr {
-    fn from(expression: Variable) -> Self {
-        Rc::new(Box::new(expression))
+    Ok(())
+}
+
+impl Expression for Expr {
+    fn evaluate_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Value, EvalError> {
+        let mut fuel = Fuel {
+            limit: None,
+            used: 0,
+        };
+        let evaluate = vec![Work::Expression(Pending::Evaluate(Rc::new(self.clone())))];
+        match execute(evaluate, environment, runtime, &mut fuel) {
+            Ok((_, mut values)) => Ok(pop(&mut values)),
+            Err(Halt::Error(error)) => Err(error),
+            Err(Halt::OutOfFuel(_)) => unreachable!("evaluation without a limit ran out of fuel"),
+        }
     }
 }
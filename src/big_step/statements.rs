@@ -1,159 +1,302 @@
-use crate::big_step::expressions::Expr;
-use crate::{Environment, Printable, Value};
+use crate::ast::{
+    Assign, AssignIndex, Expr, FunctionDef, If, Iteration, Let, Print, Read, Scope, Sequence, Stmt,
+    While,
+};
+use crate::big_step::expressions::{self, Pending};
+use crate::operators;
+use crate::{Environment, EvalError, Printable, Runtime, Value};
 use std::rc::Rc;
 
-/// Boxed version of a `Statement` (so they can be passed around generically).
-pub type Stmt = Rc<Box<dyn Statement>>;
+pub trait Statement {
+    /// Evaluates with a particular `Runtime`: its `Overflow` policy, its `Output` for
+    /// `Print` and its `Input` for `Read`.
+    fn evaluate_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Environment, EvalError>;
 
-pub struct DoNothing;
-
-pub trait Statement: Printable {
-    fn evaluate(&self, environment: &Environment) -> Environment;
-}
-
-impl Statement for DoNothing {
-    fn evaluate(&self, environment: &Environment) -> Environment {
-        environment.clone()
-    }
-}
-
-impl From<DoNothing> for Stmt {
-    fn from(statement: DoNothing) -> Stmt {
-        Rc::new(Box::new(statement))
-    }
-}
-
-impl Printable for DoNothing {
-    fn to_s(&self) -> String {
-        String::from("do-nothing")
-    }
-}
-
-pub struct Assign(String, Expr);
-
-impl Assign {
-    pub fn new<N: Into<String>, E: Into<Expr>>(name: N, expression: E) -> Self {
-        Self(name.into(), expression.into())
-    }
-}
-
-impl Statement for Assign {
-    fn evaluate(&self, environment: &Environment) -> Environment {
-        environment.update(
-            &self.0,
-            self.1.evaluate(environment).as_value().unwrap().clone(),
-        )
+    fn evaluate(&self, environment: &Environment) -> Result<Environment, EvalError> {
+        self.evaluate_with(environment, &mut Runtime::default())
     }
 }
 
-impl From<Assign> for Stmt {
-    fn from(statement: Assign) -> Self {
-        Rc::new(Box::new(statement))
-    }
+/// Why evaluation stopped short of finishing.
+pub(crate) enum Halt {
+    Error(EvalError),
+    OutOfFuel(Environment),
 }
 
-impl Printable for Assign {
-    fn to_s(&self) -> String {
-        format!("{} = {}", &self.0, self.1.to_s())
+impl From<EvalError> for Halt {
+    fn from(error: EvalError) -> Self {
+        Halt::Error(error)
     }
 }
 
-pub struct If(Expr, Stmt, Stmt);
-
-impl If {
-    pub fn new<E: Into<Expr>, S1: Into<Stmt>, S2: Into<Stmt>>(
-        condition: E,
-        consequence: S1,
-        alternative: S2,
-    ) -> Self {
-        Self(condition.into(), consequence.into(), alternative.into())
-    }
+/// The number of steps evaluation may still take. Every statement evaluated, every test of a
+/// loop's condition, and every exit from a scope costs one step, including those inside calls.
+pub(crate) struct Fuel {
+    pub(crate) limit: Option<usize>,
+    pub(crate) used: usize,
 }
 
-impl Statement for If {
-    fn evaluate(&self, environment: &Environment) -> Environment {
-        match self.0.evaluate(environment).as_value() {
-            Some(Value::Boolean(true)) => self.1.evaluate(environment),
-            Some(Value::Boolean(false)) => self.2.evaluate(environment),
-            _ => panic!("Condition must be boolean."),
+impl Fuel {
+    /// Takes a step, unless the limit has been reached.
+    fn burn(&mut self) -> bool {
+        if self.limit == Some(self.used) {
+            return false;
         }
+        self.used += 1;
+        true
     }
 }
 
-impl From<If> for Stmt {
-    fn from(statement: If) -> Stmt {
-        Rc::new(Box::new(statement))
+/// Insists on a boolean for the condition of an `If` or `While`.
+fn condition(value: Value) -> Result<bool, EvalError> {
+    match value {
+        Value::Boolean(value) => Ok(value),
+        other => Err(EvalError::NonBooleanCondition(other)),
     }
 }
 
-impl Printable for If {
-    fn to_s(&self) -> String {
-        format!(
-            "if ({}) {{ {} }} else {{ {} }}",
-            self.0.to_s(),
-            self.1.to_s(),
-            self.2.to_s()
-        )
-    }
+/// Work still to be done by `execute`, innermost last. Work which follows the evaluation of an
+/// expression takes its value from the top of the stack of values.
+pub(crate) enum Work {
+    Run(Rc<Stmt>),
+    /// Test a loop's condition, and go around again if it holds.
+    Loop(While),
+    /// Exit the innermost scope, at the end of this `Let` or `Scope`.
+    Exit(Rc<Stmt>),
+    Expression(Pending),
+    /// Assign a value to the variable with this name.
+    Assign(String),
+    /// Replace an element of the list held by the variable with this name, the index having
+    /// been evaluated before the value.
+    AssignIndex(String),
+    /// Run one statement or the other, as a condition says.
+    Branch(Rc<Stmt>, Rc<Stmt>),
+    /// Go around a loop again, if its condition holds.
+    Repeat(While),
+    /// Enter the scope of a `Let` with the variable's value, and run its body.
+    Enter(Rc<Stmt>),
+    Print,
 }
 
-pub struct Sequence(Stmt, Stmt);
-
-impl Sequence {
-    pub fn new<S1: Into<Stmt>, S2: Into<Stmt>>(first: S1, second: S2) -> Self {
-        Self(first.into(), second.into())
+/// Carries out a `break` or `continue`, throwing away what was left of the innermost loop's body,
+/// but exiting any scopes it had entered. A loop outside the call the jump is in is out of reach.
+fn unwind(
+    jump: &Stmt,
+    stack: &mut Vec<Work>,
+    mut environment: Environment,
+) -> Result<Environment, EvalError> {
+    loop {
+        match stack.pop() {
+            Some(Work::Exit(statement)) => match environment.exit_scope() {
+                Some(outer) => environment = outer,
+                None => return Err(EvalError::Irreducible(statement.inspect())),
+            },
+            Some(Work::Loop(node)) => {
+                if let Stmt::Continue = jump {
+                    stack.push(Work::Loop(node));
+                }
+                return Ok(environment);
+            }
+            Some(Work::Expression(Pending::Return(_))) | None => {
+                return Err(EvalError::OutsideLoop(jump.to_s()))
+            }
+            Some(_) => {}
+        }
     }
 }
 
-impl Statement for Sequence {
-    fn evaluate(&self, environment: &Environment) -> Environment {
-        self.1.evaluate(&self.0.evaluate(environment))
-    }
+/// The environment a program was in when it made the outermost call still unfinished, or else the
+/// one it is in now: where evaluation that runs out of fuel stops, since a call which runs out
+/// leaves the caller's environment as it was.
+fn outermost(stack: &[Work], environment: &Environment) -> Environment {
+    let caller = stack.iter().find_map(|work| match work {
+        Work::Expression(Pending::Return(caller)) => Some(caller),
+        _ => None,
+    });
+    caller.unwrap_or(environment).clone()
 }
 
-impl From<Sequence> for Stmt {
-    fn from(statement: Sequence) -> Stmt {
-        Rc::new(Box::new(statement))
-    }
+/// Takes the value of the expression evaluated last.
+pub(crate) fn pop(values: &mut Vec<Value>) -> Value {
+    values
+        .pop()
+        .expect("an expression is evaluated before its value is used")
 }
 
-impl Printable for Sequence {
-    fn to_s(&self) -> String {
-        format!("{}; {}", self.0.to_s(), self.1.to_s())
-    }
+/// Schedules the evaluation of an expression which is part of a statement.
+fn evaluate(expression: &Expr) -> Work {
+    Work::Expression(Pending::Evaluate(Rc::new(expression.clone())))
 }
 
-pub struct While(Expr, Stmt);
-
-impl While {
-    pub fn new<E: Into<Expr>, S: Into<Stmt>>(condition: E, body: S) -> Self {
-        Self(condition.into(), body.into())
-    }
-
-    /// Sort of like a `Clone`
-    pub fn from(other: &While) -> Self {
-        Self::new(other.0.clone(), other.1.clone())
+/// Does the work on `stack` until none is left, using an explicit stack rather than the native
+/// one, so neither the number of loop iterations, nor the length of a sequence, nor the depth of
+/// calls is limited by it. Every statement run, every test of a loop's condition, and every exit
+/// from a scope costs one step of `fuel`, including those inside calls.
+fn run(
+    stack: &mut Vec<Work>,
+    values: &mut Vec<Value>,
+    environment: &mut Environment,
+    runtime: &mut Runtime,
+    fuel: &mut Fuel,
+) -> Result<(), Halt> {
+    while let Some(work) = stack.pop() {
+        let step = matches!(work, Work::Run(_) | Work::Loop(_) | Work::Exit(_));
+        if step && !fuel.burn() {
+            return Err(Halt::OutOfFuel(outermost(stack, environment)));
+        }
+        match work {
+            Work::Run(statement) => match &*statement {
+                Stmt::DoNothing => {}
+                jump @ (Stmt::Break | Stmt::Continue) => {
+                    *environment = unwind(jump, stack, environment.clone())?;
+                }
+                Stmt::Assign(Assign(name, expression)) => {
+                    stack.push(Work::Assign(name.clone()));
+                    stack.push(evaluate(expression));
+                }
+                Stmt::AssignIndex(AssignIndex(name, index, expression)) => {
+                    stack.push(Work::AssignIndex(name.clone()));
+                    stack.push(evaluate(expression));
+                    stack.push(evaluate(index));
+                }
+                Stmt::FunctionDef(function) => {
+                    let FunctionDef(name, _, _, _) = function;
+                    *environment = environment.update(name, Value::Function(function.clone()));
+                }
+                Stmt::If(If(test, consequence, alternative)) => {
+                    stack.push(Work::Branch(consequence.clone(), alternative.clone()));
+                    stack.push(evaluate(test));
+                }
+                Stmt::Let(Let(_, expression, _)) => {
+                    stack.push(Work::Enter(statement.clone()));
+                    stack.push(evaluate(expression));
+                }
+                Stmt::Scope(Scope(_, body)) => {
+                    stack.push(Work::Exit(statement.clone()));
+                    stack.push(Work::Run(body.clone()));
+                }
+                Stmt::Print(Print(expression)) => {
+                    stack.push(Work::Print);
+                    stack.push(evaluate(expression));
+                }
+                Stmt::Read(Read(name)) => {
+                    let value = runtime.input.read(name)?;
+                    *environment = environment.update(name, value);
+                }
+                Stmt::Sequence(Sequence(first, second)) => {
+                    stack.push(Work::Run(second.clone()));
+                    stack.push(Work::Run(first.clone()));
+                }
+                Stmt::Sugared(sugared) => stack.push(Work::Run(sugared.core().clone())),
+                Stmt::While(node) => stack.push(Work::Loop(node.clone())),
+                Stmt::Iteration(Iteration(body, node)) => {
+                    stack.push(Work::Loop(node.clone()));
+                    stack.push(Work::Run(body.clone()));
+                }
+            },
+            Work::Exit(statement) => match environment.exit_scope() {
+                Some(outer) => *environment = outer,
+                None => return Err(EvalError::Irreducible(statement.inspect()).into()),
+            },
+            Work::Loop(node) => {
+                let While(test, _) = &node;
+                let test = evaluate(test);
+                stack.push(Work::Repeat(node));
+                stack.push(test);
+            }
+            Work::Expression(pending) => {
+                expressions::step(pending, stack, values, environment, runtime)?
+            }
+            Work::Assign(name) => *environment = environment.update(&name, pop(values)),
+            Work::AssignIndex(name) => {
+                let value = pop(values);
+                let index = pop(values);
+                let list = match environment.get(&name) {
+                    Some(list) => list.clone(),
+                    None => return Err(EvalError::UnboundVariable(name).into()),
+                };
+                *environment = environment.update(&name, operators::replace(list, index, value)?);
+            }
+            Work::Branch(consequence, alternative) => {
+                if condition(pop(values))? {
+                    stack.push(Work::Run(consequence));
+                } else {
+                    stack.push(Work::Run(alternative));
+                }
+            }
+            Work::Repeat(node) => {
+                if condition(pop(values))? {
+                    let While(_, body) = &node;
+                    let body = body.clone();
+                    stack.push(Work::Loop(node));
+                    stack.push(Work::Run(body));
+                }
+            }
+            Work::Enter(statement) => {
+                if let Stmt::Let(Let(name, _, body)) = &*statement {
+                    let body = body.clone();
+                    *environment = environment.enter_scope(name, pop(values));
+                    stack.push(Work::Exit(statement));
+                    stack.push(Work::Run(body));
+                }
+            }
+            Work::Print => runtime.output.print(&pop(values))?,
+        }
     }
+    Ok(())
 }
 
-impl Statement for While {
-    fn evaluate(&self, environment: &Environment) -> Environment {
-        match self.0.evaluate(environment).as_value() {
-            Some(Value::Boolean(true)) => self.evaluate(&self.1.evaluate(environment)),
-            Some(Value::Boolean(false)) => environment.clone(),
-            _ => unreachable!(),
+/// Does the work on `stack`, starting in `environment`, returning the environment it finishes in
+/// along with the values of any expressions it was given to evaluate. Calls still unfinished when
+/// it halts are no longer counted by the `runtime`.
+pub(crate) fn execute(
+    mut stack: Vec<Work>,
+    environment: &Environment,
+    runtime: &mut Runtime,
+    fuel: &mut Fuel,
+) -> Result<(Environment, Vec<Value>), Halt> {
+    let mut environment = environment.clone();
+    let mut values = Vec::new();
+    let result = run(&mut stack, &mut values, &mut environment, runtime, fuel);
+    if result.is_err() {
+        for work in &stack {
+            if let Work::Expression(Pending::Return(_)) = work {
+                runtime.end_call();
+            }
         }
     }
+    result.map(|()| (environment, values))
 }
 
-impl From<While> for Stmt {
-    fn from(statement: While) -> Self {
-        Rc::new(Box::new(statement))
-    }
+/// Evaluates a statement, with nothing left over on the stack of values once it is done.
+pub(crate) fn execute_statement(
+    statement: &Stmt,
+    environment: &Environment,
+    runtime: &mut Runtime,
+    fuel: &mut Fuel,
+) -> Result<Environment, Halt> {
+    let run = vec![Work::Run(Rc::new(statement.clone()))];
+    execute(run, environment, runtime, fuel).map(|(environment, _)| environment)
 }
 
-impl Printable for While {
-    fn to_s(&self) -> String {
-        format!("while ({}) {{ {} }}", self.0.to_s(), self.1.to_s())
+impl Statement for Stmt {
+    fn evaluate_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Environment, EvalError> {
+        let mut fuel = Fuel {
+            limit: None,
+            used: 0,
+        };
+        match execute_statement(self, environment, runtime, &mut fuel) {
+            Ok(environment) => Ok(environment),
+            Err(Halt::Error(error)) => Err(error),
+            Err(Halt::OutOfFuel(_)) => unreachable!("evaluation without a limit ran out of fuel"),
+        }
     }
 }
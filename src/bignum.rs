@@ -0,0 +1,348 @@
+//! Arbitrary-precision integers, for numbers which outgrow an `i64`. Only what the interpreters
+//! need is here: the arithmetic operators, comparison, and conversion to and from text.
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+/// A sign and a magnitude, the magnitude held as base 2³² digits with the least significant
+/// first. There are never any leading zero digits, and zero is never negative, so each number
+/// has exactly one representation and the derived equality is numeric equality.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(false, vec![])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The number as an `i64`, if it is in range.
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self.low_bits();
+        if self.negative {
+            if magnitude <= 1 << 63 {
+                Some((magnitude as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// The lowest 64 bits of the number in two's complement, which is what `i64` arithmetic
+    /// would have wrapped around to.
+    pub fn wrapping_to_i64(&self) -> i64 {
+        let low = self.low_bits() as i64;
+        if self.negative {
+            low.wrapping_neg()
+        } else {
+            low
+        }
+    }
+
+    fn low_bits(&self) -> u64 {
+        self.magnitude
+            .iter()
+            .take(2)
+            .rev()
+            .fold(0, |bits, digit| bits << 32 | u64::from(*digit))
+    }
+
+    /// Divides, rounding towards zero, and returns the quotient with the remainder, which takes
+    /// the sign of `self`. Returns `None` when `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divide(&self.magnitude, &divisor.magnitude);
+        Some((
+            BigInt::new(self.negative != divisor.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        ))
+    }
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (i, digit) in long.iter().enumerate() {
+        let total = u64::from(*digit) + u64::from(*short.get(i).unwrap_or(&0)) + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// Subtracts `b` from `a`, which must be at least as large.
+fn subtract(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, digit) in a.iter().enumerate() {
+        let subtrahend = i64::from(*b.get(i).unwrap_or(&0)) + borrow;
+        let mut total = i64::from(*digit) - subtrahend;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    while difference.last() == Some(&0) {
+        difference.pop();
+    }
+    difference
+}
+
+fn multiply(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let total = u64::from(*x) * u64::from(*y) + u64::from(product[i + j]) + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Long division by a single digit, which is all that printing in decimal needs.
+fn divide_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = remainder << 32 | u64::from(a[i]);
+        quotient[i] = (current / u64::from(divisor)) as u32;
+        remainder = current % u64::from(divisor);
+    }
+    (quotient, remainder as u32)
+}
+
+/// Binary long division, one bit of `a` at a time. Slow next to Knuth's algorithm, but simple,
+/// and dividing two large numbers is rare in SIMPLE programs.
+fn divide(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let (quotient, remainder) = divide_small(a, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..a.len() * 32).rev() {
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for digit in remainder.iter_mut() {
+            let shifted = *digit >> 31;
+            *digit = *digit << 1 | carry;
+            carry = shifted;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+        if compare(&remainder, b) != Ordering::Less {
+            remainder = subtract(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+impl From<i64> for BigInt {
+    fn from(number: i64) -> Self {
+        let magnitude = number.unsigned_abs();
+        BigInt::new(number < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare(&self.magnitude, &other.magnitude),
+            (true, true) => compare(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ops::Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl ops::Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add(&self.magnitude, &other.magnitude));
+        }
+        match compare(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                BigInt::new(other.negative, subtract(&other.magnitude, &self.magnitude))
+            }
+            _ => BigInt::new(self.negative, subtract(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl ops::Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl ops::Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            multiply(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+/// Like `i64`, panics when dividing by zero.
+impl ops::Div for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("attempt to divide by zero").0
+    }
+}
+
+impl ops::Rem for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+/// Nine decimal digits at a time fit in one base 2³² digit.
+const CHUNK: u32 = 1_000_000_000;
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divide_small(&magnitude, CHUNK);
+            chunks.push(remainder);
+            magnitude = quotient;
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The text was not an optional `-` followed by decimal digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "invalid integer")
+    }
+}
+
+impl std::error::Error for ParseBigIntError {}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+        let mut magnitude = vec![];
+        // Take the digits nine at a time, lining the chunks up with the end of the number.
+        let split = digits.len() % 9;
+        let chunks = std::iter::once(&digits[..split])
+            .chain(
+                digits.as_bytes()[split..]
+                    .chunks(9)
+                    .map(|chunk| std::str::from_utf8(chunk).expect("ASCII digits are valid UTF-8")),
+            )
+            .filter(|chunk| !chunk.is_empty());
+        for chunk in chunks {
+            let scale = 10u32.pow(chunk.len() as u32);
+            let value: u32 = chunk.parse().map_err(|_| ParseBigIntError)?;
+            let mut carry = u64::from(value);
+            for digit in magnitude.iter_mut() {
+                let total = u64::from(*digit) * u64::from(scale) + carry;
+                *digit = total as u32;
+                carry = total >> 32;
+            }
+            if carry != 0 {
+                magnitude.push(carry as u32);
+            }
+        }
+        Ok(BigInt::new(negative, magnitude))
+    }
+}
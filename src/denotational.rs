@@ -0,0 +1,31 @@
+//! Denotational semantics: each node is translated, once, into a Rust closure giving its meaning,
+//! so running the program again does not have to walk the tree. The same translation can also be
+//! written out as Rust source code, in the spirit of the book's `to_ruby`.
+mod expressions;
+mod statements;
+
+pub use expressions::*;
+pub use statements::*;
+
+use crate::{Environment, EvalError, Runtime};
+
+/// The meaning of a term: a function from an environment, and the `Runtime` of the run it is part
+/// of, to whatever the term produces.
+pub type Denotation<T> = Box<dyn Fn(&Environment, &mut Runtime) -> Result<T, EvalError>>;
+
+/// A meaning which runs with a `Runtime` of its own, made afresh each time it runs.
+pub type Closure<T> = Box<dyn Fn(&Environment) -> Result<T, EvalError>>;
+
+/// Gives a meaning the default `Runtime` each time it runs.
+fn with_default_runtime<T: 'static>(meaning: Denotation<T>) -> Closure<T> {
+    Box::new(move |environment| meaning(environment, &mut Runtime::default()))
+}
+
+/// Indents every line of a block of generated source by one level.
+fn indent(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,498 @@
+use crate::ast::Stmt;
+use crate::ast::{
+    Add, And, Append, Call, Divide, Equals, Expr, Frame, FunctionDef, GreaterOrEqual, GreaterThan,
+    Index, Length, LessOrEqual, LessThan, List, Modulo, Multiply, Negate, Not, NotEquals, Or,
+    Slice, Subtract, Variable,
+};
+use crate::denotational::statements::statement;
+use crate::denotational::{indent, with_default_runtime, Closure, Denotation, Statement};
+use crate::operators::{self, Binary, Unary};
+use crate::{Environment, EvalError, Printable, Runtime, Value};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::slice;
+
+pub trait Expression {
+    /// Translates the expression into a closure which computes its value with a particular
+    /// `Runtime`: its `Overflow` policy, its `Output` for `Print` and its `Input` for `Read`.
+    fn to_denotation(&self) -> Denotation<Value>;
+    /// Translates the expression into a closure which computes its value, with the default
+    /// `Runtime`.
+    fn to_closure(&self) -> Closure<Value> {
+        with_default_runtime(self.to_denotation())
+    }
+    /// Translates the expression into a Rust expression of type `Value`, reading variables from
+    /// a `HashMap<String, Value>` called `env`. Its numbers are all `i64`s, so arithmetic that
+    /// the interpreters would promote to a `Value::BigNumber` panics instead.
+    fn to_rust(&self) -> String;
+
+    /// Writes the expression out as a self-contained Rust function. Where the interpreters would
+    /// report an `EvalError`, the generated code panics.
+    fn to_rust_source(&self) -> String {
+        format!(
+            "fn run(env: &HashMap<String, Value>) -> Value {{\n{}\n}}\n",
+            indent(&self.to_rust())
+        )
+    }
+}
+
+/// Combines the meanings of a binary operator's operands.
+fn binary(
+    left: &Expr,
+    right: &Expr,
+    enclosing: &[Weak<Translation>],
+    operation: Binary,
+) -> Denotation<Value> {
+    let (left, right) = (expression(left, enclosing), expression(right, enclosing));
+    Box::new(move |environment, runtime| {
+        operation(
+            left(environment, runtime)?,
+            right(environment, runtime)?,
+            runtime.overflow,
+        )
+    })
+}
+
+fn unary(operand: &Expr, enclosing: &[Weak<Translation>], operation: Unary) -> Denotation<Value> {
+    let operand = expression(operand, enclosing);
+    Box::new(move |environment, runtime| {
+        operation(operand(environment, runtime)?, runtime.overflow)
+    })
+}
+
+fn slice(Slice(operand, start, end): &Slice, enclosing: &[Weak<Translation>]) -> Denotation<Value> {
+    let (operand, start, end) = (
+        expression(operand, enclosing),
+        expression(start, enclosing),
+        expression(end, enclosing),
+    );
+    Box::new(move |environment, runtime| {
+        operators::slice(
+            operand(environment, runtime)?,
+            start(environment, runtime)?,
+            end(environment, runtime)?,
+        )
+    })
+}
+
+/// The meaning of `&&` (`decisive` is false) or `||` (`decisive` is true), which only looks at
+/// the right operand when the left one is not `decisive`.
+fn logical(
+    left: &Expr,
+    right: &Expr,
+    enclosing: &[Weak<Translation>],
+    decisive: bool,
+) -> Denotation<Value> {
+    let (left, right) = (expression(left, enclosing), expression(right, enclosing));
+    Box::new(move |environment, runtime| {
+        if operators::boolean(left(environment, runtime)?)? == decisive {
+            Ok(Value::Boolean(decisive))
+        } else {
+            operators::boolean(right(environment, runtime)?).map(Value::Boolean)
+        }
+    })
+}
+
+/// A function's body and result, translated into closures. It keeps hold of the terms it was
+/// translated from, so that no other function's can take their place in memory while it lives.
+pub(crate) struct Translation {
+    body: Rc<Stmt>,
+    result: Rc<Expr>,
+    run_body: Denotation<Environment>,
+    run_result: Denotation<Value>,
+}
+
+impl Translation {
+    /// Translates a function's body and result inside the `enclosing` functions. A call in it to
+    /// itself, or to any of those, refers back to that translation rather than holding it, so no
+    /// translation holds on to itself.
+    fn new(body: &Rc<Stmt>, result: &Rc<Expr>, enclosing: &[Weak<Translation>]) -> Rc<Self> {
+        Rc::new_cyclic(|this| {
+            let enclosing = [enclosing, slice::from_ref(this)].concat();
+            Translation {
+                body: body.clone(),
+                result: result.clone(),
+                run_body: statement(body, &enclosing),
+                run_result: expression(result, &enclosing),
+            }
+        })
+    }
+
+    fn translates(&self, body: &Rc<Stmt>, result: &Rc<Expr>) -> bool {
+        Rc::ptr_eq(&self.body, body) && Rc::ptr_eq(&self.result, result)
+    }
+
+    /// Runs the body and then the result in the function's own environment, as one more call.
+    fn run(
+        &self,
+        name: &str,
+        locals: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Value, EvalError> {
+        runtime.call(name, |runtime| {
+            let locals = (self.run_body)(locals, runtime)?;
+            (self.run_result)(&locals, runtime)
+        })
+    }
+}
+
+/// Generates the body and result of a function, given a `HashMap` called `locals` holding its
+/// variables.
+fn function_body_source(body: &Stmt, result: &Expr) -> String {
+    format!(
+        "let env = &mut locals;\n{}\n{}",
+        body.to_rust(),
+        result.to_rust()
+    )
+}
+
+/// Generates a function as a nested Rust `fn`, which receives the caller's variables and its
+/// arguments and builds its own environment from them as `operators::enter` does.
+fn function_source(function: &FunctionDef) -> String {
+    let FunctionDef(name, parameters, body, result) = function;
+    let parameters: Vec<String> = parameters.iter().map(|p| format!("{:?}", p)).collect();
+    format!(
+        "{{\n    #[allow(unused_variables)]\n    fn function(env: &HashMap<String, Value>, arguments: Vec<Value>) -> Value {{\n        \
+         assert_eq!(arguments.len(), {}, \"`{}` takes {} arguments\");\n        \
+         let mut locals: HashMap<String, Value> = env\n            \
+         .iter()\n            \
+         .filter(|(_, value)| matches!(value, Value::Function(..)))\n            \
+         .map(|(name, value)| (name.clone(), value.clone()))\n            \
+         .collect();\n        \
+         let parameters: [&str; {}] = [{}];\n        \
+         for (name, value) in parameters.iter().zip(arguments) {{\n            \
+         locals.insert(name.to_string(), value);\n        \
+         }}\n{}\n    }}\n    \
+         Value::Function({:?}, function)\n}}",
+        parameters.len(),
+        name,
+        parameters.len(),
+        parameters.len(),
+        parameters.join(", "),
+        indent(&indent(&function_body_source(body, result))),
+        function.to_s()
+    )
+}
+
+/// Generates a `match` on the values of two operands with the given arms, which panics on any
+/// other combination.
+fn pair_source(left: &Expr, right: &Expr, operator: &str, arms: &[String]) -> String {
+    let mut source = format!("match ({}, {}) {{\n", left.to_rust(), right.to_rust());
+    for arm in arms {
+        source.push_str(&format!("    {},\n", arm));
+    }
+    source.push_str(&format!(
+        "    (a, b) => panic!(\"unexpected values {{:?}} {} {{:?}}\", a, b),\n}}",
+        operator
+    ));
+    source
+}
+
+/// An arm for `pair_source` matching two operands of the same kind, `a` and `b`.
+fn both(kind: &str, result: &str) -> String {
+    format!("(Value::{}(a), Value::{}(b)) => {}", kind, kind, result)
+}
+
+/// Checked arithmetic, which panics rather than overflowing.
+fn checked(method: &str, operator: &str) -> String {
+    both(
+        "Number",
+        &format!(
+            "Value::Number(a.{}(b).expect(\"integer overflow in {}\"))",
+            method, operator
+        ),
+    )
+}
+
+/// Generates a `match` applying a prefix or postfix operator to an operand `a` of the given kind.
+fn unary_source(operand: &Expr, kind: &str, result: &str) -> String {
+    format!(
+        "match {} {{\n    Value::{}(a) => {},\n    \
+         other => panic!(\"unexpected value {{:?}}\", other),\n}}",
+        operand.to_rust(),
+        kind,
+        result
+    )
+}
+
+/// Generates a `Slice`, panicking where `operators::slice` would fail.
+fn slice_source(operand: &Expr, start: &Expr, end: &Expr) -> String {
+    let arm = |kind: &str, length: &str, items: &str| {
+        format!(
+            "(Value::{}(a), Value::Number(start), Value::Number(end)) => {{\n        \
+             let length = a.{} as i64;\n        \
+             assert!(0 <= start && start <= length, \"index {{}} is out of range\", start);\n        \
+             assert!(0 <= end && end <= length, \"index {{}} is out of range\", end);\n        \
+             Value::{}(a.{}.skip(start as usize).take((end - start).max(0) as usize).collect())\n    \
+             }}",
+            kind, length, kind, items
+        )
+    };
+    format!(
+        "match ({}, {}, {}) {{\n    {}\n    {}\n    \
+         other => panic!(\"unexpected values {{:?}}\", other),\n}}",
+        operand.to_rust(),
+        start.to_rust(),
+        end.to_rust(),
+        arm("String", "chars().count()", "chars()"),
+        arm("List", "len()", "into_iter()")
+    )
+}
+
+/// Generates a short-circuiting `&&` or `||`, as `logical` describes.
+fn logical_source(left: &Expr, right: &Expr, decisive: bool) -> String {
+    format!(
+        "match {} {{\n    Value::Boolean({}) => Value::Boolean({}),\n    \
+         Value::Boolean(_) => match {} {{\n        Value::Boolean(b) => Value::Boolean(b),\n        \
+         other => panic!(\"unexpected value {{:?}}\", other),\n    }},\n    \
+         other => panic!(\"unexpected value {{:?}}\", other),\n}}",
+        left.to_rust(),
+        decisive,
+        decisive,
+        right.to_rust()
+    )
+}
+
+/// Translates an expression inside the bodies of the `enclosing` functions, innermost last.
+pub(crate) fn expression(node: &Expr, enclosing: &[Weak<Translation>]) -> Denotation<Value> {
+    let e = enclosing;
+    match node {
+        Expr::Value(value) => {
+            let value = value.clone();
+            Box::new(move |_, _| Ok(value.clone()))
+        }
+        Expr::Add(Add(l, r)) => binary(l, r, e, operators::add),
+        Expr::Subtract(Subtract(l, r)) => binary(l, r, e, operators::subtract),
+        Expr::Multiply(Multiply(l, r)) => binary(l, r, e, operators::multiply),
+        Expr::Divide(Divide(l, r)) => binary(l, r, e, operators::divide),
+        Expr::Modulo(Modulo(l, r)) => binary(l, r, e, operators::modulo),
+        Expr::Negate(Negate(operand)) => unary(operand, e, operators::negate),
+        Expr::Equals(Equals(l, r)) => binary(l, r, e, operators::equals),
+        Expr::NotEquals(NotEquals(l, r)) => binary(l, r, e, operators::not_equals),
+        Expr::LessThan(LessThan(l, r)) => binary(l, r, e, operators::less_than),
+        Expr::GreaterThan(GreaterThan(l, r)) => binary(l, r, e, operators::greater_than),
+        Expr::LessOrEqual(LessOrEqual(l, r)) => binary(l, r, e, operators::less_or_equal),
+        Expr::GreaterOrEqual(GreaterOrEqual(l, r)) => binary(l, r, e, operators::greater_or_equal),
+        Expr::And(And(l, r)) => logical(l, r, e, false),
+        Expr::Or(Or(l, r)) => logical(l, r, e, true),
+        Expr::Not(Not(operand)) => unary(operand, e, operators::not),
+        Expr::Length(Length(operand)) => unary(operand, e, operators::length),
+        Expr::Index(Index(operand, index)) => binary(operand, index, e, operators::index),
+        Expr::Slice(node) => slice(node, e),
+        Expr::List(List(elements)) => {
+            let elements: Vec<_> = elements
+                .iter()
+                .map(|element| expression(element, e))
+                .collect();
+            Box::new(move |environment, runtime| {
+                elements
+                    .iter()
+                    .map(|element| element(environment, runtime))
+                    .collect::<Result<_, _>>()
+                    .map(Value::List)
+            })
+        }
+        Expr::Append(Append(list, element)) => binary(list, element, e, operators::append),
+        // Which function a call runs is only known once it looks its name up. Unless the call is
+        // inside that function already, it is translated on the first run, and kept for the runs
+        // after for as long as the name finds the same function.
+        Expr::Call(Call(name, arguments)) => {
+            let name = name.clone();
+            let arguments: Vec<_> = arguments
+                .iter()
+                .map(|argument| expression(argument, e))
+                .collect();
+            let enclosing = enclosing.to_vec();
+            let kept: RefCell<Option<Rc<Translation>>> = RefCell::new(None);
+            Box::new(move |environment, runtime| {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument(environment, runtime))
+                    .collect::<Result<_, _>>()?;
+                let (FunctionDef(_, _, body, result), locals) =
+                    operators::enter(environment, &name, arguments)?;
+                let around = enclosing
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .find(|function| function.translates(&body, &result));
+                let function = around.unwrap_or_else(|| {
+                    let mut kept = kept.borrow_mut();
+                    match &*kept {
+                        Some(function) if function.translates(&body, &result) => function.clone(),
+                        _ => kept
+                            .insert(Translation::new(&body, &result, &enclosing))
+                            .clone(),
+                    }
+                });
+                function.run(&name, &locals, runtime)
+            })
+        }
+        Expr::Frame(Frame(name, locals, body, result)) => {
+            let (name, locals) = (name.clone(), locals.clone());
+            let function = Translation::new(body, result, enclosing);
+            Box::new(move |_, runtime| function.run(&name, &locals, runtime))
+        }
+        Expr::Variable(Variable(name)) => {
+            let name = name.clone();
+            Box::new(move |environment: &Environment, _: &mut Runtime| {
+                match environment.get(&name) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(EvalError::UnboundVariable(name.clone())),
+                }
+            })
+        }
+    }
+}
+
+impl Expression for Expr {
+    fn to_denotation(&self) -> Denotation<Value> {
+        expression(self, &[])
+    }
+
+    fn to_rust(&self) -> String {
+        let comparison = |operator: &str| {
+            let result = format!("Value::Boolean(a {} b)", operator);
+            [both("Number", &result), both("String", &result)]
+        };
+        let division = |method: &str, operator: &str| {
+            [
+                String::from(
+                    "(Value::Number(_), Value::Number(0)) => panic!(\"division by zero\")",
+                ),
+                checked(method, operator),
+            ]
+        };
+        let equality = |operator: &str| {
+            let result = format!("Value::Boolean(a {} b)", operator);
+            [
+                both("Number", &result),
+                both("Boolean", &result),
+                both("String", &result),
+            ]
+        };
+        match self {
+            Expr::Value(Value::Number(value)) => format!("Value::Number({})", value),
+            Expr::Value(Value::BigNumber(value)) => {
+                format!("compile_error!(\"{} does not fit in an i64\")", value)
+            }
+            Expr::Value(Value::Boolean(value)) => format!("Value::Boolean({})", value),
+            Expr::Value(Value::String(value)) => {
+                format!("Value::String(String::from({:?}))", value)
+            }
+            Expr::Value(Value::Function(function)) => function_source(function),
+            Expr::Value(Value::List(values)) => format!(
+                "Value::List(vec![{}])",
+                values
+                    .iter()
+                    .map(|value| Expr::Value(value.clone()).to_rust())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Add(Add(l, r)) => pair_source(
+                l,
+                r,
+                "+",
+                &[
+                    checked("checked_add", "+"),
+                    both("String", "Value::String(a + &b)"),
+                ],
+            ),
+            Expr::Subtract(Subtract(l, r)) => {
+                pair_source(l, r, "-", &[checked("checked_sub", "-")])
+            }
+            Expr::Multiply(Multiply(l, r)) => {
+                pair_source(l, r, "*", &[checked("checked_mul", "*")])
+            }
+            Expr::Divide(Divide(l, r)) => pair_source(l, r, "/", &division("checked_div", "/")),
+            Expr::Modulo(Modulo(l, r)) => pair_source(l, r, "%", &division("checked_rem", "%")),
+            Expr::Negate(Negate(operand)) => unary_source(
+                operand,
+                "Number",
+                "Value::Number(a.checked_neg().expect(\"integer overflow in -\"))",
+            ),
+            Expr::Equals(Equals(l, r)) => pair_source(l, r, "==", &equality("==")),
+            Expr::NotEquals(NotEquals(l, r)) => pair_source(l, r, "!=", &equality("!=")),
+            Expr::LessThan(LessThan(l, r)) => pair_source(l, r, "<", &comparison("<")),
+            Expr::GreaterThan(GreaterThan(l, r)) => pair_source(l, r, ">", &comparison(">")),
+            Expr::LessOrEqual(LessOrEqual(l, r)) => pair_source(l, r, "<=", &comparison("<=")),
+            Expr::GreaterOrEqual(GreaterOrEqual(l, r)) => {
+                pair_source(l, r, ">=", &comparison(">="))
+            }
+            Expr::And(And(l, r)) => logical_source(l, r, false),
+            Expr::Or(Or(l, r)) => logical_source(l, r, true),
+            Expr::Not(Not(operand)) => unary_source(operand, "Boolean", "Value::Boolean(!a)"),
+            Expr::Length(Length(operand)) => format!(
+                "match {} {{\n    Value::String(a) => Value::Number(a.chars().count() as i64),\n    \
+                 Value::List(a) => Value::Number(a.len() as i64),\n    \
+                 other => panic!(\"unexpected value {{:?}}\", other),\n}}",
+                operand.to_rust()
+            ),
+            // A negative index wraps around to a huge `usize`, which is out of range too.
+            Expr::Index(Index(operand, index)) => pair_source(
+                operand,
+                index,
+                "[]",
+                &[
+                    String::from(
+                        "(Value::String(a), Value::Number(b)) => Value::String(\n        \
+                         a.chars().nth(b as usize).expect(\"index out of range\").to_string(),\n    )",
+                    ),
+                    String::from(
+                        "(Value::List(a), Value::Number(b)) => \
+                         a.get(b as usize).cloned().expect(\"index out of range\")",
+                    ),
+                ],
+            ),
+            Expr::Slice(Slice(operand, start, end)) => slice_source(operand, start, end),
+            Expr::List(List(elements)) => format!(
+                "Value::List(vec![{}])",
+                elements
+                    .iter()
+                    .map(|element| element.to_rust())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expr::Append(Append(list, element)) => format!(
+                "match ({}, {}) {{\n    (Value::List(mut a), b) => {{\n        a.push(b);\n        \
+                 Value::List(a)\n    }}\n    \
+                 (a, _) => panic!(\"unexpected value {{:?}}\", a),\n}}",
+                list.to_rust(),
+                element.to_rust()
+            ),
+            Expr::Call(Call(name, arguments)) => format!(
+                "{{\n    let arguments = vec![{}];\n    \
+                 match env.get({:?}).cloned() {{\n        \
+                 Some(Value::Function(_, function)) => function(env, arguments),\n        \
+                 other => panic!(\"cannot call {{:?}}\", other),\n    }}\n}}",
+                arguments
+                    .iter()
+                    .map(|argument| argument.to_rust())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                name
+            ),
+            Expr::Frame(Frame(_, locals, body, result)) => {
+                let mut source = String::from(
+                    "{\n    let mut locals: HashMap<String, Value> = HashMap::new();\n",
+                );
+                for (name, value) in locals.iter() {
+                    let value = Expr::Value(value.clone()).to_rust();
+                    source.push_str(&indent(&format!(
+                        "locals.insert(String::from({:?}), {});",
+                        name, value
+                    )));
+                    source.push('\n');
+                }
+                format!(
+                    "{}{}\n}}",
+                    source,
+                    indent(&function_body_source(body, result))
+                )
+            }
+            Expr::Variable(Variable(name)) => format!(
+                "env.get({:?}).cloned().expect(\"unbound variable `{}`\")",
+                name, name
+            ),
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use crate::ast::{
+    Assign, AssignIndex, Expr, FunctionDef, If, Iteration, Let, Print, Read, Scope, Stmt, While,
+};
+use crate::denotational::expressions::{expression, Expression, Translation};
+use crate::denotational::{indent, with_default_runtime, Closure, Denotation};
+use crate::operators;
+use crate::{Environment, EvalError, Printable, Value};
+use std::rc::Weak;
+
+pub trait Statement {
+    /// Translates the statement into a closure which computes the environment it leaves behind,
+    /// with a particular `Runtime`: its `Overflow` policy, its `Output` for `Print` and its
+    /// `Input` for `Read`.
+    fn to_denotation(&self) -> Denotation<Environment>;
+    /// Translates the statement into a closure which computes the environment it leaves behind,
+    /// with the default `Runtime`.
+    fn to_closure(&self) -> Closure<Environment> {
+        with_default_runtime(self.to_denotation())
+    }
+    /// Translates the statement into Rust statements which update a `HashMap<String, Value>`
+    /// called `env` in place. A `Print` becomes `println!`, so `Value` must implement `Display`,
+    /// showing a value as it would appear in a program, and a `Read` parses a line of standard
+    /// input, so `Value` must also implement `FromStr`.
+    fn to_rust(&self) -> String;
+
+    /// Writes the statement out as a self-contained Rust function. Where the interpreters would
+    /// report an `EvalError`, the generated code panics.
+    fn to_rust_source(&self) -> String {
+        format!(
+            "fn run(env: &mut HashMap<String, Value>) {{\n{}\n}}\n",
+            indent(&self.to_rust())
+        )
+    }
+}
+
+/// Translates the condition of an `If` or `While`, insisting that it produces a boolean.
+fn condition(test: &Expr, enclosing: &[Weak<Translation>]) -> Denotation<bool> {
+    let condition = expression(test, enclosing);
+    Box::new(
+        move |environment, runtime| match condition(environment, runtime)? {
+            Value::Boolean(value) => Ok(value),
+            other => Err(EvalError::NonBooleanCondition(other)),
+        },
+    )
+}
+
+/// How a statement came to an end: by finishing, or by jumping to the end of its innermost loop.
+#[derive(Clone, Copy)]
+enum Completion {
+    Normal,
+    Break,
+    Continue,
+}
+
+/// Translates a statement which may jump out of the statements around it, into a closure which
+/// also tells how the statement came to an end.
+fn flow(node: &Stmt, enclosing: &[Weak<Translation>]) -> Denotation<(Environment, Completion)> {
+    match node {
+        Stmt::Break => Box::new(|environment, _| Ok((environment.clone(), Completion::Break))),
+        Stmt::Continue => {
+            Box::new(|environment, _| Ok((environment.clone(), Completion::Continue)))
+        }
+        Stmt::If(If(test, consequence, alternative)) => {
+            let condition = condition(test, enclosing);
+            let (consequence, alternative) =
+                (flow(consequence, enclosing), flow(alternative, enclosing));
+            Box::new(move |environment, runtime| {
+                if condition(environment, runtime)? {
+                    consequence(environment, runtime)
+                } else {
+                    alternative(environment, runtime)
+                }
+            })
+        }
+        Stmt::Let(Let(name, bound, body)) => {
+            let (name, expression, body) = (
+                name.clone(),
+                expression(bound, enclosing),
+                scope(node, body, enclosing),
+            );
+            Box::new(move |environment, runtime| {
+                let value = expression(environment, runtime)?;
+                body(&environment.enter_scope(&name, value), runtime)
+            })
+        }
+        Stmt::Scope(Scope(_, body)) => scope(node, body, enclosing),
+        // The statements run one after another in a loop, rather than each closure calling the
+        // next, so that a long program does not use up the stack.
+        Stmt::Sequence(sequence) => {
+            let statements: Vec<_> = sequence
+                .statements()
+                .map(|statement| flow(statement, enclosing))
+                .collect();
+            Box::new(move |environment, runtime| {
+                let mut environment = environment.clone();
+                for statement in &statements {
+                    match statement(&environment, runtime)? {
+                        (after, Completion::Normal) => environment = after,
+                        jumped => return Ok(jumped),
+                    }
+                }
+                Ok((environment, Completion::Normal))
+            })
+        }
+        Stmt::Sugared(sugared) => flow(sugared.core(), enclosing),
+        Stmt::While(node) => {
+            let meaning = looping(node, enclosing);
+            Box::new(move |environment, runtime| {
+                Ok((meaning(environment, runtime)?, Completion::Normal))
+            })
+        }
+        Stmt::Iteration(Iteration(body, node)) => {
+            let (body, rest) = (flow(body, enclosing), looping(node, enclosing));
+            Box::new(
+                move |environment, runtime| match body(environment, runtime)? {
+                    (environment, Completion::Break) => Ok((environment, Completion::Normal)),
+                    (environment, _) => Ok((rest(&environment, runtime)?, Completion::Normal)),
+                },
+            )
+        }
+        other => {
+            let meaning = statement(other, enclosing);
+            Box::new(move |environment, runtime| {
+                Ok((meaning(environment, runtime)?, Completion::Normal))
+            })
+        }
+    }
+}
+
+/// Translates a loop, which ends when its condition fails or its body breaks out.
+fn looping(While(test, body): &While, enclosing: &[Weak<Translation>]) -> Denotation<Environment> {
+    let (condition, body) = (condition(test, enclosing), flow(body, enclosing));
+    Box::new(move |environment, runtime| {
+        let mut environment = environment.clone();
+        while condition(&environment, runtime)? {
+            let (after, completion) = body(&environment, runtime)?;
+            environment = after;
+            if let Completion::Break = completion {
+                break;
+            }
+        }
+        Ok(environment)
+    })
+}
+
+/// Translates the body of a scope, which is exited once the body is done, however it ends.
+fn scope(
+    node: &Stmt,
+    body: &Stmt,
+    enclosing: &[Weak<Translation>],
+) -> Denotation<(Environment, Completion)> {
+    let (inspected, body) = (node.inspect(), flow(body, enclosing));
+    Box::new(move |environment, runtime| {
+        let (environment, completion) = body(environment, runtime)?;
+        match environment.exit_scope() {
+            Some(environment) => Ok((environment, completion)),
+            None => Err(EvalError::Irreducible(inspected.clone())),
+        }
+    })
+}
+
+/// Generates an expression unpacking the boolean result of a condition.
+fn condition_source(expression: &Expr) -> String {
+    format!(
+        "match {} {{\n    Value::Boolean(value) => value,\n    \
+         other => panic!(\"condition must be boolean, got {{:?}}\", other),\n}}",
+        expression.to_rust()
+    )
+}
+
+/// What the generated code for a statement sits inside: the code to restore what each enclosing
+/// `let` hid, outermost first, and how many of those were outside the innermost loop. A `break`
+/// or `continue` leaves the scopes inside the loop behind, so it must restore them itself.
+#[derive(Clone, Default)]
+struct Surroundings {
+    restores: Vec<String>,
+    innermost_loop: Option<usize>,
+}
+
+impl Surroundings {
+    fn in_loop(&self) -> Self {
+        Self {
+            restores: self.restores.clone(),
+            innermost_loop: Some(self.restores.len()),
+        }
+    }
+
+    fn in_scope(&self, restore: String) -> Self {
+        let mut surroundings = self.clone();
+        surroundings.restores.push(restore);
+        surroundings
+    }
+}
+
+/// Generates a `break` or `continue`, restoring what the scopes it leaves had hidden.
+fn jump_source(jump: &Stmt, surroundings: &Surroundings) -> String {
+    match surroundings.innermost_loop {
+        Some(start) => {
+            let mut lines: Vec<String> = surroundings.restores[start..]
+                .iter()
+                .rev()
+                .cloned()
+                .collect();
+            lines.push(format!("{};", jump.to_s()));
+            lines.join("\n")
+        }
+        None => format!("panic!(\"`{}` outside a loop\");", jump.to_s()),
+    }
+}
+
+/// Generates Rust statements for a statement in its `surroundings`.
+fn source(statement: &Stmt, surroundings: &Surroundings) -> String {
+    match statement {
+        Stmt::DoNothing => String::from("{}"),
+        Stmt::Assign(Assign(name, expression)) => format!(
+            "let value = {};\nenv.insert(String::from({:?}), value);",
+            expression.to_rust(),
+            name
+        ),
+        Stmt::AssignIndex(AssignIndex(name, index, expression)) => format!(
+            "let index = {};\nlet value = {};\n\
+             match (env.get_mut({:?}), index) {{\n    \
+             (Some(Value::List(items)), Value::Number(i)) => {{\n        \
+             let item = items.get_mut(i as usize).expect(\"index out of range\");\n        \
+             *item = value;\n    \
+             }}\n    \
+             (list, index) => panic!(\"cannot assign to {{:?}} at {{:?}}\", list, index),\n}}",
+            index.to_rust(),
+            expression.to_rust(),
+            name
+        ),
+        Stmt::Break | Stmt::Continue => jump_source(statement, surroundings),
+        Stmt::FunctionDef(function) => {
+            let FunctionDef(name, _, _, _) = function;
+            Stmt::from(Assign::new(name.clone(), Value::Function(function.clone()))).to_rust()
+        }
+        Stmt::If(If(test, consequence, alternative)) => format!(
+            "if {} {{\n{}\n}} else {{\n{}\n}}",
+            condition_source(test),
+            indent(&source(consequence, surroundings)),
+            indent(&source(alternative, surroundings))
+        ),
+        // A `HashMap` has no scopes, so the variable is declared by saving whatever it hides,
+        // and restoring that afterwards.
+        Stmt::Let(Let(name, expression, body)) => {
+            let hidden = format!("hidden{}", surroundings.restores.len());
+            let restore = format!(
+                "match {} {{\n    Some(value) => env.insert(String::from({:?}), value),\n    \
+                 None => env.remove({:?}),\n}};",
+                hidden, name, name
+            );
+            format!(
+                "{{\n    let value = {};\n    let {} = env.insert(String::from({:?}), value);\n\
+                 {}\n{}\n}}",
+                expression.to_rust(),
+                hidden,
+                name,
+                indent(&source(body, &surroundings.in_scope(restore.clone()))),
+                indent(&restore)
+            )
+        }
+        // What the variable of a scope already entered hides is not known here, so it is
+        // simply removed.
+        Stmt::Scope(Scope(name, body)) => {
+            let restore = format!("env.remove({:?});", name);
+            format!(
+                "{}\n{}",
+                source(body, &surroundings.in_scope(restore.clone())),
+                restore
+            )
+        }
+        Stmt::Print(Print(expression)) => format!(
+            "match {} {{\n    Value::String(text) => println!(\"{{}}\", text),\n    \
+             value => println!(\"{{}}\", value),\n}}",
+            expression.to_rust()
+        ),
+        Stmt::Read(Read(name)) => format!(
+            "let mut line = String::new();\n\
+             let read = std::io::stdin().read_line(&mut line).expect(\"cannot read input\");\n\
+             assert!(read > 0, \"no input left to read into `{}`\");\n\
+             let value: Value = line.trim_end().parse().expect(\"malformed input\");\n\
+             env.insert(String::from({:?}), value);",
+            name, name
+        ),
+        Stmt::Sequence(sequence) => sequence
+            .statements()
+            .map(|statement| source(statement, surroundings))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Stmt::Sugared(sugared) => source(sugared.core(), surroundings),
+        Stmt::While(While(test, body)) => format!(
+            "while {} {{\n{}\n}}",
+            condition_source(test),
+            indent(&source(body, &surroundings.in_loop()))
+        ),
+        // The rest of the current pass comes first, without testing the condition.
+        Stmt::Iteration(Iteration(rest, While(test, body))) => {
+            let inside = surroundings.in_loop();
+            format!(
+                "let mut resuming = true;\nwhile resuming || {} {{\n    \
+                 if resuming {{\n        resuming = false;\n{}\n    }} else {{\n{}\n    }}\n}}",
+                condition_source(test),
+                indent(&indent(&source(rest, &inside))),
+                indent(&indent(&source(body, &inside)))
+            )
+        }
+    }
+}
+
+/// Translates a statement inside the bodies of the `enclosing` functions, innermost last.
+pub(crate) fn statement(node: &Stmt, enclosing: &[Weak<Translation>]) -> Denotation<Environment> {
+    match node {
+        Stmt::DoNothing => Box::new(|environment, _| Ok(environment.clone())),
+        Stmt::Assign(Assign(name, assigned)) => {
+            let (name, assigned) = (name.clone(), expression(assigned, enclosing));
+            Box::new(move |environment, runtime| {
+                Ok(environment.update(&name, assigned(environment, runtime)?))
+            })
+        }
+        Stmt::AssignIndex(AssignIndex(name, index, assigned)) => {
+            let (name, index, assigned) = (
+                name.clone(),
+                expression(index, enclosing),
+                expression(assigned, enclosing),
+            );
+            Box::new(move |environment, runtime| {
+                let (index, value) = (
+                    index(environment, runtime)?,
+                    assigned(environment, runtime)?,
+                );
+                let list = match environment.get(&name) {
+                    Some(list) => list.clone(),
+                    None => return Err(EvalError::UnboundVariable(name.clone())),
+                };
+                Ok(environment.update(&name, operators::replace(list, index, value)?))
+            })
+        }
+        // The body is only translated once the function is called.
+        Stmt::FunctionDef(function) => {
+            let FunctionDef(name, _, _, _) = function;
+            let (name, value) = (name.clone(), Value::Function(function.clone()));
+            Box::new(move |environment, _| Ok(environment.update(&name, value.clone())))
+        }
+        Stmt::Print(Print(printed)) => {
+            let printed = expression(printed, enclosing);
+            Box::new(move |environment, runtime| {
+                let value = printed(environment, runtime)?;
+                runtime.output.print(&value)?;
+                Ok(environment.clone())
+            })
+        }
+        Stmt::Read(Read(name)) => {
+            let name = name.clone();
+            Box::new(move |environment, runtime| {
+                Ok(environment.update(&name, runtime.input.read(&name)?))
+            })
+        }
+        // Anything which might jump must say how it ended, but a jump with no loop to go to
+        // goes no further.
+        _ => {
+            let meaning = flow(node, enclosing);
+            Box::new(
+                move |environment, runtime| match meaning(environment, runtime)? {
+                    (environment, Completion::Normal) => Ok(environment),
+                    (_, Completion::Break) => Err(EvalError::OutsideLoop(String::from("break"))),
+                    (_, Completion::Continue) => {
+                        Err(EvalError::OutsideLoop(String::from("continue")))
+                    }
+                },
+            )
+        }
+    }
+}
+
+impl Statement for Stmt {
+    fn to_denotation(&self) -> Denotation<Environment> {
+        statement(self, &[])
+    }
+
+    fn to_rust(&self) -> String {
+        source(self, &Surroundings::default())
+    }
+}
@@ -0,0 +1,306 @@
+//! Lowers each form of `Sugar` into the core statements, which are all any semantics implements.
+//! The lowered statement is kept alongside the sugared one in a `Sugared`, so a program still
+//! prints the way it was written.
+use crate::ast::{
+    Add, AddAssign, And, Append, Assign, AssignIndex, Call, Divide, DoWhile, Equals, Expr, For,
+    FunctionDef, GreaterOrEqual, GreaterThan, If, Index, Iteration, Length, LessOrEqual, LessThan,
+    Let, List, Modulo, Multiply, Negate, Not, NotEquals, Or, Print, Read, Scope, Sequence, Slice,
+    Stmt, Subtract, Sugar, Unless, Variable, While,
+};
+use std::rc::Rc;
+
+pub(crate) fn lower(sugar: &Sugar) -> Stmt {
+    match sugar {
+        // `x = x + e`
+        Sugar::AddAssign(AddAssign(name, expression)) => Assign::new(
+            name.clone(),
+            Add::new(Variable::new(name.clone()), expression.clone()),
+        )
+        .into(),
+        // A pass through the body, then the loop: `break` and `continue` work in the first pass
+        // just as in the rest, and the body is known to have run at least once.
+        Sugar::DoWhile(DoWhile(body, condition)) => {
+            Iteration(body.clone(), While(condition.clone(), body.clone())).into()
+        }
+        // `init; while (condition) { body; step }`, where each `continue` runs the step first.
+        // A `continue` inside a `let` runs the step in that scope, so a `let` whose variable the
+        // step uses has it renamed to a name the loop doesn't use.
+        Sugar::For(looped @ For(init, condition, step, body)) => {
+            let body = redirect(
+                body,
+                &Sequence(step.clone(), Rc::new(Stmt::Continue)).into(),
+                step,
+                looped,
+            );
+            Sequence::new(
+                (**init).clone(),
+                While::new(condition.clone(), Sequence(Rc::new(body), step.clone())),
+            )
+            .into()
+        }
+        // `if (condition) { do-nothing } else { body }`
+        Sugar::Unless(Unless(condition, body)) => {
+            If(condition.clone(), Rc::new(Stmt::DoNothing), body.clone()).into()
+        }
+    }
+}
+
+/// Replaces each `continue` which would go back to the loop around `statement`, passing over any
+/// in loops or functions of their own.
+fn redirect(statement: &Stmt, replacement: &Stmt, step: &Stmt, looped: &For) -> Stmt {
+    let redirect = |statement: &Stmt| redirect(statement, replacement, step, looped);
+    match statement {
+        Stmt::Continue => replacement.clone(),
+        Stmt::If(If(condition, consequence, alternative)) => If::new(
+            condition.clone(),
+            redirect(consequence),
+            redirect(alternative),
+        )
+        .into(),
+        Stmt::Let(Let(name, expression, body)) if mentions(step, name) => {
+            let fresh = fresh(name, looped);
+            Let::new(
+                fresh.clone(),
+                expression.clone(),
+                redirect(&rename(body, name, &fresh)),
+            )
+            .into()
+        }
+        Stmt::Let(Let(name, expression, body)) => {
+            Let::new(name.clone(), expression.clone(), redirect(body)).into()
+        }
+        Stmt::Scope(Scope(name, body)) => Scope(name.clone(), Rc::new(redirect(body))).into(),
+        Stmt::Sequence(Sequence(first, second)) => {
+            Sequence::new(redirect(first), redirect(second)).into()
+        }
+        Stmt::Sugared(sugared) => match sugared.sugar() {
+            // Only the part of a `for` before its loop belongs to the loop around it.
+            Sugar::For(For(init, condition, step, body)) => For(
+                Rc::new(redirect(init)),
+                condition.clone(),
+                step.clone(),
+                body.clone(),
+            )
+            .into(),
+            Sugar::Unless(Unless(condition, body)) => {
+                Unless::new(condition.clone(), redirect(body)).into()
+            }
+            Sugar::AddAssign(_) | Sugar::DoWhile(_) => statement.clone(),
+        },
+        Stmt::DoNothing
+        | Stmt::Assign(_)
+        | Stmt::AssignIndex(_)
+        | Stmt::Break
+        | Stmt::FunctionDef(_)
+        | Stmt::Iteration(_)
+        | Stmt::Print(_)
+        | Stmt::Read(_)
+        | Stmt::While(_) => statement.clone(),
+    }
+}
+
+/// The first of `name_1`, `name_2`, ... which appears nowhere in the loop.
+fn fresh(name: &str, looped: &For) -> String {
+    let For(init, condition, step, body) = looped;
+    (1..)
+        .map(|n| format!("{}_{}", name, n))
+        .find(|fresh| {
+            !mentions(init, fresh)
+                && !mentions_expression(condition, fresh)
+                && !mentions(step, fresh)
+                && !mentions(body, fresh)
+        })
+        .expect("some name is unused")
+}
+
+/// Whether `name` appears anywhere in a statement, bound or not.
+fn mentions(statement: &Stmt, name: &str) -> bool {
+    let mentions_expression = |expression: &Expr| mentions_expression(expression, name);
+    match statement {
+        Stmt::DoNothing | Stmt::Break | Stmt::Continue => false,
+        Stmt::Assign(Assign(assigned, expression)) => {
+            assigned == name || mentions_expression(expression)
+        }
+        Stmt::AssignIndex(AssignIndex(assigned, index, expression)) => {
+            assigned == name || mentions_expression(index) || mentions_expression(expression)
+        }
+        Stmt::FunctionDef(FunctionDef(defined, parameters, body, result)) => {
+            defined == name
+                || parameters.iter().any(|parameter| parameter == name)
+                || mentions(body, name)
+                || mentions_expression(result)
+        }
+        Stmt::If(If(condition, consequence, alternative)) => {
+            mentions_expression(condition)
+                || mentions(consequence, name)
+                || mentions(alternative, name)
+        }
+        Stmt::Let(Let(bound, expression, body)) => {
+            bound == name || mentions_expression(expression) || mentions(body, name)
+        }
+        Stmt::Scope(Scope(bound, body)) => bound == name || mentions(body, name),
+        Stmt::Print(Print(expression)) => mentions_expression(expression),
+        Stmt::Read(Read(read)) => read == name,
+        Stmt::Sequence(Sequence(first, second)) => mentions(first, name) || mentions(second, name),
+        Stmt::While(While(condition, body)) => {
+            mentions_expression(condition) || mentions(body, name)
+        }
+        Stmt::Iteration(Iteration(first, While(condition, body))) => {
+            mentions(first, name) || mentions_expression(condition) || mentions(body, name)
+        }
+        Stmt::Sugared(sugared) => mentions(sugared.core(), name),
+    }
+}
+
+fn mentions_expression(expression: &Expr, name: &str) -> bool {
+    match expression {
+        Expr::Variable(Variable(variable)) => variable == name,
+        Expr::Call(Call(called, arguments)) => {
+            called == name
+                || arguments
+                    .iter()
+                    .any(|argument| mentions_expression(argument, name))
+        }
+        _ => operands(expression)
+            .into_iter()
+            .any(|operand| mentions_expression(operand, name)),
+    }
+}
+
+/// The expressions directly inside an expression; a frame's are its own.
+fn operands(expression: &Expr) -> Vec<&Rc<Expr>> {
+    match expression {
+        Expr::Value(_) | Expr::Frame(_) | Expr::Variable(_) => vec![],
+        Expr::Negate(Negate(operand)) | Expr::Not(Not(operand)) | Expr::Length(Length(operand)) => {
+            vec![operand]
+        }
+        Expr::Add(Add(left, right))
+        | Expr::Subtract(Subtract(left, right))
+        | Expr::Multiply(Multiply(left, right))
+        | Expr::Divide(Divide(left, right))
+        | Expr::Modulo(Modulo(left, right))
+        | Expr::Equals(Equals(left, right))
+        | Expr::NotEquals(NotEquals(left, right))
+        | Expr::LessThan(LessThan(left, right))
+        | Expr::GreaterThan(GreaterThan(left, right))
+        | Expr::LessOrEqual(LessOrEqual(left, right))
+        | Expr::GreaterOrEqual(GreaterOrEqual(left, right))
+        | Expr::And(And(left, right))
+        | Expr::Or(Or(left, right))
+        | Expr::Index(Index(left, right))
+        | Expr::Append(Append(left, right)) => vec![left, right],
+        Expr::Slice(Slice(list, start, end)) => vec![list, start, end],
+        Expr::List(List(elements)) | Expr::Call(Call(_, elements)) => elements.iter().collect(),
+    }
+}
+
+/// Renames the variable `from` to `to` wherever a statement uses it, up to any `let` which binds
+/// `from` again. Functions only see their parameters and the globals, so their bodies are left.
+fn rename(statement: &Stmt, from: &str, to: &str) -> Stmt {
+    let name = |name: &String| if name == from { to } else { name }.to_string();
+    let expression = |expression: &Expr| rename_expression(expression, from, to);
+    let rename = |statement: &Stmt| rename(statement, from, to);
+    match statement {
+        Stmt::DoNothing | Stmt::Break | Stmt::Continue => statement.clone(),
+        Stmt::Assign(Assign(assigned, assignment)) => {
+            Assign::new(name(assigned), expression(assignment)).into()
+        }
+        Stmt::AssignIndex(AssignIndex(assigned, index, assignment)) => {
+            AssignIndex::new(name(assigned), expression(index), expression(assignment)).into()
+        }
+        Stmt::FunctionDef(FunctionDef(defined, parameters, body, result)) => FunctionDef(
+            name(defined),
+            parameters.clone(),
+            body.clone(),
+            result.clone(),
+        )
+        .into(),
+        Stmt::If(If(condition, consequence, alternative)) => If::new(
+            expression(condition),
+            rename(consequence),
+            rename(alternative),
+        )
+        .into(),
+        Stmt::Let(Let(bound, bound_to, body)) if bound == from => {
+            Let(bound.clone(), expression(bound_to), body.clone()).into()
+        }
+        Stmt::Let(Let(bound, bound_to, body)) => {
+            Let::new(bound.clone(), expression(bound_to), rename(body)).into()
+        }
+        Stmt::Scope(Scope(bound, _)) if bound == from => statement.clone(),
+        Stmt::Scope(Scope(bound, body)) => Scope::new(bound.clone(), rename(body)).into(),
+        Stmt::Print(Print(printed)) => Print::new(expression(printed)).into(),
+        Stmt::Read(Read(read)) => Read::new(name(read)).into(),
+        Stmt::Sequence(Sequence(first, second)) => {
+            Sequence::new(rename(first), rename(second)).into()
+        }
+        Stmt::While(While(condition, body)) => {
+            While::new(expression(condition), rename(body)).into()
+        }
+        Stmt::Iteration(Iteration(first, While(condition, body))) => Iteration(
+            Rc::new(rename(first)),
+            While::new(expression(condition), rename(body)),
+        )
+        .into(),
+        Stmt::Sugared(sugared) => match sugared.sugar() {
+            Sugar::AddAssign(AddAssign(assigned, addend)) => {
+                AddAssign::new(name(assigned), expression(addend)).into()
+            }
+            Sugar::DoWhile(DoWhile(body, condition)) => {
+                DoWhile::new(rename(body), expression(condition)).into()
+            }
+            Sugar::For(For(init, condition, step, body)) => For::new(
+                rename(init),
+                expression(condition),
+                rename(step),
+                rename(body),
+            )
+            .into(),
+            Sugar::Unless(Unless(condition, body)) => {
+                Unless::new(expression(condition), rename(body)).into()
+            }
+        },
+    }
+}
+
+fn rename_expression(expression: &Expr, from: &str, to: &str) -> Expr {
+    let rename = |expression: &Rc<Expr>| Rc::new(rename_expression(expression, from, to));
+    match expression {
+        Expr::Value(_) | Expr::Frame(_) => expression.clone(),
+        Expr::Variable(Variable(name)) if name == from => Variable::new(to).into(),
+        Expr::Variable(_) => expression.clone(),
+        Expr::Add(Add(left, right)) => Add(rename(left), rename(right)).into(),
+        Expr::Subtract(Subtract(left, right)) => Subtract(rename(left), rename(right)).into(),
+        Expr::Multiply(Multiply(left, right)) => Multiply(rename(left), rename(right)).into(),
+        Expr::Divide(Divide(left, right)) => Divide(rename(left), rename(right)).into(),
+        Expr::Modulo(Modulo(left, right)) => Modulo(rename(left), rename(right)).into(),
+        Expr::Negate(Negate(operand)) => Negate(rename(operand)).into(),
+        Expr::Equals(Equals(left, right)) => Equals(rename(left), rename(right)).into(),
+        Expr::NotEquals(NotEquals(left, right)) => NotEquals(rename(left), rename(right)).into(),
+        Expr::LessThan(LessThan(left, right)) => LessThan(rename(left), rename(right)).into(),
+        Expr::GreaterThan(GreaterThan(left, right)) => {
+            GreaterThan(rename(left), rename(right)).into()
+        }
+        Expr::LessOrEqual(LessOrEqual(left, right)) => {
+            LessOrEqual(rename(left), rename(right)).into()
+        }
+        Expr::GreaterOrEqual(GreaterOrEqual(left, right)) => {
+            GreaterOrEqual(rename(left), rename(right)).into()
+        }
+        Expr::And(And(left, right)) => And(rename(left), rename(right)).into(),
+        Expr::Or(Or(left, right)) => Or(rename(left), rename(right)).into(),
+        Expr::Not(Not(operand)) => Not(rename(operand)).into(),
+        Expr::Length(Length(operand)) => Length(rename(operand)).into(),
+        Expr::Index(Index(list, index)) => Index(rename(list), rename(index)).into(),
+        Expr::Slice(Slice(list, start, end)) => {
+            Slice(rename(list), rename(start), rename(end)).into()
+        }
+        Expr::List(List(elements)) => List(elements.iter().map(rename).collect()).into(),
+        Expr::Append(Append(list, element)) => Append(rename(list), rename(element)).into(),
+        Expr::Call(Call(name, arguments)) => Call(
+            if name == from { to } else { name }.to_string(),
+            arguments.iter().map(rename).collect(),
+        )
+        .into(),
+    }
+}
@@ -1,13 +1,46 @@
-use std::collections::HashMap;
+use crate::ast::FunctionDef;
+use crate::bignum::BigInt;
+use crate::map::Map;
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
 use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::iter::{self, FromIterator};
 
+pub mod ast;
 pub mod big_step;
+pub mod bignum;
+pub mod denotational;
+mod desugar;
+mod map;
+mod operators;
+pub mod optimize;
+pub mod parser;
+pub mod signs;
 pub mod small_step;
+pub mod types;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Value {
     Number(i64),
+    /// A number outside the range of `i64`. Anything smaller is always a `Number`, so each number
+    /// has one representation.
+    BigNumber(BigInt),
     Boolean(bool),
+    String(String),
+    List(Vec<Value>),
+    /// A function, bound to its name by a `FunctionDef`.
+    Function(FunctionDef),
+}
+
+impl Value {
+    /// The `Value` for a number of any size.
+    pub fn integer(number: BigInt) -> Self {
+        match number.to_i64() {
+            Some(small) => Value::Number(small),
+            None => Value::BigNumber(number),
+        }
+    }
 }
 
 pub trait Printable {
@@ -15,47 +48,504 @@ pub trait Printable {
         format!("«{}»", self.to_s())
     }
     fn to_s(&self) -> String;
+    /// How tightly the term binds when printed beside an operator (higher binds tighter).
+    fn precedence(&self) -> u8 {
+        u8::MAX
+    }
+}
+
+/// Prints a prefix operator, parenthesizing an operand which binds more loosely.
+pub(crate) fn prefix<T: Printable + ?Sized>(operator: &str, operand: &T, precedence: u8) -> String {
+    if operand.precedence() < precedence {
+        format!("{}({})", operator, operand.to_s())
+    } else {
+        format!("{}{}", operator, operand.to_s())
+    }
+}
+
+/// Prints a postfix operator such as `.length`, parenthesizing an operand which binds more loosely.
+pub(crate) fn postfix<T: Printable + ?Sized>(
+    operand: &T,
+    operator: &str,
+    precedence: u8,
+) -> String {
+    if operand.precedence() < precedence {
+        format!("({}){}", operand.to_s(), operator)
+    } else {
+        format!("{}{}", operand.to_s(), operator)
+    }
+}
+
+/// Prints a left-associative binary operator, parenthesizing any operand that would otherwise
+/// be read back differently.
+pub(crate) fn infix<L, R>(left: &L, operator: &str, right: &R, precedence: u8) -> String
+where
+    L: Printable + ?Sized,
+    R: Printable + ?Sized,
+{
+    let left = if left.precedence() < precedence {
+        format!("({})", left.to_s())
+    } else {
+        left.to_s()
+    };
+    let right = if right.precedence() <= precedence {
+        format!("({})", right.to_s())
+    } else {
+        right.to_s()
+    };
+    format!("{} {} {}", left, operator, right)
+}
+
+/// Writes a string as a literal, escaping whatever could not appear between its quotes.
+fn quote(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
 impl Printable for Value {
     fn to_s(&self) -> String {
         match &self {
             Value::Number(value) => format!("{}", value),
+            Value::BigNumber(value) => format!("{}", value),
             Value::Boolean(value) => format!("{}", value),
+            Value::String(value) => quote(value),
+            Value::List(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(Printable::to_s)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Function(function) => function.to_s(),
+        }
+    }
+}
+
+/// Everything that can go wrong while reducing or evaluating a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    /// A `Variable` was referenced before anything was assigned to it.
+    UnboundVariable(String),
+    /// An operator was given a value of the wrong type.
+    TypeMismatch(Value),
+    /// The condition of an `If` or `While` was not a boolean.
+    NonBooleanCondition(Value),
+    /// Something asked to reduce a term that is already fully reduced.
+    Irreducible(String),
+    /// A `Divide` or `Modulo` had zero on the right.
+    DivisionByZero,
+    /// Arithmetic left the range of `i64` under `Overflow::Checked`.
+    Overflow(String),
+    /// An `Index`, `Slice` or `AssignIndex` reached outside its string or list.
+    IndexOutOfRange { index: Value, length: usize },
+    /// A `Call` passed a different number of arguments from the function's parameters.
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A `Print` could not write to its `Output`.
+    Output(String),
+    /// A `Read` could not read from its `Input`.
+    Input(String),
+    /// A `Read` found its `Input` empty, with nothing to assign to the variable it names.
+    EndOfInput(String),
+    /// A `Read` found a line of input which is not a literal value.
+    MalformedInput { input: String, error: String },
+    /// A `Break` or `Continue` was reached with no loop around it to leave.
+    OutsideLoop(String),
+    /// A `Call` to the named function began with as many calls already unfinished as the run
+    /// allows.
+    CallDepth(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            EvalError::UnboundVariable(name) => write!(f, "unbound variable `{}`", name),
+            EvalError::TypeMismatch(value) => write!(f, "unexpected value {}", value.inspect()),
+            EvalError::NonBooleanCondition(value) => {
+                write!(f, "condition must be boolean, got {}", value.inspect())
+            }
+            EvalError::Irreducible(term) => write!(f, "cannot reduce {}", term),
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::Overflow(operation) => write!(f, "integer overflow in {}", operation),
+            EvalError::IndexOutOfRange { index, length } => write!(
+                f,
+                "index {} is out of range for length {}",
+                index.to_s(),
+                length
+            ),
+            EvalError::ArgumentCount {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` takes {} arguments, but was given {}",
+                function, expected, found
+            ),
+            EvalError::Output(error) => write!(f, "cannot write output: {}", error),
+            EvalError::Input(error) => write!(f, "cannot read input: {}", error),
+            EvalError::EndOfInput(name) => write!(f, "no input left to read into `{}`", name),
+            EvalError::MalformedInput { input, error } => {
+                write!(f, "cannot read `{}` as a value: {}", input, error)
+            }
+            EvalError::OutsideLoop(jump) => write!(f, "`{}` outside a loop", jump),
+            EvalError::CallDepth(name) => write!(
+                f,
+                "calling `{}` nests more calls inside one another than the run allows",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+/// How a run with a limited budget of steps came to an end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program ran to completion, leaving this environment behind.
+    Finished(Environment),
+    /// The budget ran out after `steps` steps, with the program part way through.
+    OutOfFuel {
+        steps: usize,
+        environment: Environment,
+    },
+}
+
+/// What arithmetic does with a result too large or too small for an `i64`. The policy is chosen
+/// per run, so a program behaves the same however this crate was compiled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Keep the exact result, as a `Value::BigNumber`.
+    #[default]
+    Promote,
+    /// Stop with `EvalError::Overflow`.
+    Checked,
+    /// Wrap around, as two's complement hardware does.
+    Wrapping,
+    /// Clamp to `i64::MIN` or `i64::MAX`.
+    Saturating,
+}
+
+/// Where the `Print` statements of a program send what they print, one line at a time. This is
+/// separate from the trace of configurations that `Machine::run` prints.
+#[derive(Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    /// Keeps every line in memory, to be inspected once the program has run.
+    Buffer(Vec<String>),
+    /// Writes to anything else, such as a file or a socket.
+    Writer(Box<dyn Write>),
+}
+
+impl Output {
+    /// An empty `Buffer`.
+    pub fn buffer() -> Self {
+        Output::Buffer(Vec::new())
+    }
+
+    /// The lines printed so far, if they are being kept in a `Buffer`.
+    pub fn lines(&self) -> Option<&[String]> {
+        match self {
+            Output::Buffer(lines) => Some(lines),
+            _ => None,
         }
     }
+
+    /// Prints a value on a line of its own: a string as its bare text, anything else as it would
+    /// appear in a program.
+    pub(crate) fn print(&mut self, value: &Value) -> Result<(), EvalError> {
+        let line = match value {
+            Value::String(text) => text.clone(),
+            other => other.to_s(),
+        };
+        let written = match self {
+            Output::Stdout => writeln!(io::stdout(), "{}", line),
+            Output::Buffer(lines) => {
+                lines.push(line);
+                Ok(())
+            }
+            Output::Writer(writer) => writeln!(writer, "{}", line),
+        };
+        written.map_err(|error| EvalError::Output(error.to_string()))
+    }
 }
 
-#[derive(Clone)]
-pub struct Environment(HashMap<String, Value>);
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Output::Stdout => write!(f, "Stdout"),
+            Output::Buffer(lines) => f.debug_tuple("Buffer").field(lines).finish(),
+            Output::Writer(_) => write!(f, "Writer(..)"),
+        }
+    }
+}
+
+/// Where the `Read` statements of a program take their values from. Each line of text input holds
+/// one value, written as a literal just as it would be in a program: `-7`, `"text"` or `[1, 2]`.
+#[derive(Default)]
+pub enum Input {
+    #[default]
+    Stdin,
+    /// Values waiting to be read, in order.
+    Queue(VecDeque<Value>),
+    /// Reads lines from anything else, such as a file.
+    Reader(Box<dyn BufRead>),
+}
+
+impl Input {
+    /// A `Queue` holding these values.
+    pub fn queue<I: IntoIterator<Item = Value>>(values: I) -> Self {
+        Input::Queue(values.into_iter().collect())
+    }
+
+    /// Takes the next value, to be assigned to the variable `name`.
+    pub(crate) fn read(&mut self, name: &str) -> Result<Value, EvalError> {
+        let mut line = String::new();
+        let read = match self {
+            Input::Stdin => io::stdin().lock().read_line(&mut line),
+            Input::Queue(values) => {
+                return values
+                    .pop_front()
+                    .ok_or_else(|| EvalError::EndOfInput(name.to_string()))
+            }
+            Input::Reader(reader) => reader.read_line(&mut line),
+        };
+        match read {
+            Ok(0) => Err(EvalError::EndOfInput(name.to_string())),
+            Ok(_) => {
+                let input = line.trim_end_matches(&['\n', '\r'][..]);
+                parser::parse_value(input).map_err(|error| EvalError::MalformedInput {
+                    input: input.to_string(),
+                    error: error.to_string(),
+                })
+            }
+            Err(error) => Err(EvalError::Input(error.to_string())),
+        }
+    }
+}
+
+impl fmt::Debug for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Input::Stdin => write!(f, "Stdin"),
+            Input::Queue(values) => f.debug_tuple("Queue").field(values).finish(),
+            Input::Reader(_) => write!(f, "Reader(..)"),
+        }
+    }
+}
+
+/// How many calls may be unfinished at once, unless a `Runtime` says otherwise.
+pub const DEFAULT_CALL_DEPTH: usize = 10_000;
+
+/// How many calls may be unfinished at once in the semantics which run a call's body on the native
+/// stack: small-step reduction and denotational closures. Runaway recursion stops there with
+/// `EvalError::CallDepth` before that stack overflows, as calls this deep still fit in the two
+/// megabytes a spawned thread gets, even in a debug build. Big-step evaluation keeps its calls on a
+/// stack of its own, and is held only to the `Runtime`'s limit. The type checker, which checks the
+/// body of each function called, looks no deeper than this into calls within calls.
+pub const NATIVE_CALL_DEPTH: usize = 100;
+
+/// What a run carries along with it besides the environment: the `Overflow` policy, the `Output`
+/// for `Print`, the `Input` for `Read`, and how many calls are unfinished.
+#[derive(Debug)]
+pub struct Runtime {
+    pub overflow: Overflow,
+    pub output: Output,
+    pub input: Input,
+    /// How many calls may be unfinished at once: `DEFAULT_CALL_DEPTH` unless set otherwise.
+    pub max_call_depth: usize,
+    /// Calls begun and not yet finished.
+    depth: usize,
+}
+
+impl Runtime {
+    /// Counts the beginning of a call to `function`, refusing it if `max_call_depth` calls are
+    /// already unfinished. Each call begun must be ended with `end_call`.
+    pub(crate) fn begin_call(&mut self, function: &str) -> Result<(), EvalError> {
+        self.begin_call_within(function, self.max_call_depth)
+    }
+
+    pub(crate) fn end_call(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn begin_call_within(&mut self, function: &str, limit: usize) -> Result<(), EvalError> {
+        if self.depth >= limit {
+            return Err(EvalError::CallDepth(function.to_string()));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Runs a call to `function` on the native stack, refusing to begin it if `max_call_depth`
+    /// calls, or `NATIVE_CALL_DEPTH` if that is fewer, are already unfinished.
+    pub(crate) fn call<T, E, F>(&mut self, function: &str, run: F) -> Result<T, E>
+    where
+        E: From<EvalError>,
+        F: FnOnce(&mut Runtime) -> Result<T, E>,
+    {
+        self.begin_call_within(function, self.max_call_depth.min(NATIVE_CALL_DEPTH))?;
+        let result = run(self);
+        self.end_call();
+        result
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Runtime {
+            overflow: Overflow::default(),
+            output: Output::default(),
+            input: Input::default(),
+            max_call_depth: DEFAULT_CALL_DEPTH,
+            depth: 0,
+        }
+    }
+}
+
+impl From<Overflow> for Runtime {
+    fn from(overflow: Overflow) -> Self {
+        Runtime {
+            overflow,
+            ..Runtime::default()
+        }
+    }
+}
+
+/// Variables and their values. Environments are persistent: updating one returns a new
+/// environment which shares most of its structure with the old one, so keeping both is cheap.
+///
+/// Besides the global variables there is a stack of scopes, each holding the variables declared
+/// on entering it. A variable in an inner scope hides any of the same name further out, until its
+/// scope is exited.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Environment {
+    globals: Map<String, Value>,
+    /// Scopes entered and not yet exited, innermost last.
+    scopes: Vec<Map<String, Value>>,
+}
 
 impl Environment {
-    /// Adds or replaces a key in the map, returning a new map.
-    pub fn update(&self, key: &String, value: Value) -> Environment {
-        let mut map = self.0.clone();
-        map.insert(key.clone(), value);
-        Environment(map)
+    /// Adds or replaces a key, returning a new environment. The key is replaced in the innermost
+    /// scope that declares it, and is otherwise global.
+    pub fn update(&self, key: &str, value: Value) -> Environment {
+        let mut environment = self.clone();
+        match environment.declaring(key) {
+            Some(scope) => *scope = scope.insert(key.to_string(), value),
+            None => environment.globals = environment.globals.insert(key.to_string(), value),
+        }
+        environment
+    }
+
+    /// Removes a key from wherever it is visible, returning a new environment.
+    pub fn remove(&self, key: &str) -> Environment {
+        let mut environment = self.clone();
+        match environment.declaring(key) {
+            Some(scope) => *scope = scope.remove(key),
+            None => environment.globals = environment.globals.remove(key),
+        }
+        environment
+    }
+
+    /// The innermost scope which declares a key.
+    fn declaring(&mut self, key: &str) -> Option<&mut Map<String, Value>> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.get(key).is_some())
+    }
+
+    /// Enters a new scope declaring one variable, returning a new environment.
+    pub fn enter_scope(&self, key: &str, value: Value) -> Environment {
+        let mut environment = self.clone();
+        environment
+            .scopes
+            .push(Map::new().insert(key.to_string(), value));
+        environment
+    }
+
+    /// Exits the innermost scope, forgetting its variables so that any they hid are visible
+    /// again. Outside every scope there is nothing to exit, and the result is `None`.
+    pub fn exit_scope(&self) -> Option<Environment> {
+        let mut environment = self.clone();
+        environment.scopes.pop()?;
+        Some(environment)
     }
 
     pub fn empty() -> Self {
-        Self(HashMap::new())
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(key))
+            .or_else(|| self.globals.get(key))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Visits every visible variable in order of name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        let mut visible = BTreeMap::new();
+        for map in iter::once(&self.globals).chain(&self.scopes) {
+            visible.extend(map.iter().map(|(key, value)| (key.as_str(), value)));
+        }
+        visible.into_iter()
+    }
+}
+
+impl FromIterator<(String, Value)> for Environment {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(bindings: I) -> Self {
+        bindings
+            .into_iter()
+            .fold(Self::empty(), |environment, (key, value)| {
+                environment.update(&key, value)
+            })
+    }
+}
+
+impl<S: Into<String>, const N: usize> From<[(S, Value); N]> for Environment {
+    /// Seeds an environment, e.g. `Environment::from([("x", Value::Number(1))])`.
+    fn from(bindings: [(S, Value); N]) -> Self {
+        IntoIterator::into_iter(bindings)
+            .map(|(key, value)| (key.into(), value))
+            .collect()
     }
 }
 
 impl fmt::Display for Environment {
+    /// Shows the global variables, then those of each scope in turn, e.g. `{ x=1 | y=2 }`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "{{ ")?;
-
-        let locals: String = self
-            .0
-            .iter()
-            .map(|(key, value)| match value {
-                Value::Number(val) => format!("{}={}", key, val),
-                Value::Boolean(val) => format!("{}={}", key, val),
+        let maps: Vec<String> = iter::once(&self.globals)
+            .chain(&self.scopes)
+            .map(|map| {
+                map.iter()
+                    .map(|(key, value)| format!("{}={}", key, value.to_s()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
             })
-            .collect::<Vec<String>>()
-            .join(", ");
-        write!(f, "{}", locals)?;
-        write!(f, " }}")
+            .collect();
+        write!(f, "{{ {} }}", maps.join(" | "))
     }
 }
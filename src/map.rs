@@ -0,0 +1,361 @@
+//! A persistent sorted map: a balanced (AVL) binary tree whose nodes are shared between versions.
+//! Inserting or removing a key copies only the path from the root to that key, so making an
+//! updated copy costs O(log n) rather than the O(n) of cloning a `HashMap`.
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+type Link<K, V> = Option<Rc<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    height: usize,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn node<K, V>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    Some(Rc::new(Node {
+        height: height(&left).max(height(&right)) + 1,
+        key,
+        value,
+        left,
+        right,
+    }))
+}
+
+/// Builds a node, rotating it back into balance if one side has grown two levels taller.
+fn balance<K: Clone, V: Clone>(
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Link<K, V> {
+    let (left_height, right_height) = (height(&left), height(&right));
+    if left_height > right_height + 1 {
+        let l = left.unwrap();
+        if height(&l.left) >= height(&l.right) {
+            let right = node(key, value, l.right.clone(), right);
+            node(l.key.clone(), l.value.clone(), l.left.clone(), right)
+        } else {
+            let lr = l.right.as_ref().unwrap();
+            node(
+                lr.key.clone(),
+                lr.value.clone(),
+                node(
+                    l.key.clone(),
+                    l.value.clone(),
+                    l.left.clone(),
+                    lr.left.clone(),
+                ),
+                node(key, value, lr.right.clone(), right),
+            )
+        }
+    } else if right_height > left_height + 1 {
+        let r = right.unwrap();
+        if height(&r.right) >= height(&r.left) {
+            let left = node(key, value, left, r.left.clone());
+            node(r.key.clone(), r.value.clone(), left, r.right.clone())
+        } else {
+            let rl = r.left.as_ref().unwrap();
+            node(
+                rl.key.clone(),
+                rl.value.clone(),
+                node(key, value, left, rl.left.clone()),
+                node(
+                    r.key.clone(),
+                    r.value.clone(),
+                    rl.right.clone(),
+                    r.right.clone(),
+                ),
+            )
+        }
+    } else {
+        node(key, value, left, right)
+    }
+}
+
+fn insert<K: Ord + Clone, V: Clone>(link: &Link<K, V>, key: K, value: V) -> Link<K, V> {
+    match link {
+        None => node(key, value, None, None),
+        Some(n) => match key.cmp(&n.key) {
+            Ordering::Less => balance(
+                n.key.clone(),
+                n.value.clone(),
+                insert(&n.left, key, value),
+                n.right.clone(),
+            ),
+            Ordering::Greater => balance(
+                n.key.clone(),
+                n.value.clone(),
+                n.left.clone(),
+                insert(&n.right, key, value),
+            ),
+            Ordering::Equal => node(key, value, n.left.clone(), n.right.clone()),
+        },
+    }
+}
+
+/// Removes the smallest key, returning it along with what remains of the tree.
+fn remove_min<K: Clone, V: Clone>(n: &Node<K, V>) -> (K, V, Link<K, V>) {
+    match &n.left {
+        None => (n.key.clone(), n.value.clone(), n.right.clone()),
+        Some(left) => {
+            let (key, value, left) = remove_min(left);
+            (
+                key,
+                value,
+                balance(n.key.clone(), n.value.clone(), left, n.right.clone()),
+            )
+        }
+    }
+}
+
+/// Returns the tree without `key`, or `None` if the key was not there to remove.
+fn remove<K, V, Q>(link: &Link<K, V>, key: &Q) -> Option<Link<K, V>>
+where
+    K: Ord + Clone + Borrow<Q>,
+    V: Clone,
+    Q: Ord + ?Sized,
+{
+    let n = link.as_ref()?;
+    Some(match key.cmp(n.key.borrow()) {
+        Ordering::Less => balance(
+            n.key.clone(),
+            n.value.clone(),
+            remove(&n.left, key)?,
+            n.right.clone(),
+        ),
+        Ordering::Greater => balance(
+            n.key.clone(),
+            n.value.clone(),
+            n.left.clone(),
+            remove(&n.right, key)?,
+        ),
+        Ordering::Equal => match (&n.left, &n.right) {
+            (None, right) => right.clone(),
+            (left, None) => left.clone(),
+            (left, Some(right)) => {
+                let (key, value, right) = remove_min(right);
+                balance(key, value, left.clone(), right)
+            }
+        },
+    })
+}
+
+pub struct Map<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord + Clone, V: Clone> Map<K, V> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(n) = link {
+            match key.cmp(n.key.borrow()) {
+                Ordering::Less => link = &n.left,
+                Ordering::Greater => link = &n.right,
+                Ordering::Equal => return Some(&n.value),
+            }
+        }
+        None
+    }
+
+    /// Returns a copy of the map with `key` bound to `value`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        Self {
+            root: insert(&self.root, key, value),
+        }
+    }
+
+    /// Returns a copy of the map without `key`.
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match remove(&self.root, key) {
+            Some(root) => Self { root },
+            None => self.clone(),
+        }
+    }
+
+    /// Visits the entries in order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: vec![] };
+        iter.descend(&self.root);
+        iter
+    }
+}
+
+impl<K, V> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Default for Map<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + Eq> Eq for Map<K, V> {}
+
+impl<K: Ord + Clone + Hash, V: Clone + Hash> Hash for Map<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An in-order walk over a `Map`.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn descend(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(n) = link {
+            self.stack.push(n);
+            link = &n.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.stack.pop()?;
+        self.descend(&n.right);
+        Some((&n.key, &n.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Checks that every node records its height, is in balance, and holds keys in order, returning
+    /// the height of the tree.
+    fn check<K: Ord, V>(link: &Link<K, V>, low: Option<&K>, high: Option<&K>) -> usize {
+        let n = match link {
+            None => return 0,
+            Some(n) => n,
+        };
+        assert!(low.is_none_or(|low| *low < n.key));
+        assert!(high.is_none_or(|high| n.key < *high));
+        let left = check(&n.left, low, Some(&n.key));
+        let right = check(&n.right, Some(&n.key), high);
+        assert!(left.max(right) - left.min(right) <= 1);
+        assert_eq!(n.height, left.max(right) + 1);
+        n.height
+    }
+
+    /// Checks a map against the `BTreeMap` it should hold the same entries as.
+    fn agrees(map: &Map<u32, u32>, model: &BTreeMap<u32, u32>) {
+        check(&map.root, None, None);
+        assert!(map.iter().eq(model.iter()));
+        for key in model.keys() {
+            assert_eq!(map.get(key), model.get(key));
+        }
+    }
+
+    /// Keys in a scrambled but repeatable order, with some repeated.
+    fn keys(count: u32) -> impl Iterator<Item = u32> {
+        (0..count).map(move |i| i * 7919 % (count / 2 + 1))
+    }
+
+    #[test]
+    fn ascending_and_descending_inserts_stay_balanced() {
+        for keys in [(0..1000).collect::<Vec<u32>>(), (0..1000).rev().collect()] {
+            let mut map = Map::new();
+            let mut model = BTreeMap::new();
+            for key in keys {
+                map = map.insert(key, key * 2);
+                model.insert(key, key * 2);
+            }
+            agrees(&map, &model);
+            // A balanced tree of 1000 keys is at most 1.44 log2(1000), about 14, levels deep.
+            assert!(check(&map.root, None, None) <= 14);
+        }
+    }
+
+    #[test]
+    fn inserts_and_removes_match_a_model() {
+        let mut map = Map::new();
+        let mut model = BTreeMap::new();
+        for (i, key) in keys(600).enumerate() {
+            map = map.insert(key, i as u32);
+            model.insert(key, i as u32);
+            agrees(&map, &model);
+        }
+        for key in keys(600).step_by(2) {
+            map = map.remove(&key);
+            model.remove(&key);
+            agrees(&map, &model);
+        }
+        // Removing a key which is not there changes nothing.
+        let unchanged = map.remove(&10_000);
+        assert!(unchanged.iter().eq(map.iter()));
+        for key in keys(600) {
+            map = map.remove(&key);
+            model.remove(&key);
+            agrees(&map, &model);
+        }
+        assert_eq!(map.iter().next(), None);
+    }
+
+    /// Updating a map leaves every earlier version as it was.
+    #[test]
+    fn versions_are_independent() {
+        let mut versions = vec![Map::new()];
+        let mut models = vec![BTreeMap::new()];
+        for (i, key) in keys(200).enumerate() {
+            let (map, mut model) = (versions.last().unwrap(), models.last().unwrap().clone());
+            let map = if i % 3 == 0 {
+                model.remove(&key);
+                map.remove(&key)
+            } else {
+                model.insert(key, i as u32);
+                map.insert(key, i as u32)
+            };
+            versions.push(map);
+            models.push(model);
+        }
+        for (map, model) in versions.iter().zip(&models) {
+            agrees(map, model);
+        }
+    }
+}
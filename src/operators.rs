@@ -0,0 +1,321 @@
+//! What each operator does to values, shared by every semantics so that they cannot disagree.
+//! Only `And` and `Or` are missing: they decide for themselves whether to look at their right
+//! operand at all, so each semantics handles them directly, using `boolean` for the operands.
+use crate::ast::FunctionDef;
+use crate::bignum::BigInt;
+use crate::{Environment, EvalError, Overflow, Printable, Value};
+use std::cmp::Ordering;
+
+/// Combines the values of a binary operator's operands.
+pub(crate) type Binary = fn(Value, Value, Overflow) -> Result<Value, EvalError>;
+/// Transforms the value of a prefix operator's operand.
+pub(crate) type Unary = fn(Value, Overflow) -> Result<Value, EvalError>;
+
+/// Widens a numeric operand, whatever its size, to a `BigInt`.
+fn integer(value: Value) -> Result<BigInt, EvalError> {
+    match value {
+        Value::Number(number) => Ok(BigInt::from(number)),
+        Value::BigNumber(number) => Ok(number),
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// Orders two numbers of any size, or two strings.
+fn compare(left: Value, right: Value) -> Result<Ordering, EvalError> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => Ok(a.cmp(&b)),
+        (Value::String(a), Value::String(b)) => Ok(a.cmp(&b)),
+        (Value::String(_), other) => Err(EvalError::TypeMismatch(other)),
+        (left, right) => {
+            let left = integer(left)?;
+            Ok(left.cmp(&integer(right)?))
+        }
+    }
+}
+
+/// Turns the exact result of some arithmetic back into a `Value`, as the `Overflow` policy says
+/// to when it is out of the range of `i64`. `operation` describes what overflowed.
+fn fit(exact: BigInt, overflow: Overflow, operation: String) -> Result<Value, EvalError> {
+    if let Some(small) = exact.to_i64() {
+        return Ok(Value::Number(small));
+    }
+    match overflow {
+        Overflow::Promote => Ok(Value::BigNumber(exact)),
+        Overflow::Checked => Err(EvalError::Overflow(operation)),
+        Overflow::Wrapping => Ok(Value::Number(exact.wrapping_to_i64())),
+        Overflow::Saturating if exact.is_negative() => Ok(Value::Number(i64::MIN)),
+        Overflow::Saturating => Ok(Value::Number(i64::MAX)),
+    }
+}
+
+/// Whether `left / right` is a division by zero, rather than a mistake about types.
+fn divides_by_zero(left: &Value, right: &Value) -> bool {
+    matches!(left, Value::Number(_) | Value::BigNumber(_)) && *right == Value::Number(0)
+}
+
+/// Unpacks an operand of a boolean operator.
+pub(crate) fn boolean(value: Value) -> Result<bool, EvalError> {
+    match value {
+        Value::Boolean(value) => Ok(value),
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// One arithmetic operator: a fast version for `i64`s, which gives up on overflow, and an exact
+/// one to fall back on.
+struct Arithmetic {
+    symbol: &'static str,
+    small: fn(i64, i64) -> Option<i64>,
+    exact: fn(&BigInt, &BigInt) -> BigInt,
+}
+
+impl Arithmetic {
+    fn apply(&self, left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+        if let (Value::Number(a), Value::Number(b)) = (&left, &right) {
+            if let Some(result) = (self.small)(*a, *b) {
+                return Ok(Value::Number(result));
+            }
+        }
+        let operation = format!("{} {} {}", left.to_s(), self.symbol, right.to_s());
+        let left = integer(left)?;
+        fit((self.exact)(&left, &integer(right)?), overflow, operation)
+    }
+}
+
+/// Adds numbers, or joins strings.
+pub(crate) fn add(left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => Ok(Value::String(a + &b)),
+        (Value::String(_), other) => Err(EvalError::TypeMismatch(other)),
+        (left, right) => Arithmetic {
+            symbol: "+",
+            small: i64::checked_add,
+            exact: |a, b| a + b,
+        }
+        .apply(left, right, overflow),
+    }
+}
+
+pub(crate) fn subtract(left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    Arithmetic {
+        symbol: "-",
+        small: i64::checked_sub,
+        exact: |a, b| a - b,
+    }
+    .apply(left, right, overflow)
+}
+
+pub(crate) fn multiply(left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    Arithmetic {
+        symbol: "*",
+        small: i64::checked_mul,
+        exact: |a, b| a * b,
+    }
+    .apply(left, right, overflow)
+}
+
+/// Divides, rounding towards zero. Of two `i64`s, only `i64::MIN / -1` can overflow.
+pub(crate) fn divide(left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    if divides_by_zero(&left, &right) {
+        return Err(EvalError::DivisionByZero);
+    }
+    Arithmetic {
+        symbol: "/",
+        small: i64::checked_div,
+        exact: |a, b| a / b,
+    }
+    .apply(left, right, overflow)
+}
+
+/// The remainder of `divide`, which takes the sign of the left operand.
+pub(crate) fn modulo(left: Value, right: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    if divides_by_zero(&left, &right) {
+        return Err(EvalError::DivisionByZero);
+    }
+    Arithmetic {
+        symbol: "%",
+        small: i64::checked_rem,
+        exact: |a, b| a % b,
+    }
+    .apply(left, right, overflow)
+}
+
+pub(crate) fn negate(operand: Value, overflow: Overflow) -> Result<Value, EvalError> {
+    if let Value::Number(a) = operand {
+        if let Some(result) = a.checked_neg() {
+            return Ok(Value::Number(result));
+        }
+    }
+    let operation = format!("-({})", operand.to_s());
+    fit(-&integer(operand)?, overflow, operation)
+}
+
+/// Compares two values of the same type; comparing a number with a boolean is a mistake rather
+/// than simply false.
+pub(crate) fn equals(left: Value, right: Value, _: Overflow) -> Result<Value, EvalError> {
+    match (left, right) {
+        (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(a == b)),
+        (Value::Boolean(_), other) => Err(EvalError::TypeMismatch(other)),
+        (left, right) => Ok(Value::Boolean(compare(left, right)? == Ordering::Equal)),
+    }
+}
+
+pub(crate) fn not_equals(
+    left: Value,
+    right: Value,
+    overflow: Overflow,
+) -> Result<Value, EvalError> {
+    not(equals(left, right, overflow)?, overflow)
+}
+
+pub(crate) fn less_than(left: Value, right: Value, _: Overflow) -> Result<Value, EvalError> {
+    Ok(Value::Boolean(compare(left, right)? == Ordering::Less))
+}
+
+pub(crate) fn greater_than(left: Value, right: Value, _: Overflow) -> Result<Value, EvalError> {
+    Ok(Value::Boolean(compare(left, right)? == Ordering::Greater))
+}
+
+pub(crate) fn less_or_equal(left: Value, right: Value, _: Overflow) -> Result<Value, EvalError> {
+    Ok(Value::Boolean(compare(left, right)? != Ordering::Greater))
+}
+
+pub(crate) fn greater_or_equal(left: Value, right: Value, _: Overflow) -> Result<Value, EvalError> {
+    Ok(Value::Boolean(compare(left, right)? != Ordering::Less))
+}
+
+pub(crate) fn not(operand: Value, _: Overflow) -> Result<Value, EvalError> {
+    boolean(operand).map(|value| Value::Boolean(!value))
+}
+
+/// Checks that `index` is a position no further than `last` in something `length` long.
+fn position(index: Value, last: usize, length: usize) -> Result<usize, EvalError> {
+    match index {
+        Value::Number(number) if number >= 0 && number as u64 <= last as u64 => Ok(number as usize),
+        index @ Value::Number(_) | index @ Value::BigNumber(_) => {
+            Err(EvalError::IndexOutOfRange { index, length })
+        }
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// Checks that `index` is the position of an element of something `length` long.
+fn element(index: Value, length: usize) -> Result<usize, EvalError> {
+    match length.checked_sub(1) {
+        Some(last) => position(index, last, length),
+        None => match index {
+            Value::Number(_) | Value::BigNumber(_) => {
+                Err(EvalError::IndexOutOfRange { index, length })
+            }
+            other => Err(EvalError::TypeMismatch(other)),
+        },
+    }
+}
+
+/// Counts the characters of a string, not its bytes, or the elements of a list.
+pub(crate) fn length(operand: Value, _: Overflow) -> Result<Value, EvalError> {
+    let length = match operand {
+        Value::String(string) => string.chars().count(),
+        Value::List(values) => values.len(),
+        other => return Err(EvalError::TypeMismatch(other)),
+    };
+    Ok(Value::Number(length as i64))
+}
+
+pub(crate) fn index(operand: Value, index: Value, _: Overflow) -> Result<Value, EvalError> {
+    match operand {
+        Value::String(string) => {
+            let position = element(index, string.chars().count())?;
+            Ok(Value::String(
+                string.chars().skip(position).take(1).collect(),
+            ))
+        }
+        Value::List(mut values) => {
+            let position = element(index, values.len())?;
+            Ok(values.swap_remove(position))
+        }
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// Checks the ends of a slice of something `length` long, returning where it starts and how
+/// many items it takes.
+fn span(start: Value, end: Value, length: usize) -> Result<(usize, usize), EvalError> {
+    let start = position(start, length, length)?;
+    let end = position(end, length, length)?;
+    Ok((start, end.saturating_sub(start)))
+}
+
+/// Either end may be anywhere from the start of the string or list to its end. An end before the
+/// start gives an empty one.
+pub(crate) fn slice(operand: Value, start: Value, end: Value) -> Result<Value, EvalError> {
+    match operand {
+        Value::String(string) => {
+            let (start, count) = span(start, end, string.chars().count())?;
+            Ok(Value::String(
+                string.chars().skip(start).take(count).collect(),
+            ))
+        }
+        Value::List(values) => {
+            let (start, count) = span(start, end, values.len())?;
+            Ok(Value::List(
+                values.into_iter().skip(start).take(count).collect(),
+            ))
+        }
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+pub(crate) fn append(list: Value, element: Value, _: Overflow) -> Result<Value, EvalError> {
+    match list {
+        Value::List(mut values) => {
+            values.push(element);
+            Ok(Value::List(values))
+        }
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// The list with the element at `index` replaced, for `AssignIndex`.
+pub(crate) fn replace(list: Value, index: Value, element: Value) -> Result<Value, EvalError> {
+    match list {
+        Value::List(mut values) => {
+            let position = self::element(index, values.len())?;
+            values[position] = element;
+            Ok(Value::List(values))
+        }
+        other => Err(EvalError::TypeMismatch(other)),
+    }
+}
+
+/// Looks up the function a `Call` names, returning it with the environment its body runs in.
+/// That holds the caller's functions, so the body can call them and itself, and the parameters
+/// bound to the arguments. Nothing else of the caller's can be seen from inside the call, and
+/// nothing the body assigns gets out.
+pub(crate) fn enter(
+    environment: &Environment,
+    name: &str,
+    arguments: Vec<Value>,
+) -> Result<(FunctionDef, Environment), EvalError> {
+    let function = match environment.get(name) {
+        Some(Value::Function(function)) => function.clone(),
+        Some(other) => return Err(EvalError::TypeMismatch(other.clone())),
+        None => return Err(EvalError::UnboundVariable(name.to_string())),
+    };
+    let FunctionDef(_, parameters, _, _) = &function;
+    if parameters.len() != arguments.len() {
+        return Err(EvalError::ArgumentCount {
+            function: name.to_string(),
+            expected: parameters.len(),
+            found: arguments.len(),
+        });
+    }
+    let functions = environment
+        .iter()
+        .filter(|(_, value)| matches!(value, Value::Function(_)))
+        .map(|(name, value)| (name.to_string(), value.clone()));
+    let locals = functions
+        .chain(parameters.iter().cloned().zip(arguments))
+        .collect();
+    Ok((function, locals))
+}
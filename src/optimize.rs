@@ -0,0 +1,456 @@
+//! Rewrites a program into one which does the same in fewer steps: operators whose operands are
+//! all values are worked out in advance, `x + 0`, `x * 1` and the like become `x`, and an `If`
+//! with a value for its condition becomes the branch it would take.
+//!
+//! An optimised program leaves the same environment, prints the same output, and fails with the
+//! same error, under every `Overflow` policy. So an identity only applies where `x` is sure to be
+//! a number within the range of an `i64` whose evaluation cannot fail: a literal, or a variable
+//! last assigned one. Otherwise `(10 / 0) * 0` would no longer fail, nor `"a" + 0`, and under
+//! `Overflow::Wrapping` a number too big for an `i64` would change with `+ 0`.
+//!
+//! Function definitions are left as written: a function is a value, which a program can print.
+use crate::ast::{
+    sequence, Add, AddAssign, And, Append, Assign, AssignIndex, Call, Divide, DoWhile, Equals,
+    Expr, For, FunctionDef, GreaterOrEqual, GreaterThan, If, Index, Iteration, Length, LessOrEqual,
+    LessThan, Let, List, Modulo, Multiply, Negate, Not, NotEquals, Or, Print, Read, Scope, Slice,
+    Stmt, Subtract, Sugar, Unless, Variable, While,
+};
+use crate::operators::{self, Binary, Unary};
+use crate::{EvalError, Overflow, Value};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+pub trait Expression {
+    fn optimize(&self) -> Expr;
+}
+
+pub trait Statement {
+    fn optimize(&self) -> Stmt;
+}
+
+/// The variables sure to hold a `Value::Number` at some point in a program.
+type Numbers = HashSet<String>;
+
+/// Works out an operator in advance, unless it would fail, which is left for the run to report.
+/// Under `Overflow::Checked`, a result is only given when every policy agrees on it.
+fn fold(result: Result<Value, EvalError>) -> Option<Expr> {
+    result.ok().map(Expr::Value)
+}
+
+/// Optimises both operands of a binary operator, then works it out if they are both values.
+fn binary<N>(left: &Expr, right: &Expr, numbers: &Numbers, node: N, operation: Binary) -> Expr
+where
+    N: Fn(Rc<Expr>, Rc<Expr>) -> Expr,
+{
+    let (left, right) = (expression(left, numbers), expression(right, numbers));
+    match (left.as_value(), right.as_value()) {
+        (Some(a), Some(b)) => fold(operation(a.clone(), b.clone(), Overflow::Checked)),
+        _ => None,
+    }
+    .unwrap_or_else(|| node(Rc::new(left), Rc::new(right)))
+}
+
+/// Optimises the operand of a prefix operator, then works it out if it is a value.
+fn unary<N>(operand: &Expr, numbers: &Numbers, node: N, operation: Unary) -> Expr
+where
+    N: Fn(Rc<Expr>) -> Expr,
+{
+    let operand = expression(operand, numbers);
+    operand
+        .as_value()
+        .and_then(|value| fold(operation(value.clone(), Overflow::Checked)))
+        .unwrap_or_else(|| node(Rc::new(operand)))
+}
+
+fn is_number(expression: &Expr, number: i64) -> bool {
+    expression.as_value() == Some(&Value::Number(number))
+}
+
+fn is_boolean(expression: &Expr) -> Option<bool> {
+    match expression.as_value() {
+        Some(Value::Boolean(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Whether an expression is sure to evaluate to a `Value::Number` without failing.
+fn is_any_number(expression: &Expr, numbers: &Numbers) -> bool {
+    match expression {
+        Expr::Value(Value::Number(_)) => true,
+        Expr::Variable(Variable(name)) => numbers.contains(name),
+        _ => false,
+    }
+}
+
+/// Whether an expression can only evaluate to a boolean, if it evaluates at all.
+fn gives_boolean(expression: &Expr) -> bool {
+    matches!(
+        expression,
+        Expr::Value(Value::Boolean(_))
+            | Expr::Equals(_)
+            | Expr::NotEquals(_)
+            | Expr::LessThan(_)
+            | Expr::GreaterThan(_)
+            | Expr::LessOrEqual(_)
+            | Expr::GreaterOrEqual(_)
+            | Expr::And(_)
+            | Expr::Or(_)
+            | Expr::Not(_)
+    )
+}
+
+/// Applies the identities of arithmetic to an operator whose operands are already optimised.
+fn identity(expression: Expr, numbers: &Numbers) -> Expr {
+    let number = |operand: &Expr| is_any_number(operand, numbers);
+    match &expression {
+        Expr::Add(Add(operand, zero))
+        | Expr::Add(Add(zero, operand))
+        | Expr::Subtract(Subtract(operand, zero))
+            if is_number(zero, 0) && number(operand) =>
+        {
+            (**operand).clone()
+        }
+        Expr::Multiply(Multiply(operand, one))
+        | Expr::Multiply(Multiply(one, operand))
+        | Expr::Divide(Divide(operand, one))
+            if is_number(one, 1) && number(operand) =>
+        {
+            (**operand).clone()
+        }
+        Expr::Multiply(Multiply(operand, zero)) | Expr::Multiply(Multiply(zero, operand))
+            if is_number(zero, 0) && number(operand) =>
+        {
+            Value::Number(0).into()
+        }
+        _ => expression,
+    }
+}
+
+/// Optimises an expression, where `numbers` are sure to hold numbers.
+fn expression(node: &Expr, numbers: &Numbers) -> Expr {
+    let n = numbers;
+    match node {
+        Expr::Value(_) | Expr::Variable(_) | Expr::Frame(_) => node.clone(),
+        Expr::Add(Add(left, right)) => identity(
+            binary(left, right, n, |l, r| Add(l, r).into(), operators::add),
+            n,
+        ),
+        Expr::Subtract(Subtract(left, right)) => identity(
+            binary(
+                left,
+                right,
+                n,
+                |l, r| Subtract(l, r).into(),
+                operators::subtract,
+            ),
+            n,
+        ),
+        Expr::Multiply(Multiply(left, right)) => identity(
+            binary(
+                left,
+                right,
+                n,
+                |l, r| Multiply(l, r).into(),
+                operators::multiply,
+            ),
+            n,
+        ),
+        Expr::Divide(Divide(left, right)) => identity(
+            binary(
+                left,
+                right,
+                n,
+                |l, r| Divide(l, r).into(),
+                operators::divide,
+            ),
+            n,
+        ),
+        Expr::Modulo(Modulo(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| Modulo(l, r).into(),
+            operators::modulo,
+        ),
+        Expr::Negate(Negate(operand)) => unary(operand, n, |o| Negate(o).into(), operators::negate),
+        Expr::Equals(Equals(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| Equals(l, r).into(),
+            operators::equals,
+        ),
+        Expr::NotEquals(NotEquals(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| NotEquals(l, r).into(),
+            operators::not_equals,
+        ),
+        Expr::LessThan(LessThan(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| LessThan(l, r).into(),
+            operators::less_than,
+        ),
+        Expr::GreaterThan(GreaterThan(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| GreaterThan(l, r).into(),
+            operators::greater_than,
+        ),
+        Expr::LessOrEqual(LessOrEqual(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| LessOrEqual(l, r).into(),
+            operators::less_or_equal,
+        ),
+        Expr::GreaterOrEqual(GreaterOrEqual(left, right)) => binary(
+            left,
+            right,
+            n,
+            |l, r| GreaterOrEqual(l, r).into(),
+            operators::greater_or_equal,
+        ),
+        // The right operand is only evaluated when the left does not settle the answer, and then
+        // it must be a boolean, which only some expressions are sure to give.
+        Expr::And(And(left, right)) => {
+            let (left, right) = (expression(left, n), expression(right, n));
+            match is_boolean(&left) {
+                Some(false) => left,
+                Some(true) if gives_boolean(&right) => right,
+                _ => And::new(left, right).into(),
+            }
+        }
+        Expr::Or(Or(left, right)) => {
+            let (left, right) = (expression(left, n), expression(right, n));
+            match is_boolean(&left) {
+                Some(true) => left,
+                Some(false) if gives_boolean(&right) => right,
+                _ => Or::new(left, right).into(),
+            }
+        }
+        Expr::Not(Not(operand)) => unary(operand, n, |o| Not(o).into(), operators::not),
+        Expr::Length(Length(operand)) => unary(operand, n, |o| Length(o).into(), operators::length),
+        Expr::Index(Index(operand, index)) => binary(
+            operand,
+            index,
+            n,
+            |o, i| Index(o, i).into(),
+            operators::index,
+        ),
+        Expr::Slice(Slice(operand, start, end)) => {
+            let (operand, start, end) = (
+                expression(operand, n),
+                expression(start, n),
+                expression(end, n),
+            );
+            match (operand.as_value(), start.as_value(), end.as_value()) {
+                (Some(o), Some(s), Some(e)) => {
+                    fold(operators::slice(o.clone(), s.clone(), e.clone()))
+                }
+                _ => None,
+            }
+            .unwrap_or_else(|| Slice::new(operand, start, end).into())
+        }
+        Expr::List(List(elements)) => {
+            let elements: Vec<Expr> = elements.iter().map(|e| expression(e, n)).collect();
+            match elements
+                .iter()
+                .map(Expr::as_value)
+                .collect::<Option<Vec<&Value>>>()
+            {
+                Some(values) => Value::List(values.into_iter().cloned().collect()).into(),
+                None => List::new(elements).into(),
+            }
+        }
+        Expr::Append(Append(list, element)) => binary(
+            list,
+            element,
+            n,
+            |l, e| Append(l, e).into(),
+            operators::append,
+        ),
+        Expr::Call(Call(name, arguments)) => {
+            Call::new(name.clone(), arguments.iter().map(|a| expression(a, n))).into()
+        }
+    }
+}
+
+impl Expression for Expr {
+    fn optimize(&self) -> Expr {
+        expression(self, &Numbers::new())
+    }
+}
+
+/// Adds the variables a statement might assign to, anywhere inside it, to `names`.
+fn assigned(statement: &Stmt, names: &mut HashSet<String>) {
+    match statement {
+        Stmt::Assign(Assign(name, _))
+        | Stmt::AssignIndex(AssignIndex(name, _, _))
+        | Stmt::FunctionDef(FunctionDef(name, _, _, _))
+        | Stmt::Read(Read(name)) => {
+            names.insert(name.clone());
+        }
+        Stmt::If(If(_, first, second)) => {
+            assigned(first, names);
+            assigned(second, names);
+        }
+        Stmt::Sequence(sequence) => {
+            for statement in sequence.statements() {
+                assigned(statement, names);
+            }
+        }
+        Stmt::Let(Let(_, _, body)) | Stmt::Scope(Scope(_, body)) | Stmt::While(While(_, body)) => {
+            assigned(body, names)
+        }
+        Stmt::Iteration(Iteration(rest, While(_, body))) => {
+            assigned(rest, names);
+            assigned(body, names);
+        }
+        Stmt::Sugared(sugared) => assigned(sugared.core(), names),
+        Stmt::DoNothing | Stmt::Break | Stmt::Continue | Stmt::Print(_) => {}
+    }
+}
+
+/// Forgets whatever is known of the variables which the given statements, making up a loop, might
+/// assign to: they may hold anything at the start of any pass, or once the loop is left.
+fn forget(numbers: &mut Numbers, statements: &[&Stmt]) {
+    let mut names = HashSet::new();
+    for statement in statements {
+        assigned(statement, &mut names);
+    }
+    numbers.retain(|name| !names.contains(name));
+}
+
+/// Optimises the parts of some sugar, leaving it written the same way.
+fn sugar(sugar: &Sugar, numbers: &mut Numbers) -> Stmt {
+    match sugar {
+        Sugar::AddAssign(AddAssign(name, value)) => {
+            let value = expression(value, numbers);
+            numbers.remove(name);
+            AddAssign::new(name.clone(), value).into()
+        }
+        Sugar::DoWhile(DoWhile(body, condition)) => {
+            forget(numbers, &[body]);
+            let body = statement(body, &mut numbers.clone());
+            DoWhile::new(body, expression(condition, numbers)).into()
+        }
+        Sugar::For(For(init, condition, step, body)) => {
+            let init = statement(init, numbers);
+            forget(numbers, &[step, body]);
+            let condition = expression(condition, numbers);
+            let mut inside = numbers.clone();
+            let body = statement(body, &mut inside);
+            let step = statement(step, &mut numbers.clone());
+            For::new(init, condition, step, body).into()
+        }
+        Sugar::Unless(Unless(condition, body)) => {
+            let condition = expression(condition, numbers);
+            let mut after = numbers.clone();
+            let body = statement(body, &mut after);
+            numbers.retain(|name| after.contains(name));
+            Unless::new(condition, body).into()
+        }
+    }
+}
+
+/// Optimises a loop, once `numbers` has forgotten what the loop might assign to.
+fn looping(While(condition, body): &While, numbers: &Numbers) -> While {
+    While::new(
+        expression(condition, numbers),
+        statement(body, &mut numbers.clone()),
+    )
+}
+
+/// Optimises a statement, where `numbers` are sure to hold numbers, updating `numbers` to what is
+/// sure once the statement finishes.
+fn statement(node: &Stmt, numbers: &mut Numbers) -> Stmt {
+    match node {
+        Stmt::DoNothing | Stmt::Break | Stmt::Continue => node.clone(),
+        Stmt::FunctionDef(FunctionDef(name, _, _, _)) | Stmt::Read(Read(name)) => {
+            numbers.remove(name);
+            node.clone()
+        }
+        Stmt::Assign(Assign(name, value)) => {
+            let value = expression(value, numbers);
+            if is_any_number(&value, numbers) {
+                numbers.insert(name.clone());
+            } else {
+                numbers.remove(name);
+            }
+            Assign::new(name.clone(), value).into()
+        }
+        Stmt::AssignIndex(AssignIndex(name, index, value)) => {
+            let (index, value) = (expression(index, numbers), expression(value, numbers));
+            numbers.remove(name);
+            AssignIndex::new(name.clone(), index, value).into()
+        }
+        // Only what is sure after both branches is sure afterwards.
+        Stmt::If(If(condition, consequence, alternative)) => {
+            let condition = expression(condition, numbers);
+            match is_boolean(&condition) {
+                Some(true) => statement(consequence, numbers),
+                Some(false) => statement(alternative, numbers),
+                None => {
+                    let mut other = numbers.clone();
+                    let consequence = statement(consequence, numbers);
+                    let alternative = statement(alternative, &mut other);
+                    numbers.retain(|name| other.contains(name));
+                    If::new(condition, consequence, alternative).into()
+                }
+            }
+        }
+        Stmt::Iteration(Iteration(rest, node @ While(_, body))) => {
+            forget(numbers, &[rest, body]);
+            let rest = statement(rest, &mut numbers.clone());
+            Iteration(Rc::new(rest), looping(node, numbers)).into()
+        }
+        // What the variable hides is known again once the body is done.
+        Stmt::Let(Let(name, value, body)) => {
+            let value = expression(value, numbers);
+            let hidden = numbers.contains(name);
+            if is_any_number(&value, numbers) {
+                numbers.insert(name.clone());
+            } else {
+                numbers.remove(name);
+            }
+            let body = statement(body, numbers);
+            if hidden {
+                numbers.insert(name.clone());
+            } else {
+                numbers.remove(name);
+            }
+            Let::new(name.clone(), value, body).into()
+        }
+        Stmt::Print(Print(value)) => Print::new(expression(value, numbers)).into(),
+        // Neither the variable of a scope already entered, nor what it hides, is known here.
+        Stmt::Scope(Scope(name, body)) => {
+            numbers.remove(name);
+            let body = statement(body, numbers);
+            numbers.remove(name);
+            Scope(name.clone(), Rc::new(body)).into()
+        }
+        // Each statement is optimized in turn, so a long run doesn't use up the stack.
+        Stmt::Sequence(node) => sequence(
+            node.statements()
+                .map(|node| statement(node, numbers))
+                .filter(|node| !node.does_nothing())
+                .collect(),
+        ),
+        Stmt::Sugared(sugared) => sugar(sugared.sugar(), numbers),
+        Stmt::While(node @ While(_, body)) => {
+            forget(numbers, &[body]);
+            looping(node, numbers).into()
+        }
+    }
+}
+
+impl Statement for Stmt {
+    fn optimize(&self) -> Stmt {
+        statement(self, &mut Numbers::new())
+    }
+}
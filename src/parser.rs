@@ -0,0 +1,739 @@
+//! Reads SIMPLE programs written in the same concrete syntax that `Printable::to_s` produces.
+//!
+//! ```
+//! use uc::{parser::parse_statement, small_step::Machine};
+//!
+//! let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }").unwrap();
+//! Machine::new(program).run().unwrap();
+//! ```
+use crate::ast::{
+    sequence, Add, AddAssign, And, Append, Assign, AssignIndex, Break, Call, Continue, Divide,
+    DoNothing, DoWhile, Equals, Expr, For, Frame, FunctionDef, GreaterOrEqual, GreaterThan, If,
+    Index, Iteration, Length, LessOrEqual, LessThan, Let, List, Modulo, Multiply, Negate, Not,
+    NotEquals, Or, Print, Read, Scope, Slice, Stmt, Subtract, Unless, Variable, While,
+};
+use crate::{Printable, Value};
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+/// Where parsing stopped, and what would have been accepted there instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: Vec<String>,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match self.expected.as_slice() {
+            [] => write!(f, "unexpected {}", self.found),
+            [only] => write!(f, "expected {}, found {}", only, self.found),
+            many => write!(
+                f,
+                "expected one of {}, found {}",
+                many.join(", "),
+                self.found
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a complete statement, e.g. `x = 0; while (x < 5) { x = x + 2 }`.
+pub fn parse_statement(source: &str) -> Result<Stmt, ParseError> {
+    let mut parser = Parser::new(source)?;
+    let statement = parser.sequence()?;
+    parser.finish()?;
+    Ok(statement)
+}
+
+/// Parses a statement as a trace prints it, e.g. `{ x = x + 2 }; while (x < 5) { x = x + 2 }`,
+/// which may also contain the forms a program only reaches while it runs.
+pub fn parse_trace(source: &str) -> Result<Stmt, ParseError> {
+    let mut parser = Parser::new(source)?;
+    parser.trace = true;
+    let statement = parser.sequence()?;
+    parser.finish()?;
+    Ok(statement)
+}
+
+/// Parses a complete expression, e.g. `x * 2 < y + 1`.
+pub fn parse_expression(source: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser::new(source)?;
+    let expression = parser.expression()?;
+    parser.finish()?;
+    Ok(expression)
+}
+
+/// Parses a literal value, e.g. `[1, -2, 3]`. Nothing in it is evaluated, so operators and
+/// variables are not allowed.
+pub fn parse_value(source: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser::new(source)?;
+    let value = parser.value()?;
+    parser.finish()?;
+    Ok(value)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(Value),
+    String(String),
+    Identifier(String),
+    Keyword(&'static str),
+    Symbol(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Token::Number(value) => write!(f, "number `{}`", value.to_s()),
+            Token::String(value) => write!(f, "string `{}`", Value::String(value.clone()).to_s()),
+            Token::Identifier(name) => write!(f, "identifier `{}`", name),
+            Token::Keyword(word) | Token::Symbol(word) => write!(f, "`{}`", word),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "break",
+    "continue",
+    "def",
+    "do",
+    "do-nothing",
+    "else",
+    "false",
+    "for",
+    "if",
+    "let",
+    "print",
+    "read",
+    "return",
+    "true",
+    "unless",
+    "while",
+];
+/// Longer symbols come first, so that `<=` is not read as `<` followed by `=`.
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "+=", "+", "-", "*", "/", "%", "<", ">", "!", "=", ";",
+    "(", ")", "{", "}", "[", "]", ":", ".", ",",
+];
+
+/// A token along with the line and column it starts at.
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let (mut i, mut line, mut column) = (0, 1, 1);
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            i += 1;
+            line += 1;
+            column = 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            column += 1;
+            continue;
+        }
+
+        let start = i;
+        let token = if c.is_ascii_digit()
+            || (c == '-' && next_is_digit(&chars, i) && !ends_operand(tokens.last()))
+        {
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            match text.parse() {
+                Ok(value) => Token::Number(Value::Number(value)),
+                Err(_) => Token::Number(Value::integer(
+                    text.parse().expect("a run of digits is an integer"),
+                )),
+            }
+        } else if c == '"' {
+            let (string, end) = string(&chars, i).map_err(|(found, at)| ParseError {
+                line,
+                column: column + at - start,
+                expected: vec![],
+                found,
+            })?;
+            i = end;
+            Token::String(string)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            // `do-nothing` is the only word with punctuation in it.
+            if chars[start..i].iter().copied().eq("do".chars())
+                && chars[i..].iter().copied().take(8).eq("-nothing".chars())
+            {
+                i += 8;
+            }
+            let text: String = chars[start..i].iter().collect();
+            match KEYWORDS.iter().find(|keyword| **keyword == text) {
+                Some(keyword) => Token::Keyword(keyword),
+                None => Token::Identifier(text),
+            }
+        } else {
+            let rest = &chars[i..];
+            let found = |symbol: &&&str| rest.iter().copied().take(symbol.len()).eq(symbol.chars());
+            match SYMBOLS.iter().find(found) {
+                Some(symbol) => {
+                    i += symbol.len();
+                    Token::Symbol(symbol)
+                }
+                None => {
+                    return Err(ParseError {
+                        line,
+                        column,
+                        expected: vec![],
+                        found: format!("character `{}`", c),
+                    })
+                }
+            }
+        };
+        tokens.push(Spanned {
+            token,
+            line,
+            column,
+        });
+        column += i - start;
+    }
+
+    tokens.push(Spanned {
+        token: Token::End,
+        line,
+        column,
+    });
+    Ok(tokens)
+}
+
+/// Reads the string literal whose opening quote is at `start`, returning its contents and the
+/// position just past the closing quote, or else what went wrong and where.
+fn string(chars: &[char], start: usize) -> Result<(String, usize), (String, usize)> {
+    let mut string = String::new();
+    let mut i = start + 1;
+    loop {
+        match chars.get(i) {
+            None | Some('\n') => return Err((String::from("unterminated string"), i)),
+            Some('"') => return Ok((string, i + 1)),
+            Some('\\') => {
+                string.push(match chars.get(i + 1) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(other) => return Err((format!("unknown escape `\\{}`", other), i)),
+                    None => return Err((String::from("unterminated string"), i + 1)),
+                });
+                i += 2;
+            }
+            Some(c) => {
+                string.push(*c);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn next_is_digit(chars: &[char], i: usize) -> bool {
+    chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Whether a token can end an operand. A `-` straight after one subtracts (`x-1`); anywhere else,
+/// a `-` directly before a digit is part of a negative number (`x * -1`).
+fn ends_operand(token: Option<&Spanned>) -> bool {
+    matches!(
+        token.map(|spanned| &spanned.token),
+        Some(Token::Number(_))
+            | Some(Token::String(_))
+            | Some(Token::Identifier(_))
+            | Some(Token::Keyword("true"))
+            | Some(Token::Keyword("false"))
+            | Some(Token::Symbol(")"))
+            | Some(Token::Symbol("]"))
+    )
+}
+
+/// Builds the node for a binary operator from its operands.
+type Combine = fn(Expr, Expr) -> Expr;
+
+/// A recursive descent parser which remembers every token it tried at the current position, so
+/// that errors can list all of the alternatives.
+struct Parser {
+    tokens: Vec<Spanned>,
+    position: usize,
+    expected: Vec<String>,
+    /// Whether the forms that only appear in traces are allowed.
+    trace: bool,
+}
+
+impl Parser {
+    fn new(source: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            tokens: tokenize(source)?,
+            position: 0,
+            expected: vec![],
+            trace: false,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].token
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].token.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        self.expected.clear();
+        token
+    }
+
+    /// Consumes the next token if it matches, otherwise notes it as a possible alternative.
+    fn eat(&mut self, token: Token) -> bool {
+        if *self.peek() == token {
+            self.advance();
+            true
+        } else {
+            self.expect_description(token.to_string());
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn expect_description(&mut self, description: String) {
+        if !self.expected.contains(&description) {
+            self.expected.push(description);
+        }
+    }
+
+    fn error(&self) -> ParseError {
+        let spanned = &self.tokens[self.position];
+        ParseError {
+            line: spanned.line,
+            column: spanned.column,
+            expected: self.expected.clone(),
+            found: spanned.token.to_string(),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), ParseError> {
+        self.expect(Token::End)
+    }
+
+    /// Parses statements separated by `;` in a loop rather than by recursion, so that a long
+    /// program does not use up the stack.
+    fn sequence(&mut self) -> Result<Stmt, ParseError> {
+        let mut statements = vec![self.statement()?];
+        while self.eat(Token::Symbol(";")) {
+            statements.push(self.statement()?);
+        }
+        Ok(sequence(statements))
+    }
+
+    fn block(&mut self) -> Result<Stmt, ParseError> {
+        self.expect(Token::Symbol("{"))?;
+        let body = self.sequence()?;
+        self.expect(Token::Symbol("}"))?;
+        Ok(body)
+    }
+
+    fn condition(&mut self) -> Result<Expr, ParseError> {
+        self.expect(Token::Symbol("("))?;
+        let condition = self.expression()?;
+        self.expect(Token::Symbol(")"))?;
+        Ok(condition)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        if self.trace && self.eat(Token::Symbol("{")) {
+            self.iteration()
+        } else if self.eat(Token::Keyword("do-nothing")) {
+            Ok(DoNothing.into())
+        } else if self.eat(Token::Keyword("break")) {
+            Ok(Break.into())
+        } else if self.eat(Token::Keyword("continue")) {
+            Ok(Continue.into())
+        } else if self.eat(Token::Keyword("if")) {
+            let condition = self.condition()?;
+            let consequence = self.block()?;
+            self.expect(Token::Keyword("else"))?;
+            let alternative = self.block()?;
+            Ok(If::new(condition, consequence, alternative).into())
+        } else if self.eat(Token::Keyword("while")) {
+            let condition = self.condition()?;
+            let body = self.block()?;
+            Ok(While::new(condition, body).into())
+        } else if self.eat(Token::Keyword("unless")) {
+            let condition = self.condition()?;
+            Ok(Unless::new(condition, self.block()?).into())
+        } else if self.eat(Token::Keyword("do")) {
+            let body = self.block()?;
+            self.expect(Token::Keyword("while"))?;
+            Ok(DoWhile::new(body, self.condition()?).into())
+        } else if self.eat(Token::Keyword("for")) {
+            self.expect(Token::Symbol("("))?;
+            let init = self.statement()?;
+            self.expect(Token::Symbol(";"))?;
+            let condition = self.expression()?;
+            self.expect(Token::Symbol(";"))?;
+            let step = self.statement()?;
+            self.expect(Token::Symbol(")"))?;
+            Ok(For::new(init, condition, step, self.block()?).into())
+        } else if self.eat(Token::Keyword("def")) {
+            self.function()
+        } else if self.eat(Token::Keyword("let")) {
+            // A scope already entered, as a trace shows it, names its variable without a value.
+            if self.trace {
+                if let Token::Identifier(name) = self.peek().clone() {
+                    self.advance();
+                    return Ok(Scope::new(name, self.block()?).into());
+                }
+                self.expect_description(String::from("identifier"));
+            }
+            self.expect(Token::Symbol("("))?;
+            let name = self.identifier()?;
+            self.expect(Token::Symbol("="))?;
+            let expression = self.expression()?;
+            self.expect(Token::Symbol(")"))?;
+            Ok(Let::new(name, expression, self.block()?).into())
+        } else if self.eat(Token::Keyword("print")) {
+            Ok(Print::new(self.expression()?).into())
+        } else if self.eat(Token::Keyword("read")) {
+            Ok(Read::new(self.identifier()?).into())
+        } else if let Token::Identifier(name) = self.peek().clone() {
+            self.advance();
+            if self.eat(Token::Symbol("[")) {
+                let index = self.expression()?;
+                self.expect(Token::Symbol("]"))?;
+                self.expect(Token::Symbol("="))?;
+                return Ok(AssignIndex::new(name, index, self.expression()?).into());
+            }
+            if self.eat(Token::Symbol("+=")) {
+                return Ok(AddAssign::new(name, self.expression()?).into());
+            }
+            self.expect(Token::Symbol("="))?;
+            Ok(Assign::new(name, self.expression()?).into())
+        } else {
+            self.expect_description(String::from("identifier"));
+            Err(self.error())
+        }
+    }
+
+    /// Parses the rest of `{ rest }; while (condition) { body }`, a pass around a loop already
+    /// under way followed by the loop itself.
+    fn iteration(&mut self) -> Result<Stmt, ParseError> {
+        let rest = self.sequence()?;
+        self.expect(Token::Symbol("}"))?;
+        self.expect(Token::Symbol(";"))?;
+        self.expect(Token::Keyword("while"))?;
+        let condition = self.condition()?;
+        let body = self.block()?;
+        Ok(Iteration(Rc::new(rest), While::new(condition, body)).into())
+    }
+
+    /// Parses the rest of `def name(parameters) { statements; return result }`, where there may be
+    /// no statements before the `return`.
+    fn function(&mut self) -> Result<Stmt, ParseError> {
+        let name = self.identifier()?;
+        self.expect(Token::Symbol("("))?;
+        let mut parameters = vec![];
+        if !self.eat(Token::Symbol(")")) {
+            loop {
+                parameters.push(self.identifier()?);
+                if self.eat(Token::Symbol(")")) {
+                    break;
+                }
+                self.expect(Token::Symbol(","))?;
+            }
+        }
+        let (body, result) = self.function_body()?;
+        Ok(FunctionDef::new(name, parameters, body, result).into())
+    }
+
+    /// Parses `{ statements; return result }`, the body of a function or of a call under way.
+    fn function_body(&mut self) -> Result<(Stmt, Expr), ParseError> {
+        self.expect(Token::Symbol("{"))?;
+        let mut statements = vec![];
+        while !self.eat(Token::Keyword("return")) {
+            statements.push(self.statement()?);
+            self.expect(Token::Symbol(";"))?;
+        }
+        let result = self.expression()?;
+        self.expect(Token::Symbol("}"))?;
+        Ok((sequence(statements), result))
+    }
+
+    /// Parses the rest of a call under way, as a trace shows it: `name(local=value, ...) { body;
+    /// return result }`. The functions a call can see aren't shown, so they don't come back.
+    fn frame(&mut self, name: String) -> Result<Expr, ParseError> {
+        let mut locals = vec![];
+        if !self.eat(Token::Symbol(")")) {
+            loop {
+                let local = self.identifier()?;
+                self.expect(Token::Symbol("="))?;
+                locals.push((local, self.value()?));
+                if self.eat(Token::Symbol(")")) {
+                    break;
+                }
+                self.expect(Token::Symbol(","))?;
+            }
+        }
+        let (body, result) = self.function_body()?;
+        let locals = locals.into_iter().collect();
+        Ok(Frame(name, locals, Rc::new(body), Rc::new(result)).into())
+    }
+
+    /// Whether a call's parentheses hold the locals of a frame rather than arguments.
+    fn at_frame(&self) -> bool {
+        let next = self.tokens[self.position..].iter().take(2);
+        let next: Vec<&Token> = next.map(|spanned| &spanned.token).collect();
+        matches!(
+            next.as_slice(),
+            [Token::Identifier(_), Token::Symbol("=")] | [Token::Symbol(")"), Token::Symbol("{")]
+        )
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        if let Token::Identifier(name) = self.peek().clone() {
+            self.advance();
+            Ok(name)
+        } else {
+            self.expect_description(String::from("identifier"));
+            Err(self.error())
+        }
+    }
+
+    /// Parses the arguments of a call, after its opening parenthesis.
+    fn arguments(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut arguments = vec![];
+        if !self.eat(Token::Symbol(")")) {
+            loop {
+                arguments.push(self.expression()?);
+                if self.eat(Token::Symbol(")")) {
+                    break;
+                }
+                self.expect(Token::Symbol(","))?;
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// Parses a run of operands joined by any of the given left-associative operators.
+    fn left_associative(
+        &mut self,
+        operators: &[(&'static str, Combine)],
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut left = operand(self)?;
+        'outer: loop {
+            for (symbol, node) in operators {
+                if self.eat(Token::Symbol(symbol)) {
+                    left = node(left, operand(self)?);
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(&[("||", |l, r| Or::new(l, r).into())], Self::conjunction)
+    }
+
+    fn conjunction(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(&[("&&", |l, r| And::new(l, r).into())], Self::equality)
+    }
+
+    fn equality(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(
+            &[
+                ("==", |l, r| Equals::new(l, r).into()),
+                ("!=", |l, r| NotEquals::new(l, r).into()),
+            ],
+            Self::comparison,
+        )
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(
+            &[
+                ("<", |l, r| LessThan::new(l, r).into()),
+                (">", |l, r| GreaterThan::new(l, r).into()),
+                ("<=", |l, r| LessOrEqual::new(l, r).into()),
+                (">=", |l, r| GreaterOrEqual::new(l, r).into()),
+            ],
+            Self::additive,
+        )
+    }
+
+    fn additive(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(
+            &[
+                ("+", |l, r| Add::new(l, r).into()),
+                ("-", |l, r| Subtract::new(l, r).into()),
+            ],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ParseError> {
+        self.left_associative(
+            &[
+                ("*", |l, r| Multiply::new(l, r).into()),
+                ("/", |l, r| Divide::new(l, r).into()),
+                ("%", |l, r| Modulo::new(l, r).into()),
+            ],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat(Token::Symbol("-")) {
+            Ok(Negate::new(self.unary()?).into())
+        } else if self.eat(Token::Symbol("!")) {
+            Ok(Not::new(self.unary()?).into())
+        } else {
+            self.postfix()
+        }
+    }
+
+    /// Parses an atom followed by any number of `.length`, `.append(element)`, `[index]` and
+    /// `[start:end]`.
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let mut operand = self.atom()?;
+        loop {
+            if self.eat(Token::Symbol(".")) {
+                if self.eat(Token::Identifier(String::from("length"))) {
+                    operand = Length::new(operand).into();
+                } else {
+                    self.expect(Token::Identifier(String::from("append")))?;
+                    self.expect(Token::Symbol("("))?;
+                    operand = Append::new(operand, self.expression()?).into();
+                    self.expect(Token::Symbol(")"))?;
+                }
+            } else if self.eat(Token::Symbol("[")) {
+                let index = self.expression()?;
+                if self.eat(Token::Symbol(":")) {
+                    operand = Slice::new(operand, index, self.expression()?).into();
+                } else {
+                    operand = Index::new(operand, index).into();
+                }
+                self.expect(Token::Symbol("]"))?;
+            } else {
+                return Ok(operand);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr, ParseError> {
+        match self.peek().clone() {
+            Token::Number(value) => {
+                self.advance();
+                Ok(value.into())
+            }
+            Token::String(value) => {
+                self.advance();
+                Ok(Value::String(value).into())
+            }
+            Token::Identifier(name) => {
+                self.advance();
+                if self.eat(Token::Symbol("(")) {
+                    if self.trace && self.at_frame() {
+                        self.frame(name)
+                    } else {
+                        Ok(Call::new(name, self.arguments()?).into())
+                    }
+                } else {
+                    Ok(Variable::new(name).into())
+                }
+            }
+            _ if self.eat(Token::Keyword("true")) => Ok(Value::Boolean(true).into()),
+            _ if self.eat(Token::Keyword("false")) => Ok(Value::Boolean(false).into()),
+            _ if self.eat(Token::Symbol("(")) => {
+                let inner = self.expression()?;
+                self.expect(Token::Symbol(")"))?;
+                Ok(inner)
+            }
+            _ if self.eat(Token::Symbol("[")) => {
+                let mut elements = vec![];
+                if !self.eat(Token::Symbol("]")) {
+                    loop {
+                        elements.push(self.expression()?);
+                        if self.eat(Token::Symbol("]")) {
+                            break;
+                        }
+                        self.expect(Token::Symbol(","))?;
+                    }
+                }
+                Ok(List::new(elements).into())
+            }
+            _ => {
+                self.expect_description(String::from("number"));
+                self.expect_description(String::from("string"));
+                self.expect_description(String::from("identifier"));
+                Err(self.error())
+            }
+        }
+    }
+
+    /// Parses a number, string or boolean literal, or a list of them.
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek().clone() {
+            Token::Number(value) => {
+                self.advance();
+                Ok(value)
+            }
+            Token::String(value) => {
+                self.advance();
+                Ok(Value::String(value))
+            }
+            _ if self.eat(Token::Keyword("true")) => Ok(Value::Boolean(true)),
+            _ if self.eat(Token::Keyword("false")) => Ok(Value::Boolean(false)),
+            _ if self.eat(Token::Symbol("[")) => {
+                let mut elements = vec![];
+                if !self.eat(Token::Symbol("]")) {
+                    loop {
+                        elements.push(self.value()?);
+                        if self.eat(Token::Symbol("]")) {
+                            break;
+                        }
+                        self.expect(Token::Symbol(","))?;
+                    }
+                }
+                Ok(Value::List(elements))
+            }
+            _ => {
+                self.expect_description(String::from("number"));
+                self.expect_description(String::from("string"));
+                Err(self.error())
+            }
+        }
+    }
+}
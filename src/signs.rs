@@ -0,0 +1,540 @@
+//! Abstract interpretation over signs: runs a program on approximations of its values (is the
+//! number negative, zero or positive?) to learn things about every possible execution at once.
+//! It assumes arithmetic does not wrap: under `Overflow::Wrapping`, the sum of two positive numbers
+//! can come out negative.
+use crate::ast::{
+    Add, And, Append, Assign, AssignIndex, Divide, Equals, Expr, FunctionDef, GreaterOrEqual,
+    GreaterThan, If, Index, Iteration, Length, LessOrEqual, LessThan, Let, Modulo, Multiply,
+    Negate, Not, NotEquals, Or, Read, Scope, Slice, Stmt, Subtract, Variable, While,
+};
+use crate::{Environment, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    Negative,
+    Zero,
+    Positive,
+    /// Could be any number.
+    Unknown,
+}
+
+impl Sign {
+    pub fn of(number: i64) -> Self {
+        match number {
+            n if n < 0 => Sign::Negative,
+            0 => Sign::Zero,
+            _ => Sign::Positive,
+        }
+    }
+
+    /// The least precise sign covering both `self` and `other`.
+    pub fn join(self, other: Sign) -> Sign {
+        if self == other {
+            self
+        } else {
+            Sign::Unknown
+        }
+    }
+
+    /// Whether every number with the sign `other` also has this sign.
+    pub fn contains(self, other: Sign) -> bool {
+        self == other || self == Sign::Unknown
+    }
+
+    /// What can be said about `self < other`.
+    fn less_than(self, other: Sign) -> Option<bool> {
+        use Sign::*;
+        match (self, other) {
+            (Negative, Zero) | (Negative, Positive) | (Zero, Positive) => Some(true),
+            (Zero, Zero) | (Zero, Negative) | (Positive, Zero) | (Positive, Negative) => {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    /// What can be said about `self == other`.
+    fn equals(self, other: Sign) -> Option<bool> {
+        match (self, other) {
+            (Sign::Unknown, _) | (_, Sign::Unknown) => None,
+            (Sign::Zero, Sign::Zero) => Some(true),
+            (a, b) if a == b => None,
+            _ => Some(false),
+        }
+    }
+
+    /// The sign of a quotient or remainder. Rounding towards zero can turn anything into zero,
+    /// so only a zero on the left gives a definite answer.
+    fn divide(self, _other: Sign) -> Sign {
+        match self {
+            Sign::Zero => Sign::Zero,
+            _ => Sign::Unknown,
+        }
+    }
+}
+
+impl ops::Neg for Sign {
+    type Output = Sign;
+
+    fn neg(self) -> Sign {
+        match self {
+            Sign::Negative => Sign::Positive,
+            Sign::Positive => Sign::Negative,
+            sign => sign,
+        }
+    }
+}
+
+impl ops::Add for Sign {
+    type Output = Sign;
+
+    fn add(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::Zero, sign) | (sign, Sign::Zero) => sign,
+            (a, b) if a == b => a,
+            _ => Sign::Unknown,
+        }
+    }
+}
+
+impl ops::Sub for Sign {
+    type Output = Sign;
+
+    fn sub(self, other: Sign) -> Sign {
+        self + -other
+    }
+}
+
+impl ops::Mul for Sign {
+    type Output = Sign;
+
+    fn mul(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::Zero, _) | (_, Sign::Zero) => Sign::Zero,
+            (Sign::Unknown, _) | (_, Sign::Unknown) => Sign::Unknown,
+            (a, b) if a == b => Sign::Positive,
+            _ => Sign::Negative,
+        }
+    }
+}
+
+impl fmt::Display for Sign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Sign::Negative => write!(f, "negative"),
+            Sign::Zero => write!(f, "zero"),
+            Sign::Positive => write!(f, "positive"),
+            Sign::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// An approximation of a `Value`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbstractValue {
+    Number(Sign),
+    /// A boolean, if it is known which one.
+    Boolean(Option<bool>),
+    /// Some string; nothing is tracked about which.
+    String,
+    /// Some list; nothing is tracked about its length or elements.
+    List,
+    /// Some function.
+    Function,
+    /// Nothing is known, not even the type (e.g. the program misuses a value).
+    Any,
+}
+
+impl AbstractValue {
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Number(number) => AbstractValue::Number(Sign::of(*number)),
+            // Too large to be zero.
+            Value::BigNumber(number) if number.is_negative() => {
+                AbstractValue::Number(Sign::Negative)
+            }
+            Value::BigNumber(_) => AbstractValue::Number(Sign::Positive),
+            Value::Boolean(boolean) => AbstractValue::Boolean(Some(*boolean)),
+            Value::String(_) => AbstractValue::String,
+            Value::List(_) => AbstractValue::List,
+            Value::Function(_) => AbstractValue::Function,
+        }
+    }
+
+    /// The least precise approximation covering both `self` and `other`.
+    pub fn join(self, other: AbstractValue) -> AbstractValue {
+        match (self, other) {
+            (AbstractValue::Number(a), AbstractValue::Number(b)) => {
+                AbstractValue::Number(a.join(b))
+            }
+            (AbstractValue::Boolean(a), AbstractValue::Boolean(b)) => {
+                AbstractValue::Boolean(if a == b { a } else { None })
+            }
+            (AbstractValue::String, AbstractValue::String) => AbstractValue::String,
+            (AbstractValue::List, AbstractValue::List) => AbstractValue::List,
+            (AbstractValue::Function, AbstractValue::Function) => AbstractValue::Function,
+            _ => AbstractValue::Any,
+        }
+    }
+}
+
+impl fmt::Display for AbstractValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            AbstractValue::Number(sign) => write!(f, "{}", sign),
+            AbstractValue::Boolean(Some(value)) => write!(f, "{}", value),
+            AbstractValue::Boolean(None) => write!(f, "boolean"),
+            AbstractValue::String => write!(f, "string"),
+            AbstractValue::List => write!(f, "list"),
+            AbstractValue::Function => write!(f, "function"),
+            AbstractValue::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// The abstract counterpart of an `Environment`. A variable missing from it has not been
+/// assigned on any path reaching this point.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AbstractEnvironment(HashMap<String, AbstractValue>);
+
+impl AbstractEnvironment {
+    pub fn empty() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Adds or replaces a variable, returning a new environment.
+    pub fn update(&self, name: &str, value: AbstractValue) -> Self {
+        let mut map = self.0.clone();
+        map.insert(name.to_string(), value);
+        Self(map)
+    }
+
+    /// Removes a variable, returning a new environment.
+    pub fn remove(&self, name: &str) -> Self {
+        let mut map = self.0.clone();
+        map.remove(name);
+        Self(map)
+    }
+
+    pub fn get(&self, name: &str) -> Option<AbstractValue> {
+        self.0.get(name).copied()
+    }
+
+    /// Merges the outcomes of two paths through a program.
+    pub fn join(&self, other: &AbstractEnvironment) -> Self {
+        let mut map = self.0.clone();
+        for (name, value) in &other.0 {
+            let joined = match map.get(name) {
+                Some(existing) => existing.join(*value),
+                None => *value,
+            };
+            map.insert(name.clone(), joined);
+        }
+        Self(map)
+    }
+}
+
+impl From<&Environment> for AbstractEnvironment {
+    fn from(environment: &Environment) -> Self {
+        Self(
+            environment
+                .iter()
+                .map(|(name, value)| (name.to_string(), AbstractValue::of(value)))
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for AbstractEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let mut bindings: Vec<String> = self
+            .0
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        bindings.sort();
+        write!(f, "{{ {} }}", bindings.join(", "))
+    }
+}
+
+pub trait Expression {
+    fn abstract_evaluate(&self, environment: &AbstractEnvironment) -> AbstractValue;
+}
+
+impl Expression for Expr {
+    fn abstract_evaluate(&self, environment: &AbstractEnvironment) -> AbstractValue {
+        use AbstractValue::{Any, Boolean, Number};
+        let signs = |left: &Expr, right: &Expr| match (
+            left.abstract_evaluate(environment),
+            right.abstract_evaluate(environment),
+        ) {
+            (Number(a), Number(b)) => Some((a, b)),
+            _ => None,
+        };
+        let arithmetic = |left: &Expr, right: &Expr, operation: fn(Sign, Sign) -> Sign| {
+            signs(left, right).map_or(Any, |(a, b)| Number(operation(a, b)))
+        };
+        let comparison =
+            |left: &Expr, right: &Expr, operation: fn(Sign, Sign) -> Option<bool>| match (
+                left.abstract_evaluate(environment),
+                right.abstract_evaluate(environment),
+            ) {
+                (Number(a), Number(b)) => Boolean(operation(a, b)),
+                (AbstractValue::String, AbstractValue::String) => Boolean(None),
+                _ => Any,
+            };
+        let equality = |left: &Expr, right: &Expr| match (
+            left.abstract_evaluate(environment),
+            right.abstract_evaluate(environment),
+        ) {
+            (Number(a), Number(b)) => Some(a.equals(b)),
+            (Boolean(Some(a)), Boolean(Some(b))) => Some(Some(a == b)),
+            (Boolean(_), Boolean(_)) | (AbstractValue::String, AbstractValue::String) => Some(None),
+            _ => None,
+        };
+        // `&&` and `||` settle on a `decisive` left operand without looking right.
+        let logical =
+            |left: &Expr, right: &Expr, decisive: bool| match left.abstract_evaluate(environment) {
+                Boolean(Some(value)) if value == decisive => Boolean(Some(decisive)),
+                Boolean(left) => match right.abstract_evaluate(environment) {
+                    Boolean(Some(value)) if value == decisive => Boolean(Some(decisive)),
+                    Boolean(right) => Boolean(left.and(right)),
+                    _ => Any,
+                },
+                _ => Any,
+            };
+        match self {
+            Expr::Value(value) => AbstractValue::of(value),
+            Expr::Add(Add(left, right)) => match (
+                left.abstract_evaluate(environment),
+                right.abstract_evaluate(environment),
+            ) {
+                (Number(a), Number(b)) => Number(a + b),
+                (AbstractValue::String, AbstractValue::String) => AbstractValue::String,
+                _ => Any,
+            },
+            Expr::Subtract(Subtract(left, right)) => arithmetic(left, right, |a, b| a - b),
+            Expr::Multiply(Multiply(left, right)) => arithmetic(left, right, |a, b| a * b),
+            Expr::Divide(Divide(left, right)) | Expr::Modulo(Modulo(left, right)) => {
+                arithmetic(left, right, Sign::divide)
+            }
+            Expr::Negate(Negate(operand)) => match operand.abstract_evaluate(environment) {
+                Number(sign) => Number(-sign),
+                _ => Any,
+            },
+            Expr::Equals(Equals(left, right)) => equality(left, right).map_or(Any, Boolean),
+            Expr::NotEquals(NotEquals(left, right)) => {
+                equality(left, right).map_or(Any, |equal| Boolean(equal.map(|equal| !equal)))
+            }
+            Expr::LessThan(LessThan(left, right)) => comparison(left, right, Sign::less_than),
+            Expr::GreaterThan(GreaterThan(left, right)) => {
+                comparison(left, right, |a, b| b.less_than(a))
+            }
+            Expr::LessOrEqual(LessOrEqual(left, right)) => {
+                comparison(left, right, |a, b| b.less_than(a).map(|greater| !greater))
+            }
+            Expr::GreaterOrEqual(GreaterOrEqual(left, right)) => {
+                comparison(left, right, |a, b| a.less_than(b).map(|less| !less))
+            }
+            Expr::And(And(left, right)) => logical(left, right, false),
+            Expr::Or(Or(left, right)) => logical(left, right, true),
+            Expr::Not(Not(operand)) => match operand.abstract_evaluate(environment) {
+                Boolean(value) => Boolean(value.map(|value| !value)),
+                _ => Any,
+            },
+            // A length is never negative, but could be zero.
+            Expr::Length(Length(operand)) => match operand.abstract_evaluate(environment) {
+                AbstractValue::String | AbstractValue::List => Number(Sign::Unknown),
+                _ => Any,
+            },
+            Expr::Index(Index(operand, index)) => match (
+                operand.abstract_evaluate(environment),
+                index.abstract_evaluate(environment),
+            ) {
+                (AbstractValue::String, Number(_)) => AbstractValue::String,
+                // Nothing is known about the elements of a list.
+                _ => Any,
+            },
+            Expr::Slice(Slice(operand, start, end)) => match (
+                operand.abstract_evaluate(environment),
+                start.abstract_evaluate(environment),
+                end.abstract_evaluate(environment),
+            ) {
+                (AbstractValue::String, Number(_), Number(_)) => AbstractValue::String,
+                (AbstractValue::List, Number(_), Number(_)) => AbstractValue::List,
+                _ => Any,
+            },
+            Expr::List(_) => AbstractValue::List,
+            Expr::Append(Append(list, _)) => match list.abstract_evaluate(environment) {
+                AbstractValue::List => AbstractValue::List,
+                _ => Any,
+            },
+            // Calls are not followed, so nothing is known about what they return.
+            Expr::Call(_) | Expr::Frame(_) => Any,
+            Expr::Variable(Variable(name)) => environment.get(name).unwrap_or(Any),
+        }
+    }
+}
+
+pub trait Statement {
+    /// Approximates the environment after running the statement, covering every path through it.
+    fn abstract_evaluate(&self, environment: &AbstractEnvironment) -> AbstractEnvironment;
+}
+
+/// Approximates the environments a statement can leave behind, depending on how it ends: by
+/// finishing, by `break` or by `continue`. `None` means it never ends that way.
+#[derive(Default)]
+struct Paths {
+    finished: Option<AbstractEnvironment>,
+    broken: Option<AbstractEnvironment>,
+    continued: Option<AbstractEnvironment>,
+}
+
+impl Paths {
+    fn finished(environment: AbstractEnvironment) -> Self {
+        Paths {
+            finished: Some(environment),
+            ..Paths::default()
+        }
+    }
+
+    /// Covers the paths of either statement.
+    fn join(self, other: Paths) -> Self {
+        Paths {
+            finished: either(self.finished, other.finished),
+            broken: either(self.broken, other.broken),
+            continued: either(self.continued, other.continued),
+        }
+    }
+
+    /// Applies `f` to every environment, however it was reached.
+    fn map<F: Fn(AbstractEnvironment) -> AbstractEnvironment>(self, f: F) -> Self {
+        Paths {
+            finished: self.finished.map(&f),
+            broken: self.broken.map(&f),
+            continued: self.continued.map(&f),
+        }
+    }
+}
+
+fn either(
+    left: Option<AbstractEnvironment>,
+    right: Option<AbstractEnvironment>,
+) -> Option<AbstractEnvironment> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(left.join(&right)),
+        (left, right) => left.or(right),
+    }
+}
+
+/// Runs a loop from `environment` until nothing changes, returning every environment it can be
+/// left in. Every join can only lose precision, and there is little to lose, so this terminates.
+fn looping(
+    While(condition, body): &While,
+    environment: AbstractEnvironment,
+) -> AbstractEnvironment {
+    let mut current = environment;
+    let mut broken = None;
+    loop {
+        if condition.abstract_evaluate(&current) == AbstractValue::Boolean(Some(false)) {
+            break;
+        }
+        let body = paths(body, &current);
+        broken = either(broken, body.broken);
+        let next = [body.finished, body.continued]
+            .iter()
+            .flatten()
+            .fold(current.clone(), |next, after| next.join(after));
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+    either(Some(current), broken).unwrap()
+}
+
+fn paths(statement: &Stmt, environment: &AbstractEnvironment) -> Paths {
+    match statement {
+        Stmt::DoNothing
+        | Stmt::Assign(_)
+        | Stmt::AssignIndex(_)
+        | Stmt::FunctionDef(_)
+        | Stmt::Print(_)
+        | Stmt::Read(_) => Paths::finished(statement.abstract_evaluate(environment)),
+        Stmt::Break => Paths {
+            broken: Some(environment.clone()),
+            ..Paths::default()
+        },
+        Stmt::Continue => Paths {
+            continued: Some(environment.clone()),
+            ..Paths::default()
+        },
+        Stmt::If(If(condition, consequence, alternative)) => {
+            match condition.abstract_evaluate(environment) {
+                AbstractValue::Boolean(Some(true)) => paths(consequence, environment),
+                AbstractValue::Boolean(Some(false)) => paths(alternative, environment),
+                _ => paths(consequence, environment).join(paths(alternative, environment)),
+            }
+        }
+        Stmt::Let(Let(name, expression, body)) => {
+            let value = expression.abstract_evaluate(environment);
+            let hidden = environment.get(name);
+            paths(body, &environment.update(name, value)).map(|after| match hidden {
+                Some(hidden) => after.update(name, hidden),
+                None => after.remove(name),
+            })
+        }
+        // What the variable hides is not known here, so it is left as the body leaves it.
+        Stmt::Scope(Scope(_, body)) => paths(body, environment),
+        // Each statement starts where the last finished, until one never does.
+        Stmt::Sequence(sequence) => {
+            let mut all = Paths::finished(environment.clone());
+            for statement in sequence.statements() {
+                let before = match all.finished.take() {
+                    Some(before) => before,
+                    None => break,
+                };
+                let next = paths(statement, &before);
+                all = Paths {
+                    finished: next.finished,
+                    broken: either(all.broken, next.broken),
+                    continued: either(all.continued, next.continued),
+                };
+            }
+            all
+        }
+        Stmt::Sugared(sugared) => paths(sugared.core(), environment),
+        Stmt::While(node) => Paths::finished(looping(node, environment.clone())),
+        Stmt::Iteration(Iteration(body, node)) => {
+            let body = paths(body, environment);
+            let rest = either(body.finished, body.continued).map(|after| looping(node, after));
+            Paths::finished(either(rest, body.broken).unwrap_or_else(|| environment.clone()))
+        }
+    }
+}
+
+impl Statement for Stmt {
+    fn abstract_evaluate(&self, environment: &AbstractEnvironment) -> AbstractEnvironment {
+        match self {
+            Stmt::DoNothing | Stmt::Print(_) => environment.clone(),
+            Stmt::Assign(Assign(name, expression)) => {
+                environment.update(name, expression.abstract_evaluate(environment))
+            }
+            // Whatever the variable held, a run which gets past this holds a list in it.
+            Stmt::AssignIndex(AssignIndex(name, _, _)) => {
+                environment.update(name, AbstractValue::List)
+            }
+            Stmt::FunctionDef(FunctionDef(name, _, _, _)) => {
+                environment.update(name, AbstractValue::Function)
+            }
+            Stmt::Read(Read(name)) => environment.update(name, AbstractValue::Any),
+            // A jump with no loop to go to stops the program, so only finishing counts.
+            _ => paths(self, environment)
+                .finished
+                .unwrap_or_else(|| environment.clone()),
+        }
+    }
+}
@@ -1,12 +1,33 @@
 mod expressions;
 mod statements;
-use crate::{Environment, Printable};
+use crate::ast::Stmt;
+use crate::types::{self, TypeContext, TypeError};
+use crate::{Environment, EvalError, Input, Outcome, Output, Overflow, Printable, Runtime};
 pub use expressions::*;
 pub use statements::*;
+use std::fmt;
 
+/// One configuration of the machine: the statement left to run, and the environment so far.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Step {
+    pub statement: Stmt,
+    pub environment: Environment,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}, {}", self.statement.inspect(), self.environment)
+    }
+}
+
+/// Reduces a statement one step at a time. As an `Iterator`, it yields the starting
+/// configuration followed by the result of each reduction.
 pub struct Machine {
     statement: Stmt,
     environment: Environment,
+    runtime: Runtime,
+    started: bool,
+    finished: bool,
 }
 
 impl Machine {
@@ -14,23 +35,127 @@ impl Machine {
         Self {
             statement: stmt.into(),
             environment: Environment::empty(),
+            runtime: Runtime::default(),
+            started: false,
+            finished: false,
         }
     }
-    fn print(&self) {
-        println!("{}, {}", self.statement.inspect(), &self.environment);
+
+    /// Like `new`, but refuses a program that does not type-check.
+    pub fn checked<S: Into<Stmt>>(stmt: S) -> Result<Self, Vec<TypeError>> {
+        let machine = Self::new(stmt);
+        types::check(&machine.statement, &TypeContext::from(&machine.environment))?;
+        Ok(machine)
+    }
+
+    /// Chooses what arithmetic does when it overflows; `Overflow::Promote` unless told otherwise.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.runtime.overflow = overflow;
+        self
+    }
+
+    /// Chooses where `Print` statements write; standard output unless told otherwise.
+    pub fn with_output(mut self, output: Output) -> Self {
+        self.runtime.output = output;
+        self
+    }
+
+    /// Chooses where `Read` statements take values from; standard input unless told otherwise.
+    pub fn with_input(mut self, input: Input) -> Self {
+        self.runtime.input = input;
+        self
+    }
+
+    /// Chooses how many calls may be unfinished at once; `DEFAULT_CALL_DEPTH` unless told
+    /// otherwise, and never more than `NATIVE_CALL_DEPTH`.
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.runtime.max_call_depth = depth;
+        self
+    }
+
+    /// Where `Print` statements have been writing, e.g. to read back a `Buffer`.
+    pub fn output(&self) -> &Output {
+        &self.runtime.output
     }
 
-    fn step(&mut self) {
-        let (statement, environment) = self.statement.reduce(&self.environment);
+    fn step(&mut self) -> Result<(), EvalError> {
+        let (statement, environment) = self
+            .statement
+            .reduce_with(&self.environment, &mut self.runtime)?;
         self.statement = statement;
         self.environment = environment;
+        Ok(())
     }
 
-    pub fn run(&mut self) {
-        while self.statement.is_reducible() {
-            self.print();
-            self.step();
+    /// Runs the machine to completion, collecting every configuration it passes through.
+    pub fn trace(&mut self) -> Result<Vec<Step>, EvalError> {
+        self.collect()
+    }
+
+    /// Reduces the statement until it can go no further, or until a step fails, printing each
+    /// configuration along the way. What the program itself prints goes to its `Output`.
+    pub fn run(&mut self) -> Result<(), EvalError> {
+        for step in self {
+            println!("{}", step?);
+        }
+        Ok(())
+    }
+
+    /// Reduces the statement until it can go no further, or until `limit` steps have been taken,
+    /// without printing the configurations along the way.
+    pub fn finish_with_limit(&mut self, limit: usize) -> Result<Outcome, EvalError> {
+        self.limited(limit, |_| {})
+    }
+
+    /// Like `run`, but gives up once `limit` steps have been taken.
+    pub fn run_with_limit(&mut self, limit: usize) -> Result<Outcome, EvalError> {
+        self.limited(limit, |step| println!("{}", step))
+    }
+
+    /// Shows each configuration to `visit` until the machine finishes or `limit` steps are taken.
+    fn limited<F: FnMut(&Step)>(
+        &mut self,
+        limit: usize,
+        mut visit: F,
+    ) -> Result<Outcome, EvalError> {
+        for (steps, step) in self.enumerate() {
+            let step = step?;
+            visit(&step);
+            if !step.statement.is_reducible() {
+                return Ok(Outcome::Finished(step.environment));
+            }
+            if steps == limit {
+                return Ok(Outcome::OutOfFuel {
+                    steps,
+                    environment: step.environment,
+                });
+            }
+        }
+        Ok(Outcome::Finished(self.environment.clone()))
+    }
+}
+
+impl Iterator for Machine {
+    type Item = Result<Step, EvalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if self.started {
+            if !self.statement.is_reducible() {
+                self.finished = true;
+                return None;
+            }
+            if let Err(error) = self.step() {
+                self.finished = true;
+                return Some(Err(error));
+            }
         }
-        self.print();
+        self.started = true;
+        Some(Ok(Step {
+            statement: self.statement.clone(),
+            environment: self.environment.clone(),
+        }))
     }
 }
@@ -1,173 +1,334 @@
-use crate::small_step::{Environment, Printable};
-use crate::Value;
+use crate::ast::{
+    Add, And, Append, Call, Divide, Equals, Expr, Frame, FunctionDef, GreaterOrEqual, GreaterThan,
+    Index, Length, LessOrEqual, LessThan, List, Modulo, Multiply, Negate, Not, NotEquals, Or,
+    Slice, Subtract, Variable,
+};
+use crate::operators::{self, Binary, Unary};
+use crate::small_step::Statement;
+use crate::{Environment, EvalError, Printable, Runtime, Value};
 use std::rc::Rc;
 
-/// Boxed version of an `Expression` (so they can be passed around generically).
-pub type Expr = Rc<Box<dyn Expression>>;
-pub trait Expression: Printable {
+pub trait Expression {
     fn is_reducible(&self) -> bool;
-    fn reduce(&self, environment: &Environment) -> Expr;
-    fn as_value(&self) -> Option<&Value> {
-        None
-    }
-}
+    /// Reduces with a particular `Runtime`: its `Overflow` policy, its `Output` for
+    /// `Print` and its `Input` for `Read`.
+    fn reduce_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Expr, EvalError>;
 
-impl Expression for Value {
-    fn is_reducible(&self) -> bool {
-        false
-    }
-    fn reduce(&self, _environment: &Environment) -> Expr {
-        panic!("Cannot reduce a Value.")
-    }
-    fn as_value(&self) -> Option<&Value> {
-        Some(&self)
+    fn reduce(&self, environment: &Environment) -> Result<Expr, EvalError> {
+        self.reduce_with(environment, &mut Runtime::default())
     }
 }
 
-impl From<Value> for Expr {
-    fn from(expression: Value) -> Self {
-        Rc::new(Box::new(expression))
-    }
+/// Unpacks an operand which should already be fully reduced.
+fn value(operand: &Expr) -> Result<Value, EvalError> {
+    operand
+        .as_value()
+        .cloned()
+        .ok_or_else(|| EvalError::Irreducible(operand.inspect()))
 }
 
-pub struct Add(Expr, Expr);
-
-impl Add {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
+/// Reduces whichever operand of a binary operator is next in line, or else combines the two.
+fn reduce_binary<N>(
+    left: &Rc<Expr>,
+    right: &Rc<Expr>,
+    environment: &Environment,
+    runtime: &mut Runtime,
+    node: N,
+    operation: Binary,
+) -> Result<Expr, EvalError>
+where
+    N: Fn(Rc<Expr>, Rc<Expr>) -> Expr,
+{
+    Ok(match (left.is_reducible(), right.is_reducible()) {
+        (true, _) => node(
+            Rc::new(left.reduce_with(environment, runtime)?),
+            right.clone(),
+        ),
+        (_, true) => node(
+            left.clone(),
+            Rc::new(right.reduce_with(environment, runtime)?),
+        ),
+        _ => operation(value(left)?, value(right)?, runtime.overflow)?.into(),
+    })
 }
 
-impl Expression for Add {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-
-    fn reduce(&self, environment: &Environment) -> Expr {
-        match (self.0.is_reducible(), self.1.is_reducible()) {
-            (true, _) => Add::new(self.0.reduce(environment), self.1.clone()).into(),
-            (_, true) => Add::new(self.0.clone(), self.1.reduce(environment)).into(),
-            _ => match (self.0.as_value(), self.1.as_value()) {
-                (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Number(a + b).into(),
-                _ => panic!("Unexpected values"),
-            },
-        }
+/// Reduces the operand of a prefix operator, or else applies it.
+fn reduce_unary<N>(
+    operand: &Rc<Expr>,
+    environment: &Environment,
+    runtime: &mut Runtime,
+    node: N,
+    operation: Unary,
+) -> Result<Expr, EvalError>
+where
+    N: Fn(Rc<Expr>) -> Expr,
+{
+    if operand.is_reducible() {
+        Ok(node(Rc::new(operand.reduce_with(environment, runtime)?)))
+    } else {
+        Ok(operation(value(operand)?, runtime.overflow)?.into())
     }
 }
 
-impl Printable for Add {
-    fn to_s(&self) -> String {
-        format!("{} + {}", self.0.to_s(), self.1.to_s())
+/// Reduces `&&` (`decisive` is false) or `||` (`decisive` is true). Once the left operand is
+/// `decisive` it is the answer, and the right operand is never reduced.
+fn reduce_logical<N>(
+    left: &Rc<Expr>,
+    right: &Rc<Expr>,
+    environment: &Environment,
+    runtime: &mut Runtime,
+    node: N,
+    decisive: bool,
+) -> Result<Expr, EvalError>
+where
+    N: Fn(Rc<Expr>, Rc<Expr>) -> Expr,
+{
+    if left.is_reducible() {
+        return Ok(node(
+            Rc::new(left.reduce_with(environment, runtime)?),
+            right.clone(),
+        ));
     }
-}
-
-impl From<Add> for Expr {
-    fn from(expression: Add) -> Self {
-        Rc::new(Box::new(expression))
+    if operators::boolean(value(left)?)? == decisive {
+        Ok(Value::Boolean(decisive).into())
+    } else if right.is_reducible() {
+        Ok(node(
+            left.clone(),
+            Rc::new(right.reduce_with(environment, runtime)?),
+        ))
+    } else {
+        Ok(Value::Boolean(operators::boolean(value(right)?)?).into())
     }
 }
 
-pub struct Multiply(Expr, Expr);
-
-impl Multiply {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
+/// Reduces the operands of a `Slice` from left to right, then takes the slice.
+fn reduce_slice(
+    Slice(operand, start, end): &Slice,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<Expr, EvalError> {
+    let mut reduce = |expression: &Rc<Expr>| -> Result<Rc<Expr>, EvalError> {
+        Ok(Rc::new(expression.reduce_with(environment, runtime)?))
+    };
+    Ok(if operand.is_reducible() {
+        Slice(reduce(operand)?, start.clone(), end.clone()).into()
+    } else if start.is_reducible() {
+        Slice(operand.clone(), reduce(start)?, end.clone()).into()
+    } else if end.is_reducible() {
+        Slice(operand.clone(), start.clone(), reduce(end)?).into()
+    } else {
+        operators::slice(value(operand)?, value(start)?, value(end)?)?.into()
+    })
 }
 
-impl Expression for Multiply {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-
-    fn reduce(&self, environment: &Environment) -> Expr {
-        match (self.0.is_reducible(), self.1.is_reducible()) {
-            (true, _) => Multiply::new(self.0.reduce(environment), self.1.clone()).into(),
-            (_, true) => Multiply::new(self.0.clone(), self.1.reduce(environment)).into(),
-            _ => match (self.0.as_value(), self.1.as_value()) {
-                (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Number(a * b).into(),
-                _ => panic!("Unexpected values"),
-            },
+/// Reduces the first element of a list literal which is not yet a value, or else builds the list.
+fn reduce_list(
+    List(elements): &List,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<Expr, EvalError> {
+    match elements.iter().position(|element| element.is_reducible()) {
+        Some(next) => {
+            let mut elements = elements.clone();
+            elements[next] = Rc::new(elements[next].reduce_with(environment, runtime)?);
+            Ok(List(elements).into())
+        }
+        None => {
+            let values = elements.iter().map(|element| value(element));
+            Ok(Value::List(values.collect::<Result<_, _>>()?).into())
         }
     }
 }
 
-impl Printable for Multiply {
-    fn to_s(&self) -> String {
-        format!("{} * {}", self.0.to_s(), self.1.to_s())
-    }
-}
-
-impl From<Multiply> for Expr {
-    fn from(expression: Multiply) -> Self {
-        Rc::new(Box::new(expression))
+/// Reduces the arguments of a call from left to right, then enters the function, leaving a
+/// `Frame` to run its body.
+fn reduce_call(
+    Call(name, arguments): &Call,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<Expr, EvalError> {
+    if let Some(next) = arguments
+        .iter()
+        .position(|argument| argument.is_reducible())
+    {
+        let mut arguments = arguments.clone();
+        arguments[next] = Rc::new(arguments[next].reduce_with(environment, runtime)?);
+        return Ok(Call(name.clone(), arguments).into());
     }
+    let arguments = arguments.iter().map(|argument| value(argument));
+    let arguments = arguments.collect::<Result<_, _>>()?;
+    runtime.call(name, |_| {
+        let (FunctionDef(_, _, body, result), locals) =
+            operators::enter(environment, name, arguments)?;
+        Ok(Frame(name.clone(), locals, body, result).into())
+    })
 }
 
-pub struct LessThan(Expr, Expr);
-
-impl LessThan {
-    pub fn new<T1: Into<Expr>, T2: Into<Expr>>(left: T1, right: T2) -> Self {
-        Self(left.into(), right.into())
-    }
+/// Reduces the body of a call in its own environment, then its result, and finally returns the
+/// result to the caller. The caller's environment plays no part. Frames nest one inside another,
+/// so each counts towards the depth of calls a new `Call` inside it would begin at.
+fn reduce_frame(
+    Frame(name, locals, body, result): &Frame,
+    runtime: &mut Runtime,
+) -> Result<Expr, EvalError> {
+    runtime.call(name, |runtime| {
+        if body.is_reducible() {
+            let (body, locals) = body.reduce_with(locals, runtime)?;
+            Ok(Frame(name.clone(), locals, Rc::new(body), result.clone()).into())
+        } else if result.is_reducible() {
+            let result = Rc::new(result.reduce_with(locals, runtime)?);
+            Ok(Frame(name.clone(), locals.clone(), body.clone(), result).into())
+        } else {
+            Ok(value(result)?.into())
+        }
+    })
 }
 
-impl Expression for LessThan {
+impl Expression for Expr {
     fn is_reducible(&self) -> bool {
-        true
+        !matches!(self, Expr::Value(_))
     }
 
-    fn reduce(&self, environment: &Environment) -> Expr {
-        match (self.0.is_reducible(), self.1.is_reducible()) {
-            (true, _) => LessThan(self.0.reduce(environment), self.1.clone()).into(),
-            (_, true) => LessThan(self.0.clone(), self.1.reduce(environment)).into(),
-            _ => match (self.0.as_value(), self.1.as_value()) {
-                (Some(Value::Number(a)), Some(Value::Number(b))) => Value::Boolean(a < b).into(),
-                _ => panic!("Unexpected values"),
+    fn reduce_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<Expr, EvalError> {
+        let env = environment;
+        match self {
+            Expr::Value(_) => Err(EvalError::Irreducible(self.inspect())),
+            Expr::Add(Add(l, r)) => {
+                reduce_binary(l, r, env, runtime, |l, r| Add(l, r).into(), operators::add)
+            }
+            Expr::Subtract(Subtract(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Subtract(l, r).into(),
+                operators::subtract,
+            ),
+            Expr::Multiply(Multiply(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Multiply(l, r).into(),
+                operators::multiply,
+            ),
+            Expr::Divide(Divide(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Divide(l, r).into(),
+                operators::divide,
+            ),
+            Expr::Modulo(Modulo(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Modulo(l, r).into(),
+                operators::modulo,
+            ),
+            Expr::Negate(Negate(operand)) => reduce_unary(
+                operand,
+                env,
+                runtime,
+                |o| Negate(o).into(),
+                operators::negate,
+            ),
+            Expr::Equals(Equals(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Equals(l, r).into(),
+                operators::equals,
+            ),
+            Expr::NotEquals(NotEquals(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| NotEquals(l, r).into(),
+                operators::not_equals,
+            ),
+            Expr::LessThan(LessThan(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| LessThan(l, r).into(),
+                operators::less_than,
+            ),
+            Expr::GreaterThan(GreaterThan(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| GreaterThan(l, r).into(),
+                operators::greater_than,
+            ),
+            Expr::LessOrEqual(LessOrEqual(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| LessOrEqual(l, r).into(),
+                operators::less_or_equal,
+            ),
+            Expr::GreaterOrEqual(GreaterOrEqual(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| GreaterOrEqual(l, r).into(),
+                operators::greater_or_equal,
+            ),
+            Expr::And(And(l, r)) => {
+                reduce_logical(l, r, env, runtime, |l, r| And(l, r).into(), false)
+            }
+            Expr::Or(Or(l, r)) => reduce_logical(l, r, env, runtime, |l, r| Or(l, r).into(), true),
+            Expr::Not(Not(operand)) => {
+                reduce_unary(operand, env, runtime, |o| Not(o).into(), operators::not)
+            }
+            Expr::Length(Length(operand)) => reduce_unary(
+                operand,
+                env,
+                runtime,
+                |o| Length(o).into(),
+                operators::length,
+            ),
+            Expr::Index(Index(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Index(l, r).into(),
+                operators::index,
+            ),
+            Expr::Slice(slice) => reduce_slice(slice, env, runtime),
+            Expr::List(list) => reduce_list(list, env, runtime),
+            Expr::Append(Append(l, r)) => reduce_binary(
+                l,
+                r,
+                env,
+                runtime,
+                |l, r| Append(l, r).into(),
+                operators::append,
+            ),
+            Expr::Call(call) => reduce_call(call, env, runtime),
+            Expr::Frame(frame) => reduce_frame(frame, runtime),
+            Expr::Variable(Variable(name)) => match environment.get(name) {
+                Some(value) => Ok(value.clone().into()),
+                None => Err(EvalError::UnboundVariable(name.clone())),
             },
         }
     }
 }
-
-impl Printable for LessThan {
-    fn to_s(&self) -> String {
-        format!("{} < {}", self.0.to_s(), self.1.to_s())
-    }
-}
-
-impl From<LessThan> for Expr {
-    fn from(expression: LessThan) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
-
-pub struct Variable(String);
-
-impl Variable {
-    pub fn new<S: Into<String>>(name: S) -> Self {
-        Self(name.into())
-    }
-}
-
-impl Expression for Variable {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-
-    fn reduce(&self, environment: &Environment) -> Expr {
-        let value = &environment.0[&self.0];
-        value.clone().into()
-    }
-}
-
-impl Printable for Variable {
-    fn to_s(&self) -> String {
-        self.0.clone()
-    }
-}
-
-impl From<Variable> for Expr {
-    fn from(expression: Variable) -> Self {
-        Rc::new(Box::new(expression))
-    }
-}
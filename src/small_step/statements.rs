@@ -1,210 +1,276 @@
-use crate::small_step::expressions::Expr;
-use crate::{Environment, Printable, Value};
+use crate::ast::{
+    Assign, AssignIndex, FunctionDef, If, Iteration, Let, Print, Read, Scope, Sequence, Stmt, While,
+};
+use crate::operators;
+use crate::small_step::expressions::Expression;
+use crate::{Environment, EvalError, Printable, Runtime, Value};
 use std::rc::Rc;
 
-/// Boxed version of a `Statement` (so they can be passed around generically).
-pub type Stmt = Rc<Box<dyn Statement>>;
-
-pub struct DoNothing;
-
-pub trait Statement: Printable {
+pub trait Statement {
     fn is_reducible(&self) -> bool;
-    fn reduce(&self, environment: &Environment) -> (Stmt, Environment);
-    fn does_nothing(&self) -> bool {
-        false
-    }
-}
-
-impl Statement for DoNothing {
-    fn is_reducible(&self) -> bool {
-        false
-    }
-
-    fn reduce(&self, _environment: &Environment) -> (Rc<Box<dyn Statement>>, Environment) {
-        panic!("Cannot Reduce")
-    }
+    /// Reduces with a particular `Runtime`: its `Overflow` policy, its `Output` for
+    /// `Print` and its `Input` for `Read`.
+    fn reduce_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<(Stmt, Environment), EvalError>;
 
-    /// Used exclusively to know when statements are "done."
-    fn does_nothing(&self) -> bool {
-        true
+    fn reduce(&self, environment: &Environment) -> Result<(Stmt, Environment), EvalError> {
+        self.reduce_with(environment, &mut Runtime::default())
     }
 }
 
-impl From<DoNothing> for Stmt {
-    fn from(statement: DoNothing) -> Self {
-        Rc::new(Box::new(statement))
-    }
-}
-
-impl Printable for DoNothing {
-    fn to_s(&self) -> String {
-        String::from("do-nothing")
-    }
-}
-
-pub struct Assign(String, Expr);
-
-impl Assign {
-    pub fn new<S: Into<String>, E: Into<Expr>>(name: S, expression: E) -> Self {
-        Self(name.into(), expression.into())
+/// Reduces the expression being assigned, or else assigns its value.
+fn reduce_assign(
+    Assign(name, expression): &Assign,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if expression.is_reducible() {
+        Ok((
+            Assign(name.clone(), expression.reduce_with(environment, runtime)?).into(),
+            environment.clone(),
+        ))
+    } else {
+        match expression.as_value() {
+            Some(value) => Ok((Stmt::DoNothing, environment.update(name, value.clone()))),
+            None => Err(EvalError::Irreducible(expression.inspect())),
+        }
     }
 }
 
-impl Statement for Assign {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-
-    fn reduce(&self, environment: &Environment) -> (Rc<Box<dyn Statement>>, Environment) {
-        if self.1.is_reducible() {
-            (
-                Assign(self.0.clone(), self.1.reduce(environment)).into(),
-                environment.clone(),
+/// Reduces the index, then the expression being assigned, or else replaces the element.
+fn reduce_assign_index(
+    statement @ AssignIndex(name, index, expression): &AssignIndex,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if index.is_reducible() {
+        Ok((
+            AssignIndex(
+                name.clone(),
+                index.reduce_with(environment, runtime)?,
+                expression.clone(),
             )
-        } else {
-            (
-                DoNothing.into(),
-                environment.update(&self.0, self.1.as_value().cloned().unwrap()),
+            .into(),
+            environment.clone(),
+        ))
+    } else if expression.is_reducible() {
+        Ok((
+            AssignIndex(
+                name.clone(),
+                index.clone(),
+                expression.reduce_with(environment, runtime)?,
             )
-        }
-    }
-}
-
-impl From<Assign> for Stmt {
-    fn from(statement: Assign) -> Self {
-        Rc::new(Box::new(statement))
-    }
-}
-
-impl Printable for Assign {
-    fn to_s(&self) -> String {
-        format!("{} = {}", &self.0, self.1.to_s())
+            .into(),
+            environment.clone(),
+        ))
+    } else {
+        let list = environment
+            .get(name)
+            .cloned()
+            .ok_or_else(|| EvalError::UnboundVariable(name.clone()))?;
+        let (index, value) = match (index.as_value(), expression.as_value()) {
+            (Some(index), Some(value)) => (index.clone(), value.clone()),
+            _ => {
+                return Err(EvalError::Irreducible(
+                    Stmt::from(statement.clone()).inspect(),
+                ))
+            }
+        };
+        let list = operators::replace(list, index, value)?;
+        Ok((Stmt::DoNothing, environment.update(name, list)))
     }
 }
 
-pub struct If(Expr, Stmt, Stmt);
-
-impl If {
-    pub fn new<E: Into<Expr>, S1: Into<Stmt>, S2: Into<Stmt>>(
-        condition: E,
-        consequence: S1,
-        alternative: S2,
-    ) -> Self {
-        Self(condition.into(), consequence.into(), alternative.into())
+/// Reduces the condition, or else takes the branch it chooses.
+fn reduce_if(
+    If(condition, consequence, alternative): &If,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if condition.is_reducible() {
+        Ok((
+            If(
+                condition.reduce_with(environment, runtime)?,
+                consequence.clone(),
+                alternative.clone(),
+            )
+            .into(),
+            environment.clone(),
+        ))
+    } else {
+        let stmt = match condition.as_value() {
+            Some(Value::Boolean(true)) => consequence,
+            Some(Value::Boolean(false)) => alternative,
+            Some(other) => return Err(EvalError::NonBooleanCondition(other.clone())),
+            None => return Err(EvalError::Irreducible(condition.inspect())),
+        };
+        Ok(((**stmt).clone(), environment.clone()))
     }
 }
 
-impl Statement for If {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-    fn reduce(&self, environment: &Environment) -> (Rc<Box<dyn Statement>>, Environment) {
-        if self.0.is_reducible() {
-            let cond_reduced = self.0.reduce(environment);
-            (
-                If::new(cond_reduced, self.1.clone(), self.2.clone()).into(),
-                environment.clone(),
+/// Reduces the expression a `Let` declares its variable with, or else enters its scope.
+fn reduce_let(
+    Let(name, expression, body): &Let,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if expression.is_reducible() {
+        Ok((
+            Let(
+                name.clone(),
+                expression.reduce_with(environment, runtime)?,
+                body.clone(),
             )
-        } else {
-            let stmt = match self.0.as_value() {
-                Some(Value::Boolean(true)) => self.1.clone(),
-                Some(Value::Boolean(false)) => self.2.clone(),
-                _ => panic!("Condition must be boolean."),
-            };
-            (stmt, environment.clone())
+            .into(),
+            environment.clone(),
+        ))
+    } else {
+        match expression.as_value() {
+            Some(value) => Ok((
+                Scope(name.clone(), body.clone()).into(),
+                environment.enter_scope(name, value.clone()),
+            )),
+            None => Err(EvalError::Irreducible(expression.inspect())),
         }
     }
 }
 
-impl From<If> for Stmt {
-    fn from(statement: If) -> Self {
-        Rc::new(Box::new(statement))
-    }
-}
-
-impl Printable for If {
-    fn to_s(&self) -> String {
-        format!(
-            "if ({}) {{ {} }} else {{ {} }}",
-            self.0.to_s(),
-            self.1.to_s(),
-            self.2.to_s()
-        )
-    }
-}
-
-pub struct Sequence(Stmt, Stmt);
-
-impl Sequence {
-    pub fn new<S1: Into<Stmt>, S2: Into<Stmt>>(first: S1, second: S2) -> Self {
-        Self(first.into(), second.into())
-    }
-}
-
-impl Statement for Sequence {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-    fn reduce(&self, environment: &Environment) -> (Rc<Box<dyn Statement>>, Environment) {
-        if self.0.does_nothing() {
-            (self.1.clone(), environment.clone())
-        } else {
-            let (first_reduced, reduced_env) = self.0.reduce(environment);
-            (Sequence(first_reduced, self.1.clone()).into(), reduced_env)
+/// Reduces the body of a scope, exiting it once the body is done or has reached a jump.
+fn reduce_scope(
+    statement @ Scope(name, body): &Scope,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if body.is_jump() {
+        match environment.exit_scope() {
+            Some(environment) => Ok(((**body).clone(), environment)),
+            None => Err(EvalError::Irreducible(
+                Stmt::from(statement.clone()).inspect(),
+            )),
+        }
+    } else if body.is_reducible() {
+        let (body, environment) = body.reduce_with(environment, runtime)?;
+        Ok((Scope(name.clone(), Rc::new(body)).into(), environment))
+    } else {
+        match environment.exit_scope() {
+            Some(environment) => Ok((Stmt::DoNothing, environment)),
+            None => Err(EvalError::Irreducible(
+                Stmt::from(statement.clone()).inspect(),
+            )),
         }
     }
 }
 
-impl From<Sequence> for Stmt {
-    fn from(statement: Sequence) -> Self {
-        Rc::new(Box::new(statement))
+/// Reduces the expression to print, or else prints its value.
+fn reduce_print(
+    Print(expression): &Print,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if expression.is_reducible() {
+        Ok((
+            Print(expression.reduce_with(environment, runtime)?).into(),
+            environment.clone(),
+        ))
+    } else {
+        match expression.as_value() {
+            Some(value) => runtime.output.print(value)?,
+            None => return Err(EvalError::Irreducible(expression.inspect())),
+        }
+        Ok((Stmt::DoNothing, environment.clone()))
     }
 }
 
-impl Printable for Sequence {
-    fn to_s(&self) -> String {
-        format!("{}; {}", self.0.to_s(), self.1.to_s())
+/// Reduces the first statement, moving on to the second once it is done, or making way for a
+/// jump.
+fn reduce_sequence(
+    Sequence(first, second): &Sequence,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    if first.does_nothing() {
+        Ok(((**second).clone(), environment.clone()))
+    } else if first.is_jump() {
+        Ok(((**first).clone(), environment.clone()))
+    } else {
+        let (first_reduced, reduced_env) = first.reduce_with(environment, runtime)?;
+        Ok((
+            Sequence(Rc::new(first_reduced), second.clone()).into(),
+            reduced_env,
+        ))
     }
 }
 
-pub struct While(Expr, Stmt);
-
-impl While {
-    pub fn new<E: Into<Expr>, S: Into<Stmt>>(condition: E, body: S) -> Self {
-        Self(condition.into(), body.into())
-    }
-
-    /// Sort of like a `Clone`
-    pub fn from(other: &While) -> Self {
-        Self::new(other.0.clone(), other.1.clone())
+/// Reduces the body of one pass around a loop, going on to the loop again once the body is done
+/// or reaches a `continue`, and leaving it at a `break`.
+fn reduce_iteration(
+    Iteration(body, node): &Iteration,
+    environment: &Environment,
+    runtime: &mut Runtime,
+) -> Result<(Stmt, Environment), EvalError> {
+    match &**body {
+        Stmt::Break => Ok((Stmt::DoNothing, environment.clone())),
+        Stmt::Continue | Stmt::DoNothing => Ok((node.clone().into(), environment.clone())),
+        _ => {
+            let (body, environment) = body.reduce_with(environment, runtime)?;
+            Ok((Iteration(Rc::new(body), node.clone()).into(), environment))
+        }
     }
 }
 
-impl Statement for While {
-    fn is_reducible(&self) -> bool {
-        true
-    }
-    fn reduce(&self, environment: &Environment) -> (Rc<Box<dyn Statement>>, Environment) {
-        (
-            If::new(
-                self.0.clone(),
-                Sequence(self.1.clone(), While::from(&self).into()),
-                DoNothing,
-            )
-            .into(),
-            environment.clone(),
+/// Unrolls a loop into an `If` which, when its condition holds, makes one pass around it.
+fn unroll(node @ While(condition, body): &While, environment: &Environment) -> (Stmt, Environment) {
+    (
+        If(
+            condition.clone(),
+            Rc::new(Iteration(body.clone(), node.clone()).into()),
+            Rc::new(Stmt::DoNothing),
         )
-    }
+        .into(),
+        environment.clone(),
+    )
 }
 
-impl From<While> for Stmt {
-    fn from(statement: While) -> Self {
-        Rc::new(Box::new(statement))
+impl Statement for Stmt {
+    fn is_reducible(&self) -> bool {
+        !self.does_nothing()
     }
-}
 
-impl Printable for While {
-    fn to_s(&self) -> String {
-        format!("while ({}) {{ {} }}", self.0.to_s(), self.1.to_s())
+    /// Each kind of statement is reduced by a function of its own, so that a call nested deep
+    /// inside another's body needs little stack for each statement around it.
+    fn reduce_with(
+        &self,
+        environment: &Environment,
+        runtime: &mut Runtime,
+    ) -> Result<(Stmt, Environment), EvalError> {
+        match self {
+            Stmt::DoNothing => Err(EvalError::Irreducible(self.inspect())),
+            // A jump reduces only as part of the statements around it, which make way for it
+            // until it reaches its loop.
+            Stmt::Break | Stmt::Continue => Err(EvalError::OutsideLoop(self.to_s())),
+            Stmt::Assign(node) => reduce_assign(node, environment, runtime),
+            Stmt::AssignIndex(node) => reduce_assign_index(node, environment, runtime),
+            Stmt::FunctionDef(function) => {
+                let FunctionDef(name, _, _, _) = function;
+                let value = Value::Function(function.clone());
+                Ok((Stmt::DoNothing, environment.update(name, value)))
+            }
+            Stmt::If(node) => reduce_if(node, environment, runtime),
+            Stmt::Let(node) => reduce_let(node, environment, runtime),
+            Stmt::Scope(node) => reduce_scope(node, environment, runtime),
+            Stmt::Print(node) => reduce_print(node, environment, runtime),
+            Stmt::Read(Read(name)) => {
+                let value = runtime.input.read(name)?;
+                Ok((Stmt::DoNothing, environment.update(name, value)))
+            }
+            Stmt::Sequence(node) => reduce_sequence(node, environment, runtime),
+            // Sugar takes a step of its own to become the statement it stands for.
+            Stmt::Sugared(sugared) => Ok((Stmt::clone(sugared.core()), environment.clone())),
+            Stmt::While(node) => Ok(unroll(node, environment)),
+            Stmt::Iteration(node) => reduce_iteration(node, environment, runtime),
+        }
     }
 }
@@ -0,0 +1,627 @@
+//! Static semantics: works out the type of every part of a program without running it, so that
+//! ill-typed programs can be rejected up front instead of failing part way through.
+use crate::ast::{
+    Add, And, Append, Assign, AssignIndex, Call, Divide, Equals, Expr, Frame, FunctionDef,
+    GreaterOrEqual, GreaterThan, If, Index, Iteration, Length, LessOrEqual, LessThan, Let, List,
+    Modulo, Multiply, Negate, Not, NotEquals, Or, Print, Read, Scope, Slice, Stmt, Subtract,
+    Variable, While,
+};
+use crate::{Environment, Printable, Value, NATIVE_CALL_DEPTH};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Number,
+    Boolean,
+    String,
+    /// A list whose elements all have the same type, when that is known. Nothing is known about
+    /// the elements of an empty list.
+    List(Option<Box<Type>>),
+    /// A particular function. Its parameters have no declared types, so its body is checked
+    /// afresh at each call, with the types of that call's arguments.
+    Function(FunctionDef),
+    /// The type of statements, which produce no value.
+    Void,
+}
+
+impl Type {
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Number(_) | Value::BigNumber(_) => Type::Number,
+            Value::Boolean(_) => Type::Boolean,
+            Value::String(_) => Type::String,
+            // A list of mixed types can only be used as though nothing is known about it.
+            Value::List(values) => {
+                let mut types = values.iter().map(Type::of);
+                let first = types.next();
+                let element = first.and_then(|first| types.try_fold(first, |a, b| a.unify(&b)));
+                Type::List(element.map(Box::new))
+            }
+            Value::Function(function) => Type::Function(function.clone()),
+        }
+    }
+
+    /// The more precise of two compatible types, or `None` if they are incompatible. Types only
+    /// differ yet agree when one is a list whose elements are unknown.
+    fn unify(&self, other: &Type) -> Option<Type> {
+        match (self, other) {
+            (Type::List(Some(a)), Type::List(Some(b))) => a
+                .unify(b)
+                .map(|element| Type::List(Some(Box::new(element)))),
+            (Type::List(a), Type::List(b)) => Some(Type::List(a.clone().or_else(|| b.clone()))),
+            (a, b) if a == b => Some(a.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Type::Number => write!(f, "number"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::String => write!(f, "string"),
+            Type::List(Some(element)) => write!(f, "list of {}", element),
+            Type::List(None) => write!(f, "list"),
+            Type::Function(FunctionDef(name, parameters, _, _)) => {
+                write!(f, "function {}({})", name, parameters.join(", "))
+            }
+            Type::Void => write!(f, "void"),
+        }
+    }
+}
+
+/// The types of variables, as an `Environment` holds their values. A variable can be assigned
+/// yet have no known type, when its value came from a mistake that has already been reported or
+/// from a recursive call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeContext {
+    variables: HashMap<String, Option<Type>>,
+    /// The functions whose bodies are being checked for a call, so that recursion stops.
+    calls: Vec<String>,
+}
+
+impl TypeContext {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a variable's type, returning a new context.
+    pub fn update(&self, name: &str, ty: Type) -> Self {
+        self.assign(name, Some(ty))
+    }
+
+    fn assign(&self, name: &str, ty: Option<Type>) -> Self {
+        let mut context = self.clone();
+        context.variables.insert(name.to_string(), ty);
+        context
+    }
+
+    /// Forgets a variable declared by a `Let`, bringing back whatever it hid in `outer`.
+    fn restore(&self, name: &str, outer: &TypeContext) -> Self {
+        let mut context = self.clone();
+        match outer.variables.get(name) {
+            Some(ty) => context.variables.insert(name.to_string(), ty.clone()),
+            None => context.variables.remove(name),
+        };
+        context
+    }
+
+    /// The type of a variable, if it has been assigned and its type is known.
+    pub fn get(&self, name: &str) -> Option<Type> {
+        self.variables.get(name).cloned().flatten()
+    }
+}
+
+impl From<&Environment> for TypeContext {
+    fn from(environment: &Environment) -> Self {
+        Self {
+            variables: environment
+                .iter()
+                .map(|(name, value)| (name.to_string(), Some(Type::of(value))))
+                .collect(),
+            calls: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeError {
+    /// A variable was read before anything could have been assigned to it.
+    UnboundVariable(String),
+    /// The subterm has a different type from the one its surroundings need.
+    Mismatch {
+        term: Expr,
+        expected: Type,
+        found: Type,
+    },
+    /// Something which is not a function was called.
+    NotAFunction { name: String, found: Type },
+    /// A call passed a different number of arguments from the function's parameters.
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A `break` or `continue` has no loop around it to leave.
+    OutsideLoop(Stmt),
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            TypeError::UnboundVariable(name) => write!(f, "unbound variable `{}`", name),
+            TypeError::Mismatch {
+                term,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} should be a {}, but is a {}",
+                term.inspect(),
+                expected,
+                found
+            ),
+            TypeError::NotAFunction { name, found } => {
+                write!(f, "`{}` is a {}, not a function", name, found)
+            }
+            TypeError::ArgumentCount {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` takes {} arguments, but was given {}",
+                function, expected, found
+            ),
+            TypeError::OutsideLoop(jump) => write!(f, "{} outside a loop", jump.inspect()),
+        }
+    }
+}
+
+impl Error for TypeError {}
+
+pub trait Typed {
+    /// Returns the type of the term, or every type error found within it.
+    fn type_of(&self, context: &TypeContext) -> Result<Type, Vec<TypeError>>;
+}
+
+impl Typed for Expr {
+    fn type_of(&self, context: &TypeContext) -> Result<Type, Vec<TypeError>> {
+        let mut errors = vec![];
+        match infer(self, context, &mut errors) {
+            Some(ty) if errors.is_empty() => Ok(ty),
+            _ => Err(errors),
+        }
+    }
+}
+
+impl Typed for Stmt {
+    fn type_of(&self, context: &TypeContext) -> Result<Type, Vec<TypeError>> {
+        check(self, context).map(|_| Type::Void)
+    }
+}
+
+/// Checks a statement, returning the context it leaves behind: the types of every variable it
+/// is certain to have assigned.
+pub fn check(statement: &Stmt, context: &TypeContext) -> Result<TypeContext, Vec<TypeError>> {
+    let mut errors = vec![];
+    let context = check_statement(statement, context, &mut errors);
+    check_jumps(statement, false, &mut errors);
+    if errors.is_empty() {
+        Ok(context)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Infers the type of an expression, recording problems as it goes. Returns `None` only when the
+/// type cannot be known at all, so that one mistake is not reported again by everything above it.
+fn infer(expression: &Expr, context: &TypeContext, errors: &mut Vec<TypeError>) -> Option<Type> {
+    match expression {
+        Expr::Value(value) => Some(Type::of(value)),
+        Expr::Add(Add(left, right)) => {
+            agree(left, right, &[Type::Number, Type::String], context, errors)
+        }
+        Expr::Subtract(Subtract(left, right))
+        | Expr::Multiply(Multiply(left, right))
+        | Expr::Divide(Divide(left, right))
+        | Expr::Modulo(Modulo(left, right)) => {
+            expect(left, Type::Number, context, errors);
+            expect(right, Type::Number, context, errors);
+            Some(Type::Number)
+        }
+        Expr::Negate(Negate(operand)) => {
+            expect(operand, Type::Number, context, errors);
+            Some(Type::Number)
+        }
+        Expr::Equals(Equals(left, right)) | Expr::NotEquals(NotEquals(left, right)) => {
+            let types = [Type::Number, Type::Boolean, Type::String];
+            agree(left, right, &types, context, errors);
+            Some(Type::Boolean)
+        }
+        Expr::LessThan(LessThan(left, right))
+        | Expr::GreaterThan(GreaterThan(left, right))
+        | Expr::LessOrEqual(LessOrEqual(left, right))
+        | Expr::GreaterOrEqual(GreaterOrEqual(left, right)) => {
+            agree(left, right, &[Type::Number, Type::String], context, errors);
+            Some(Type::Boolean)
+        }
+        Expr::And(And(left, right)) | Expr::Or(Or(left, right)) => {
+            expect(left, Type::Boolean, context, errors);
+            expect(right, Type::Boolean, context, errors);
+            Some(Type::Boolean)
+        }
+        Expr::Not(Not(operand)) => {
+            expect(operand, Type::Boolean, context, errors);
+            Some(Type::Boolean)
+        }
+        Expr::Length(Length(operand)) => {
+            sequence(operand, context, errors);
+            Some(Type::Number)
+        }
+        Expr::Index(Index(operand, index)) => {
+            let ty = sequence(operand, context, errors);
+            expect(index, Type::Number, context, errors);
+            match ty {
+                Some(Type::List(element)) => element.map(|element| *element),
+                ty => ty,
+            }
+        }
+        Expr::Slice(Slice(operand, start, end)) => {
+            let ty = sequence(operand, context, errors);
+            expect(start, Type::Number, context, errors);
+            expect(end, Type::Number, context, errors);
+            ty
+        }
+        Expr::List(List(elements)) => {
+            let element = elements
+                .iter()
+                .fold(None, |element, item| member(element, item, context, errors));
+            Some(Type::List(element.map(Box::new)))
+        }
+        Expr::Append(Append(list, element)) => match infer(list, context, errors) {
+            Some(Type::List(known)) => {
+                let element = member(known.map(|known| *known), element, context, errors);
+                Some(Type::List(element.map(Box::new)))
+            }
+            found => {
+                if let Some(found) = found {
+                    errors.push(mismatch(list, Type::List(None), found));
+                }
+                infer(element, context, errors);
+                None
+            }
+        },
+        Expr::Call(Call(name, arguments)) => {
+            let arguments = arguments
+                .iter()
+                .map(|argument| infer(argument, context, errors))
+                .collect();
+            match context.variables.get(name) {
+                Some(Some(Type::Function(function))) => call(function, arguments, context, errors),
+                Some(Some(found)) => {
+                    errors.push(TypeError::NotAFunction {
+                        name: name.clone(),
+                        found: found.clone(),
+                    });
+                    None
+                }
+                Some(None) => None,
+                None => {
+                    errors.push(TypeError::UnboundVariable(name.clone()));
+                    None
+                }
+            }
+        }
+        Expr::Frame(Frame(_, locals, body, result)) => {
+            let locals = check_statement(body, &TypeContext::from(locals), errors);
+            infer(result, &locals, errors)
+        }
+        Expr::Variable(Variable(name)) => match context.variables.get(name) {
+            Some(ty) => ty.clone(),
+            None => {
+                errors.push(TypeError::UnboundVariable(name.clone()));
+                None
+            }
+        },
+    }
+}
+
+/// Checks the body and result of a function for a call with arguments of the given types, in an
+/// environment made as `operators::enter` makes it. A call the function makes to itself, directly
+/// or not, is taken to return whatever the rest of the function shows that it does. So is a call
+/// made with `NATIVE_CALL_DEPTH` others already being checked, as each is checked on the native
+/// stack.
+fn call(
+    function: &FunctionDef,
+    arguments: Vec<Option<Type>>,
+    context: &TypeContext,
+    errors: &mut Vec<TypeError>,
+) -> Option<Type> {
+    let FunctionDef(name, parameters, body, result) = function;
+    if parameters.len() != arguments.len() {
+        errors.push(TypeError::ArgumentCount {
+            function: name.clone(),
+            expected: parameters.len(),
+            found: arguments.len(),
+        });
+        return None;
+    }
+    if context.calls.len() >= NATIVE_CALL_DEPTH || context.calls.contains(name) {
+        return None;
+    }
+    let functions = context
+        .variables
+        .iter()
+        .filter(|(_, ty)| matches!(ty, Some(Type::Function(_))))
+        .map(|(name, ty)| (name.clone(), ty.clone()));
+    let mut calls = context.calls.clone();
+    calls.push(name.clone());
+    let locals = TypeContext {
+        variables: functions
+            .chain(parameters.iter().cloned().zip(arguments))
+            .collect(),
+        calls,
+    };
+    // A function called more than once would report the same mistake for every call.
+    let mut found = vec![];
+    let locals = check_statement(body, &locals, &mut found);
+    let ty = infer(result, &locals, &mut found);
+    for error in found {
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+    ty
+}
+
+fn mismatch(term: &Expr, expected: Type, found: Type) -> TypeError {
+    TypeError::Mismatch {
+        term: term.clone(),
+        expected,
+        found,
+    }
+}
+
+/// Infers the type of the operand of `.length`, indexing or slicing, which must be a string or
+/// a list.
+fn sequence(expression: &Expr, context: &TypeContext, errors: &mut Vec<TypeError>) -> Option<Type> {
+    match infer(expression, context, errors) {
+        Some(ty @ Type::String) | Some(ty @ Type::List(_)) => Some(ty),
+        Some(found) => {
+            errors.push(mismatch(expression, Type::String, found));
+            None
+        }
+        None => None,
+    }
+}
+
+/// Checks an element of a list against the `known` type of the other elements, returning what is
+/// then known about the type of them all.
+fn member(
+    known: Option<Type>,
+    expression: &Expr,
+    context: &TypeContext,
+    errors: &mut Vec<TypeError>,
+) -> Option<Type> {
+    match known {
+        Some(ty) => Some(expect(expression, ty, context, errors)),
+        None => infer(expression, context, errors),
+    }
+}
+
+/// Infers the type of two operands which must both have the same one of the `allowed` types,
+/// taking whichever side is known when the other is not.
+fn agree(
+    left: &Expr,
+    right: &Expr,
+    allowed: &[Type],
+    context: &TypeContext,
+    errors: &mut Vec<TypeError>,
+) -> Option<Type> {
+    match infer(left, context, errors) {
+        Some(ty) if allowed.contains(&ty) => Some(expect(right, ty, context, errors)),
+        Some(found) => {
+            errors.push(mismatch(left, allowed[0].clone(), found));
+            infer(right, context, errors);
+            None
+        }
+        None => match infer(right, context, errors) {
+            Some(ty) if allowed.contains(&ty) => Some(ty),
+            Some(found) => {
+                errors.push(mismatch(right, allowed[0].clone(), found));
+                None
+            }
+            None => None,
+        },
+    }
+}
+
+/// Checks that an expression has the `expected` type, returning the most precise version of it.
+fn expect(
+    expression: &Expr,
+    expected: Type,
+    context: &TypeContext,
+    errors: &mut Vec<TypeError>,
+) -> Type {
+    match infer(expression, context, errors) {
+        Some(found) => match expected.unify(&found) {
+            Some(ty) => ty,
+            None => {
+                errors.push(mismatch(expression, expected.clone(), found));
+                expected
+            }
+        },
+        None => expected,
+    }
+}
+
+/// Finds every `break` and `continue` with no loop around it. The body of a function starts
+/// outside any loop, wherever the function is defined.
+fn check_jumps(statement: &Stmt, in_loop: bool, errors: &mut Vec<TypeError>) {
+    match statement {
+        Stmt::Break | Stmt::Continue => {
+            if !in_loop {
+                errors.push(TypeError::OutsideLoop(statement.clone()));
+            }
+        }
+        Stmt::FunctionDef(FunctionDef(_, _, body, _)) => check_jumps(body, false, errors),
+        Stmt::If(If(_, consequence, alternative)) => {
+            check_jumps(consequence, in_loop, errors);
+            check_jumps(alternative, in_loop, errors);
+        }
+        Stmt::Let(Let(_, _, body)) | Stmt::Scope(Scope(_, body)) => {
+            check_jumps(body, in_loop, errors)
+        }
+        Stmt::Sequence(sequence) => {
+            for statement in sequence.statements() {
+                check_jumps(statement, in_loop, errors);
+            }
+        }
+        Stmt::Sugared(sugared) => once(errors, |found| check_jumps(sugared.core(), in_loop, found)),
+        Stmt::While(While(_, body)) => check_jumps(body, true, errors),
+        Stmt::Iteration(Iteration(rest, While(_, body))) => {
+            check_jumps(rest, true, errors);
+            check_jumps(body, true, errors);
+        }
+        Stmt::DoNothing
+        | Stmt::Assign(_)
+        | Stmt::AssignIndex(_)
+        | Stmt::Print(_)
+        | Stmt::Read(_) => {}
+    }
+}
+
+/// Checks the core statement that some sugar stands for. That may hold parts of the sugar more than
+/// once, such as the body of a `do`, so each problem found is reported only once.
+fn once<T>(errors: &mut Vec<TypeError>, check: impl FnOnce(&mut Vec<TypeError>) -> T) -> T {
+    let mut found = vec![];
+    let result = check(&mut found);
+    for (i, error) in found.iter().enumerate() {
+        if !found[..i].contains(error) {
+            errors.push(error.clone());
+        }
+    }
+    result
+}
+
+fn check_statement(
+    statement: &Stmt,
+    context: &TypeContext,
+    errors: &mut Vec<TypeError>,
+) -> TypeContext {
+    match statement {
+        Stmt::DoNothing | Stmt::Break | Stmt::Continue => context.clone(),
+        Stmt::Assign(Assign(name, expression)) => match context.get(name) {
+            // A variable keeps the type of its first assignment, though what it says about the
+            // elements of a list may become more precise.
+            Some(ty) => {
+                let ty = expect(expression, ty, context, errors);
+                context.update(name, ty)
+            }
+            None => context.assign(name, infer(expression, context, errors)),
+        },
+        Stmt::AssignIndex(AssignIndex(name, index, expression)) => {
+            expect(index, Type::Number, context, errors);
+            match context.variables.get(name) {
+                Some(Some(Type::List(known))) => {
+                    let known = known.clone().map(|known| *known);
+                    let element = member(known, expression, context, errors);
+                    context.update(name, Type::List(element.map(Box::new)))
+                }
+                found => {
+                    match found {
+                        Some(Some(found)) => errors.push(mismatch(
+                            &Variable::new(name.clone()).into(),
+                            Type::List(None),
+                            found.clone(),
+                        )),
+                        Some(None) => {}
+                        None => errors.push(TypeError::UnboundVariable(name.clone())),
+                    }
+                    infer(expression, context, errors);
+                    context.clone()
+                }
+            }
+        }
+        // Functions may be redefined, but a variable holding anything else keeps its type.
+        Stmt::FunctionDef(function) => {
+            let FunctionDef(name, _, _, _) = function;
+            match context.get(name) {
+                Some(Type::Function(_)) | None => {}
+                Some(ty) => errors.push(mismatch(
+                    &Variable::new(name.clone()).into(),
+                    ty,
+                    Type::Function(function.clone()),
+                )),
+            }
+            context.update(name, Type::Function(function.clone()))
+        }
+        Stmt::If(If(condition, consequence, alternative)) => {
+            expect(condition, Type::Boolean, context, errors);
+            let consequence = check_statement(consequence, context, errors);
+            let alternative = check_statement(alternative, context, errors);
+            // Only variables assigned on both branches are sure to exist afterwards. Where one
+            // branch's type is unknown, the other's is the best guess.
+            let mut merged = context.clone();
+            for (name, ty) in &consequence.variables {
+                match (ty, alternative.variables.get(name)) {
+                    (Some(ty), Some(Some(other))) => match ty.unify(other) {
+                        Some(ty) => merged = merged.update(name, ty),
+                        None => errors.push(mismatch(
+                            &Variable::new(name.clone()).into(),
+                            ty.clone(),
+                            other.clone(),
+                        )),
+                    },
+                    (ty, Some(other)) => merged = merged.assign(name, ty.clone().or(other.clone())),
+                    (_, None) => {}
+                }
+            }
+            merged
+        }
+        Stmt::Let(Let(name, expression, body)) => {
+            let ty = infer(expression, context, errors);
+            check_statement(body, &context.assign(name, ty), errors).restore(name, context)
+        }
+        // The variable of a scope already entered is in the context, with the type it had then.
+        Stmt::Scope(Scope(_, body)) => check_statement(body, context, errors),
+        // Any value can be printed.
+        Stmt::Print(Print(expression)) => {
+            infer(expression, context, errors);
+            context.clone()
+        }
+        // Nothing is known about input until it arrives, so a variable read into has an unknown
+        // type, whatever it held before.
+        Stmt::Read(Read(name)) => context.assign(name, None),
+        // The rest of a pass through a loop, and then the loop itself.
+        Stmt::Iteration(Iteration(rest, node)) => {
+            let context = check_statement(rest, context, errors);
+            check_statement(&node.clone().into(), &context, errors)
+        }
+        Stmt::Sequence(sequence) => sequence
+            .statements()
+            .fold(context.clone(), |context, statement| {
+                check_statement(statement, &context, errors)
+            }),
+        Stmt::Sugared(sugared) => once(errors, |found| {
+            check_statement(sugared.core(), context, found)
+        }),
+        Stmt::While(While(condition, body)) => {
+            expect(condition, Type::Boolean, context, errors);
+            let after = check_statement(body, context, errors);
+            // The body may never run, so nothing it assigns is guaranteed. Whatever it learns
+            // about the elements of a list that already existed holds either way, though.
+            let mut refined = context.clone();
+            for (name, ty) in &after.variables {
+                if let (true, Some(ty)) = (context.variables.contains_key(name), ty) {
+                    refined = refined.update(name, ty.clone());
+                }
+            }
+            refined
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use uc::ast::*;
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::small_step::{Machine, Statement as _};
+use uc::{
+    Environment, Printable,
+    Value::{Boolean, Number},
+};
+
+/// One tree, built once, is run by every semantics without being converted.
+#[test]
+fn one_tree_for_every_semantics() {
+    let program: Stmt = Sequence::new(
+        Assign::new("x", Number(1)),
+        While::new(
+            LessThan::new(Variable::new("x"), Number(50)),
+            Assign::new("x", Multiply::new(Variable::new("x"), Number(3))),
+        ),
+    )
+    .into();
+    let expected = Environment::from([("x", Number(81))]);
+    assert_eq!(
+        program.evaluate(&Environment::empty()),
+        Ok(expected.clone())
+    );
+    assert_eq!(
+        program.to_closure()(&Environment::empty()),
+        Ok(expected.clone())
+    );
+    let last = Machine::new(program.clone()).last().unwrap().unwrap();
+    assert_eq!(last.environment, expected);
+    assert!(!last.statement.is_reducible());
+    assert_eq!(program.to_s(), "x = 1; while (x < 50) { x = x * 3 }");
+}
+
+/// Reduction builds new trees out of the same nodes, which the other semantics run just as well.
+#[test]
+fn reduced_trees_run_anywhere() {
+    let program: Stmt = Sequence::new(
+        Assign::new("x", Add::new(Number(1), Number(2))),
+        Assign::new("y", Add::new(Variable::new("x"), Number(3))),
+    )
+    .into();
+    let (reduced, environment) = program.reduce(&Environment::empty()).unwrap();
+    assert_eq!(
+        reduced,
+        Sequence::new(
+            Assign::new("x", Number(3)),
+            Assign::new("y", Add::new(Variable::new("x"), Number(3))),
+        )
+        .into()
+    );
+    assert_eq!(
+        reduced.evaluate(&environment),
+        program.evaluate(&Environment::empty())
+    );
+}
+
+/// Each node converts into the `Expr` or `Stmt` that holds it, and values into literals.
+#[test]
+fn conversions() {
+    assert_eq!(Expr::from(Number(5)), Expr::Value(Number(5)));
+    assert_eq!(
+        Expr::from(Variable::new("x")),
+        Expr::Variable(Variable(String::from("x")))
+    );
+    assert_eq!(Stmt::from(DoNothing), Stmt::DoNothing);
+    let assign = Assign::new("x", Number(5));
+    assert_eq!(
+        Stmt::from(assign.clone()),
+        Stmt::Assign(Assign(String::from("x"), Number(5).into()))
+    );
+    assert_eq!(Stmt::from(assign).to_s(), "x = 5");
+}
+
+/// Trees are compared, and hashed, by their structure rather than by where they are kept.
+#[test]
+fn structural_equality() {
+    let build = |n: i64| -> Stmt {
+        If::new(
+            LessThan::new(Variable::new("x"), Number(n)),
+            Assign::new("y", Add::new(Variable::new("x"), Number(1))),
+            DoNothing,
+        )
+        .into()
+    };
+    assert_eq!(build(1), build(1));
+    assert_ne!(build(1), build(2));
+    assert_ne!(
+        Expr::from(Add::new(Number(1), Number(2))),
+        Expr::from(Add::new(Number(2), Number(1)))
+    );
+    let distinct: HashSet<Stmt> = vec![build(1), build(2), build(1)].into_iter().collect();
+    assert_eq!(distinct.len(), 2);
+    assert!(distinct.contains(&build(2)));
+    let environments: HashSet<Environment> = vec![
+        Environment::from([("x", Number(1))]),
+        Environment::empty().update("x", Number(1)),
+        Environment::empty().enter_scope("x", Number(1)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(environments.len(), 2);
+}
+
+/// `Debug` shows the whole structure of a tree, unlike `to_s` and `inspect`.
+#[test]
+fn debug() {
+    let expression: Expr = Add::new(Variable::new("x"), Number(1)).into();
+    assert_eq!(
+        format!("{:?}", expression),
+        "Add(Add(Variable(Variable(\"x\")), Value(Number(1))))"
+    );
+    assert_eq!(
+        format!("{:?}", Stmt::from(Assign::new("y", Boolean(true)))),
+        "Assign(Assign(\"y\", Value(Boolean(true))))"
+    );
+}
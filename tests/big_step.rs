@@ -0,0 +1,51 @@
+use uc::ast::*;
+use uc::big_step::*;
+use uc::{Environment, Value::*};
+
+#[test]
+fn comparisons() {
+    for (comparison, expected) in [
+        (LessThan::new(Number(5), Number(8)), true),
+        (LessThan::new(Number(2), Number(2)), false),
+        (LessThan::new(Number(18), Number(2)), false),
+    ] {
+        let comparison = Expr::from(comparison);
+        assert_eq!(
+            comparison.evaluate(&Environment::empty()),
+            Ok(Boolean(expected))
+        );
+    }
+}
+
+#[test]
+fn while_loop() {
+    let program: Stmt = Sequence::new(
+        Assign::new("x", Number(0)),
+        While::new(
+            LessThan::new(Variable::new("x"), Number(60_001)),
+            Assign::new("x", Add::new(Variable::new("x"), Number(2))),
+        ),
+    )
+    .into();
+    assert_eq!(
+        program.evaluate(&Environment::empty()),
+        Ok(Environment::from([("x", Number(60_002))]))
+    );
+}
+
+/// Loops run in constant stack space, however many times they go around.
+#[test]
+fn million_iterations() {
+    let program: Stmt = Sequence::new(
+        Assign::new("x", Number(0)),
+        While::new(
+            LessThan::new(Variable::new("x"), Number(1_000_000)),
+            Assign::new("x", Add::new(Variable::new("x"), Number(1))),
+        ),
+    )
+    .into();
+    assert_eq!(
+        program.evaluate(&Environment::empty()),
+        Ok(Environment::from([("x", Number(1_000_000))]))
+    );
+}
@@ -0,0 +1,70 @@
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::Statement as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::Machine;
+use uc::{Environment, Printable, Value};
+
+#[test]
+fn every_semantics_promotes() {
+    for (source, name, digits) in [
+        (
+            "f = 1; n = 30; while (n > 1) { f = f * n; n = n - 1 }",
+            "f",
+            "265252859812191058636308480000000",
+        ),
+        (
+            "a = 0; b = 1; n = 0; while (n < 100) { t = a + b; a = b; b = t; n = n + 1 }",
+            "a",
+            "354224848179261915075",
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        let expected = Value::integer(digits.parse().unwrap());
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        let denotational = program.to_closure()(&Environment::empty()).unwrap();
+        let small_step = Machine::new(program).last().unwrap().unwrap().environment;
+        for environment in [&big_step, &denotational, &small_step] {
+            assert_eq!(environment.get(name), Some(&expected));
+        }
+    }
+}
+
+/// Results come back down to `Value::Number` as soon as they fit again.
+#[test]
+fn results_shrink_back() {
+    let environment = parse_statement("x = 9223372036854775807 * 9223372036854775807")
+        .unwrap()
+        .evaluate(&Environment::empty())
+        .unwrap();
+    for (source, expected) in [
+        ("x / 9223372036854775807", "9223372036854775807"),
+        ("x - x + 1", "1"),
+        ("-x < 0 && x > 9223372036854775807", "true"),
+        ("x % 10", "9"),
+        ("x == 85070591730234615847396907784232501249", "true"),
+    ] {
+        let value = parse_expression(source)
+            .unwrap()
+            .evaluate(&environment)
+            .unwrap();
+        assert_eq!(value.to_s(), expected);
+    }
+    assert_eq!(
+        parse_expression("x - x").unwrap().evaluate(&environment),
+        Ok(Value::Number(0))
+    );
+}
+
+/// Big literals print in full and read back the same.
+#[test]
+fn literals_round_trip() {
+    for source in [
+        "x = 123456789012345678901234567890",
+        "y = -(123456789012345678901234567890)",
+        "z = -123456789012345678901234567890 + 1",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(program.to_s(), source);
+        assert_eq!(parse_statement(&program.to_s()), Ok(program));
+    }
+}
@@ -0,0 +1,142 @@
+use uc::ast::Stmt;
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::{parse_statement, parse_trace};
+use uc::signs::{AbstractEnvironment, AbstractValue, Sign, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext, TypeError};
+use uc::{Environment, EvalError, Printable};
+
+#[test]
+fn every_semantics_agrees() {
+    let programs = [
+        // A search which stops at the first match, with no flag variable.
+        (
+            "xs = [4, 8, 15, 16, 23, 42]; i = 0; found = -1; while (i < xs.length) { \
+             if (xs[i] > 10) { found = xs[i]; break } else { do-nothing }; i = i + 1 }",
+            "{ found=15, i=2, xs=[4, 8, 15, 16, 23, 42] }",
+        ),
+        (
+            "i = 0; total = 0; while (i < 10) { i = i + 1; \
+             if (i % 2 == 0) { continue } else { do-nothing }; total = total + i }",
+            "{ i=10, total=25 }",
+        ),
+        // A `break` leaves only the innermost loop.
+        (
+            "primes = []; n = 2; while (n < 30) { d = 2; prime = true; while (d * d <= n) { \
+             if (n % d == 0) { prime = false; break } else { d = d + 1 } }; \
+             if (prime) { primes = primes.append(n) } else { do-nothing }; n = n + 1 }",
+            "{ d=6, n=30, prime=true, primes=[2, 3, 5, 7, 11, 13, 17, 19, 23, 29] }",
+        ),
+        // Jumping out of a block still forgets the variables it declared.
+        (
+            "n = 0; while (true) { let (square = n * n) { \
+             if (square > 50) { break } else { n = n + 1; continue } } }",
+            "{ n=8 }",
+        ),
+        // A loop inside a function is the function's own.
+        (
+            "def first(xs, limit) { r = -1; i = 0; while (i < xs.length) { \
+             if (xs[i] > limit) { r = i; break } else { i = i + 1 } }; return r }; \
+             i = 0; hits = []; while (i < 3) { i = i + 1; \
+             hits = hits.append(first([1, 5, 10], i * 4)) }",
+            "{ first=def first(xs, limit) { r = -1; i = 0; while (i < xs.length) { \
+             if (xs[i] > limit) { r = i; break } else { i = i + 1 } }; return r }, \
+             hits=[1, 2, -1], i=3 }",
+        ),
+    ];
+    for (source, expected) in programs {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+        types::check(&program, &TypeContext::empty()).unwrap();
+
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        assert_eq!(big_step.to_string(), expected);
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Ok(big_step.clone())
+        );
+        let small_step = Machine::new(program).last().unwrap().unwrap().environment;
+        assert_eq!(small_step, big_step);
+    }
+}
+
+/// A pass part way around a loop is only written by a trace, so a program can't start with one.
+#[test]
+fn passes_only_parse_as_traces() {
+    let source = "{ break }; while (true) { x = 1 }";
+    assert!(parse_statement(source).is_err());
+    assert!(matches!(parse_trace(source), Ok(Stmt::Iteration(_))));
+}
+
+/// The machine carries a jump outwards, statement by statement, until it reaches its loop.
+#[test]
+fn machine_carries_jumps_outwards() {
+    let program = parse_statement("while (true) { x = 1; break; x = 2 }").unwrap();
+    let trace = Machine::new(program).trace().unwrap();
+    let statements: Vec<String> = trace.iter().map(|step| step.statement.to_s()).collect();
+    assert!(statements.contains(&String::from(
+        "{ break; x = 2 }; while (true) { x = 1; break; x = 2 }"
+    )));
+    assert!(statements.contains(&String::from(
+        "{ break }; while (true) { x = 1; break; x = 2 }"
+    )));
+    assert_eq!(trace.last().unwrap().statement, Stmt::DoNothing);
+}
+
+/// Each step of a loop's trace, a pass part way through included, parses back to itself.
+#[test]
+fn loop_traces_parse() {
+    for source in [
+        "i = 0; while (i < 3) { i = i + 1; if (i == 2) { continue } else { do-nothing }; x = i }",
+        "n = 0; while (true) { let (m = n + 1) { n = m; if (n > 2) { break } else { do-nothing } } }",
+        "i = 0; do { i = i + 1 } while (i < 2)",
+        "for (i = 0; i < 3; i += 1) { let (j = i) { if (j == 1) { continue } else { print j } } }",
+    ] {
+        let trace = Machine::new(parse_statement(source).unwrap()).trace().unwrap();
+        assert!(trace
+            .iter()
+            .any(|step| matches!(step.statement, Stmt::Iteration(_))));
+        for step in &trace {
+            assert_eq!(
+                parse_trace(&step.statement.to_s()),
+                Ok(step.statement.clone())
+            );
+        }
+    }
+}
+
+/// Had the analysis ignored the `break`, it would claim `x` ends up negative.
+#[test]
+fn signs_follow_break() {
+    let program = parse_statement("x = -1; while (x < 0) { x = 1; break; x = -1 }").unwrap();
+    let after = program.abstract_evaluate(&AbstractEnvironment::empty());
+    assert_eq!(after.get("x"), Some(AbstractValue::Number(Sign::Unknown)));
+    assert_eq!(
+        program.evaluate(&Environment::empty()).unwrap().to_string(),
+        "{ x=1 }"
+    );
+}
+
+/// Outside a loop there is nothing to jump to, which the checker catches before running.
+#[test]
+fn jumps_outside_loops() {
+    for source in [
+        "x = 1; if (x > 0) { break } else { continue }",
+        "def stop() { break; return 0 }; while (true) { x = stop() }",
+    ] {
+        let program = parse_statement(source).unwrap();
+        let errors = types::check(&program, &TypeContext::empty()).unwrap_err();
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error, TypeError::OutsideLoop(_))));
+
+        let error = program.evaluate(&Environment::empty()).unwrap_err();
+        assert!(matches!(error, EvalError::OutsideLoop(_)));
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Err(error.clone())
+        );
+        assert_eq!(Machine::new(program).trace(), Err(error));
+    }
+}
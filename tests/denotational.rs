@@ -0,0 +1,66 @@
+use uc::ast::*;
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::{Expression as _, Statement as _};
+use uc::parser::{parse_expression, parse_statement};
+use uc::{Environment, EvalError, Value::Number};
+
+/// A closure gives the same meaning as big-step evaluation.
+#[test]
+fn closures_agree_with_big_step() {
+    let environment = Environment::from([("x", Number(3)), ("y", Number(-4))]);
+    for source in ["x * 2 + y", "x < y || !(x == 3)", "-(x - y) % 5"] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(
+            expression.to_closure()(&environment),
+            expression.evaluate(&environment)
+        );
+    }
+    for source in [
+        "z = x; while (z < 100) { z = z * 2 }",
+        "if (x > y) { max = x } else { max = y }; x = y",
+        "n = 0; total = 0; while (n < x) { n = n + 1; total = total + n * y }",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(
+            program.to_closure()(&environment),
+            program.evaluate(&environment)
+        );
+    }
+}
+
+/// A program is translated once, and its closure run in as many environments as we like.
+#[test]
+fn closures_run_again() {
+    let program: Stmt = Sequence::new(
+        Assign::new("total", Number(0)),
+        While::new(
+            LessThan::new(Number(0), Variable::new("n")),
+            Sequence::new(
+                Assign::new(
+                    "total",
+                    Add::new(Variable::new("total"), Variable::new("n")),
+                ),
+                Assign::new("n", Add::new(Variable::new("n"), Number(-1))),
+            ),
+        ),
+    )
+    .into();
+    let run = program.to_closure();
+    drop(program);
+    for n in [0, 1, 10, 30_000] {
+        let environment = run(&Environment::from([("n", Number(n))])).unwrap();
+        assert_eq!(environment.get("total"), Some(&Number(n * (n + 1) / 2)));
+        assert_eq!(environment.get("n"), Some(&Number(0)));
+    }
+}
+
+#[test]
+fn closures_report_errors() {
+    let meaning = parse_statement("x = 1; y = x + z").unwrap().to_closure();
+    assert_eq!(
+        meaning(&Environment::empty()),
+        Err(EvalError::UnboundVariable(String::from("z")))
+    );
+    let environment = meaning(&Environment::from([("z", Number(2))])).unwrap();
+    assert_eq!(environment.get("y"), Some(&Number(3)));
+}
@@ -0,0 +1,105 @@
+use uc::{Environment, Value::Number};
+
+#[test]
+fn lookup() {
+    let environment = Environment::from([("x", Number(1)), ("y", Number(2))]);
+    assert_eq!(environment.get("x"), Some(&Number(1)));
+    assert!(environment.contains("y"));
+    assert!(!environment.contains("z"));
+    assert_eq!(environment.get("z"), None);
+    // Updating returns a new environment, and leaves the old one as it was.
+    let updated = environment.update("x", Number(10)).update("z", Number(3));
+    assert_eq!(updated.get("x"), Some(&Number(10)));
+    assert!(updated.contains("z"));
+    assert_eq!(environment.get("x"), Some(&Number(1)));
+    assert!(!environment.contains("z"));
+}
+
+#[test]
+fn remove() {
+    let environment = Environment::from([("x", Number(1)), ("y", Number(2))]);
+    let removed = environment.remove("x");
+    assert!(!removed.contains("x"));
+    assert_eq!(removed.get("y"), Some(&Number(2)));
+    assert!(environment.contains("x"));
+    assert_eq!(removed.remove("x"), removed);
+    // Removing a variable declared by a scope uncovers the one it hid.
+    let scoped = environment.enter_scope("x", Number(10));
+    assert_eq!(scoped.remove("x").get("x"), Some(&Number(1)));
+    assert!(!scoped.remove("x").remove("x").contains("x"));
+}
+
+#[test]
+fn scopes() {
+    let outer = Environment::from([("x", Number(1)), ("y", Number(2))]);
+    assert_eq!(outer.exit_scope(), None);
+    let inner = outer.enter_scope("x", Number(10));
+    assert_eq!(inner.get("x"), Some(&Number(10)));
+    // Updates go to the scope declaring the variable, or else to the globals.
+    let inner = inner.update("x", Number(11)).update("y", Number(20));
+    assert_eq!(inner.get("x"), Some(&Number(11)));
+    let exited = inner.exit_scope().unwrap();
+    assert_eq!(exited.get("x"), Some(&Number(1)));
+    assert_eq!(exited.get("y"), Some(&Number(20)));
+    assert_eq!(exited, outer.update("y", Number(20)));
+    // Scopes nest, each hiding the one before until it is exited.
+    let nested = outer
+        .enter_scope("x", Number(10))
+        .enter_scope("x", Number(100));
+    assert_eq!(nested.get("x"), Some(&Number(100)));
+    let once = nested.exit_scope().unwrap();
+    assert_eq!(once.get("x"), Some(&Number(10)));
+    assert_eq!(once.exit_scope().unwrap().get("x"), Some(&Number(1)));
+}
+
+/// Iteration visits each visible variable once, in order of name, whichever scope it is in.
+/// Display shows every scope, hidden variables included, outermost first.
+#[test]
+fn ordering() {
+    let environment = Environment::from([("c", Number(3)), ("a", Number(1))])
+        .enter_scope("b", Number(2))
+        .enter_scope("a", Number(10))
+        .update("d", Number(4));
+    let visible: Vec<(&str, _)> = environment.iter().collect();
+    assert_eq!(
+        visible,
+        vec![
+            ("a", &Number(10)),
+            ("b", &Number(2)),
+            ("c", &Number(3)),
+            ("d", &Number(4)),
+        ]
+    );
+    assert_eq!(environment.to_string(), "{ a=1, c=3, d=4 | b=2 | a=10 }");
+    assert_eq!(Environment::empty().to_string(), "{  }");
+    assert_eq!(Environment::empty().iter().count(), 0);
+}
+
+/// Collecting bindings updates an empty environment with each in turn, so the last binding of a
+/// name wins.
+#[test]
+fn construction() {
+    let collected: Environment = vec![
+        (String::from("x"), Number(1)),
+        (String::from("y"), Number(2)),
+        (String::from("x"), Number(3)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        collected,
+        Environment::empty()
+            .update("y", Number(2))
+            .update("x", Number(3))
+    );
+    assert_eq!(
+        Environment::from([("x", Number(1)), ("y", Number(2)), ("x", Number(3))]),
+        collected
+    );
+    assert_eq!(
+        Environment::from([(String::from("y"), Number(2))]),
+        Environment::empty().update("y", Number(2))
+    );
+    let none: [(&str, _); 0] = [];
+    assert_eq!(Environment::from(none), Environment::empty());
+}
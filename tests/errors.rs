@@ -0,0 +1,88 @@
+use uc::ast::*;
+use uc::big_step::{Expression as _, Statement as _};
+use uc::small_step::{Expression as _, Machine, Statement as _};
+use uc::{
+    Environment, EvalError,
+    Value::{Boolean, Number},
+};
+
+/// A program which goes wrong stops with an error, the same one whichever way it is run.
+#[test]
+fn every_semantics_reports_errors() {
+    for (program, error) in [
+        (
+            Stmt::from(Assign::new("x", Add::new(Variable::new("y"), Number(1)))),
+            EvalError::UnboundVariable(String::from("y")),
+        ),
+        (
+            Assign::new("x", Add::new(Boolean(true), Number(1))).into(),
+            EvalError::TypeMismatch(Boolean(true)),
+        ),
+        (
+            If::new(Number(1), Assign::new("x", Number(1)), DoNothing).into(),
+            EvalError::NonBooleanCondition(Number(1)),
+        ),
+        (
+            While::new(Number(0), DoNothing).into(),
+            EvalError::NonBooleanCondition(Number(0)),
+        ),
+    ] {
+        assert_eq!(program.evaluate(&Environment::empty()), Err(error.clone()));
+        assert_eq!(
+            Machine::new(program)
+                .last()
+                .unwrap()
+                .map(|step| step.environment),
+            Err(error)
+        );
+    }
+}
+
+#[test]
+fn expressions_report_errors() {
+    let environment = Environment::from([("x", Number(1))]);
+    let unbound: Expr = LessThan::new(Variable::new("x"), Variable::new("z")).into();
+    let error = EvalError::UnboundVariable(String::from("z"));
+    assert_eq!(unbound.evaluate(&environment), Err(error.clone()));
+    let reduced = unbound.reduce(&environment).unwrap();
+    assert_eq!(reduced, LessThan::new(Number(1), Variable::new("z")).into());
+    assert_eq!(reduced.reduce(&environment), Err(error));
+}
+
+/// A term which is already as reduced as it can be is refused, rather than reduced again.
+#[test]
+fn irreducible() {
+    let value = Expr::from(Number(3));
+    assert_eq!(
+        value.reduce(&Environment::empty()),
+        Err(EvalError::Irreducible(String::from("«3»")))
+    );
+    assert!(matches!(
+        Stmt::from(DoNothing).reduce(&Environment::empty()),
+        Err(EvalError::Irreducible(_))
+    ));
+}
+
+#[test]
+fn messages() {
+    for (error, message) in [
+        (
+            EvalError::UnboundVariable(String::from("y")),
+            "unbound variable `y`",
+        ),
+        (
+            EvalError::TypeMismatch(Boolean(true)),
+            "unexpected value «true»",
+        ),
+        (
+            EvalError::NonBooleanCondition(Number(1)),
+            "condition must be boolean, got «1»",
+        ),
+        (
+            EvalError::Irreducible(String::from("«3»")),
+            "cannot reduce «3»",
+        ),
+    ] {
+        assert_eq!(error.to_string(), message);
+    }
+}
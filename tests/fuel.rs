@@ -0,0 +1,83 @@
+use uc::big_step::Evaluator;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::{Environment, Outcome, Value};
+
+#[test]
+fn machine_gives_up() {
+    let forever = parse_statement("x = 0; while (true) { x = x + 1 }").unwrap();
+    let outcome = Machine::new(forever.clone())
+        .finish_with_limit(100_000)
+        .unwrap();
+    match &outcome {
+        Outcome::OutOfFuel { steps, .. } => assert_eq!(*steps, 100_000),
+        Outcome::Finished(_) => panic!("an endless loop finished"),
+    }
+    assert_eq!(
+        Machine::new(forever).run_with_limit(100_000).unwrap(),
+        outcome
+    );
+}
+
+#[test]
+fn machine_finishes_within_its_limit() {
+    let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }").unwrap();
+    match Machine::new(program).finish_with_limit(1_000).unwrap() {
+        Outcome::Finished(environment) => assert_eq!(environment.get("x"), Some(&Value::Number(6))),
+        outcome => panic!("ran out of fuel: {:?}", outcome),
+    }
+}
+
+#[test]
+fn evaluator_gives_up() {
+    let forever = parse_statement("x = 0; while (true) { x = x + 1 }").unwrap();
+    let outcome = Evaluator::new()
+        .with_fuel(1_000)
+        .evaluate(&forever, &Environment::empty())
+        .unwrap();
+    assert!(matches!(outcome, Outcome::OutOfFuel { .. }));
+}
+
+/// Statements run inside a call are paid for from the same fuel as the rest of the program.
+#[test]
+fn calls_burn_fuel() {
+    let program = parse_statement("def f() { while (true) { x = 1 }; return 1 }; y = f()").unwrap();
+    match Evaluator::new()
+        .with_fuel(100)
+        .evaluate(&program, &Environment::empty())
+        .unwrap()
+    {
+        Outcome::OutOfFuel { steps, environment } => {
+            assert_eq!(steps, 100);
+            assert!(environment.get("f").is_some());
+            assert_eq!(environment.get("x"), None);
+        }
+        Outcome::Finished(_) => panic!("an endless call finished"),
+    }
+    let outcome = Machine::new(program).finish_with_limit(100).unwrap();
+    assert!(matches!(outcome, Outcome::OutOfFuel { steps: 100, .. }));
+}
+
+/// Each evaluation starts with the whole budget, and stops exactly when it is spent.
+#[test]
+fn evaluator_budget() {
+    let mut evaluator = Evaluator::new().with_fuel(1_000);
+    let forever = parse_statement("x = 0; while (true) { x = x + 1 }").unwrap();
+    for _ in 0..2 {
+        match evaluator.evaluate(&forever, &Environment::empty()).unwrap() {
+            Outcome::OutOfFuel { steps, environment } => {
+                assert_eq!(steps, 1_000);
+                assert!(matches!(environment.get("x"), Some(Value::Number(n)) if *n > 0));
+            }
+            Outcome::Finished(_) => panic!("an endless loop finished"),
+        }
+    }
+    let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }").unwrap();
+    assert_eq!(
+        evaluator.evaluate(&program, &Environment::empty()),
+        Ok(Outcome::Finished(Environment::from([(
+            "x",
+            Value::Number(6)
+        )])))
+    );
+}
@@ -0,0 +1,298 @@
+use uc::big_step::{Evaluator, Statement as _};
+use uc::denotational::Statement as _;
+use uc::parser::{parse_statement, parse_trace};
+use uc::small_step::Machine;
+use uc::types::{self, Type, TypeContext};
+use uc::{
+    Environment, EvalError, Printable, Runtime, Value, DEFAULT_CALL_DEPTH, NATIVE_CALL_DEPTH,
+};
+
+#[test]
+fn every_semantics_agrees() {
+    let programs = [
+        (
+            "def fact(n) { if (n < 2) { r = 1 } else { r = n * fact(n - 1) }; return r }; \
+             x = fact(20)",
+            "x",
+            Value::Number(2_432_902_008_176_640_000),
+        ),
+        (
+            "def fib(n) { if (n < 2) { r = n } else { r = fib(n - 1) + fib(n - 2) }; return r }; \
+             x = fib(15)",
+            "x",
+            Value::Number(610),
+        ),
+        // Each function can call the others, whichever was defined first.
+        (
+            "def even(n) { if (n == 0) { r = true } else { r = odd(n - 1) }; return r }; \
+             def odd(n) { if (n == 0) { r = false } else { r = even(n - 1) }; return r }; \
+             x = even(10) && odd(7)",
+            "x",
+            Value::Boolean(true),
+        ),
+        (
+            "def sum(xs) { i = 0; total = 0; while (i < xs.length) { total = total + xs[i]; \
+             i = i + 1 }; return total }; def square(x) { return x * x }; \
+             x = sum([square(1), square(2), square(3)])",
+            "x",
+            Value::Number(14),
+        ),
+        // A call's variables stay inside it, including ones named like the caller's.
+        (
+            "x = 1; def set(y) { x = y * 2; return x }; z = set(5) + x",
+            "x",
+            Value::Number(1),
+        ),
+    ];
+    for (source, variable, expected) in programs {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+        let context = types::check(&program, &TypeContext::empty()).unwrap();
+        assert_eq!(context.get(variable), Some(Type::of(&expected)));
+
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        assert_eq!(big_step.get(variable), Some(&expected));
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Ok(big_step.clone())
+        );
+        let small_step = Machine::new(program).last().unwrap().unwrap().environment;
+        assert_eq!(small_step, big_step);
+    }
+}
+
+/// The trace shows each call as a frame holding its own variables, nested in the caller's.
+#[test]
+fn trace_shows_frames() {
+    let program = parse_statement(
+        "def double(n) { m = n * 2; return m }; def quadruple(n) { return double(double(n)) }; \
+         x = quadruple(5)",
+    )
+    .unwrap();
+    let trace = Machine::new(program).trace().unwrap();
+    for frame in [
+        "x = quadruple(n=5) { return double(double(n=5) { m = n * 2; return m }) }",
+        "x = quadruple(n=5) { return double(double(m=10, n=5) { return m }) }",
+        "x = quadruple(n=5) { return double(m=20, n=10) { return m } }",
+    ] {
+        assert!(trace.iter().any(|step| step.statement.to_s() == frame));
+        assert_eq!(parse_trace(frame).unwrap().to_s(), frame);
+    }
+    let environment = &trace.last().unwrap().environment;
+    assert_eq!(environment.get("x"), Some(&Value::Number(20)));
+    assert!(!environment.contains("m") && !environment.contains("n"));
+}
+
+#[test]
+fn failed_calls() {
+    for (source, error) in [
+        (
+            "def f(a, b) { return a + b }; x = f(1)",
+            EvalError::ArgumentCount {
+                function: String::from("f"),
+                expected: 2,
+                found: 1,
+            },
+        ),
+        ("y = 3; x = y(1)", EvalError::TypeMismatch(Value::Number(3))),
+        (
+            "y = 3; def f() { return y }; x = f()",
+            EvalError::UnboundVariable(String::from("y")),
+        ),
+        // Runaway recursion is stopped before it overflows the native stack.
+        (
+            "def f(n) { return f(n + 1) }; y = f(0)",
+            EvalError::CallDepth(String::from("f")),
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(program.evaluate(&Environment::empty()), Err(error.clone()));
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Err(error.clone())
+        );
+        assert_eq!(
+            Machine::new(program)
+                .last()
+                .unwrap()
+                .map(|step| step.environment),
+            Err(error)
+        );
+    }
+}
+
+/// As many calls may be unfinished at once as the `Runtime` allows, and no more. The semantics
+/// which run calls on the native stack stop at `NATIVE_CALL_DEPTH` however much more it allows.
+#[test]
+fn call_depth() {
+    let down = |depth: usize| {
+        let source = format!(
+            "def down(n) {{ if (n > 0) {{ r = down(n - 1) }} else {{ r = 0 }}; return r }}; \
+             x = down({})",
+            depth
+        );
+        parse_statement(&source).unwrap()
+    };
+    let expected = Ok(Some(Value::Number(0)));
+    let error = Err(EvalError::CallDepth(String::from("down")));
+    let get = |environment: Environment| environment.get("x").cloned();
+    // `down(n)` has `n + 1` calls unfinished at its deepest.
+    assert_eq!(
+        down(DEFAULT_CALL_DEPTH - 1)
+            .evaluate(&Environment::empty())
+            .map(get),
+        expected
+    );
+    assert_eq!(
+        down(DEFAULT_CALL_DEPTH).evaluate(&Environment::empty()),
+        error
+    );
+
+    let mut runtime = Runtime::default();
+    runtime.max_call_depth = 20;
+    assert_eq!(
+        down(19)
+            .evaluate_with(&Environment::empty(), &mut runtime)
+            .map(get),
+        expected
+    );
+    assert_eq!(
+        down(20).evaluate_with(&Environment::empty(), &mut runtime),
+        error
+    );
+    assert_eq!(
+        Evaluator::new()
+            .with_max_call_depth(20)
+            .evaluate(&down(20), &Environment::empty())
+            .map(|_| ()),
+        Err(EvalError::CallDepth(String::from("down")))
+    );
+    assert_eq!(
+        Machine::new(down(20))
+            .with_max_call_depth(20)
+            .last()
+            .unwrap()
+            .map(|step| step.environment),
+        error
+    );
+
+    let deepest = down(NATIVE_CALL_DEPTH - 1);
+    assert_eq!(
+        deepest.to_closure()(&Environment::empty()).map(get),
+        expected
+    );
+    assert_eq!(
+        Machine::new(deepest)
+            .last()
+            .unwrap()
+            .map(|step| get(step.environment)),
+        expected
+    );
+    let too_deep = down(NATIVE_CALL_DEPTH);
+    assert_eq!(too_deep.to_closure()(&Environment::empty()), error);
+    assert_eq!(
+        Machine::new(too_deep)
+            .last()
+            .unwrap()
+            .map(|step| step.environment),
+        error
+    );
+}
+
+/// Big-step evaluation keeps calls off the native stack, so deep recursion needs no more of it.
+#[test]
+fn deep_recursion() {
+    let program = parse_statement(
+        "def count(n) { if (n == 0) { r = 0 } else { r = 1 + count(n - 1) }; return r }; \
+         def fib(n) { if (n < 2) { r = n } else { r = fib(n - 1) + fib(n - 2) }; return r }; \
+         x = count(5000); y = fib(25)",
+    )
+    .unwrap();
+    let environment = program.evaluate(&Environment::empty()).unwrap();
+    assert_eq!(environment.get("x"), Some(&Value::Number(5000)));
+    assert_eq!(environment.get("y"), Some(&Value::Number(75_025)));
+}
+
+#[test]
+fn ill_typed_calls() {
+    for source in [
+        "def f(a, b) { return a + b }; x = f(1)",
+        "y = 3; x = y(1)",
+        "def length(s) { return s.length }; x = length(3)",
+        "def f(n) { return n }; x = f(1) + f(true)",
+        "def f() { return 1 }; f = 2",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert!(types::check(&program, &TypeContext::empty()).is_err());
+    }
+}
+
+#[test]
+fn malformed_definitions() {
+    for source in ["def f(n) { n = 1 }", "def f(1) { return 1 }", "x = f(1, )"] {
+        assert!(parse_statement(source).is_err());
+    }
+}
+
+/// A closure can call a function it never saw defined, such as one left behind by another run.
+#[test]
+fn functions_from_elsewhere() {
+    let definition = parse_statement("def triple(n) { return n * 3 }").unwrap();
+    let environment = definition.to_closure()(&Environment::empty()).unwrap();
+    drop(definition);
+    let program = parse_statement("x = triple(2); y = triple(x)").unwrap();
+    let environment = program.to_closure()(&environment).unwrap();
+    assert_eq!(environment.get("y"), Some(&Value::Number(18)));
+}
+
+/// A call runs whichever function its name finds each time, even where one definition replaces
+/// another, or is freed and a new one made in its place.
+#[test]
+fn calls_follow_redefinitions() {
+    let program = parse_statement(
+        "def apply(n) { return f(n) }; i = 0; xs = []; while (i < 4) { \
+         if (i % 2 == 0) { def f(n) { return n + 1 } } else { def f(n) { return n * 10 } }; \
+         xs = xs.append(apply(i)); i = i + 1 }",
+    )
+    .unwrap();
+    let expected = Some(Value::List(
+        vec![1, 10, 3, 30].into_iter().map(Value::Number).collect(),
+    ));
+    let meaning = program.to_closure();
+    for _ in 0..2 {
+        let environment = meaning(&Environment::empty()).unwrap();
+        assert_eq!(environment.get("xs").cloned(), expected);
+    }
+    let definition = parse_statement("def f(n) { return -n }").unwrap();
+    let call = parse_statement("x = f(5)").unwrap().to_closure();
+    for _ in 0..2 {
+        let environment = definition.to_closure()(&Environment::empty()).unwrap();
+        assert_eq!(
+            call(&environment).unwrap().get("x"),
+            Some(&Value::Number(-5))
+        );
+    }
+}
+
+/// Checking calls nested deeper than `NATIVE_CALL_DEPTH` gives up on their types rather than
+/// overflowing the native stack.
+#[test]
+fn deep_calls_check() {
+    let functions: Vec<String> = (0..1000)
+        .map(|i| format!("def f{}(n) {{ return f{}(n + 1) }}", i, i + 1))
+        .collect();
+    let program = parse_statement(&format!(
+        "{}; def f1000(n) {{ return n }}; x = f0(0); y = x + 1",
+        functions.join("; ")
+    ))
+    .unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("x"), None);
+    assert!(context.get("f0").is_some());
+    let short = parse_statement(
+        "def f0(n) { return f1(n + 1) }; def f1(n) { return n }; x = f0(0); y = x + 1",
+    )
+    .unwrap();
+    let context = types::check(&short, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("x"), Some(Type::Number));
+}
@@ -0,0 +1,162 @@
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::{Expression as _, Statement as _};
+use uc::parser::{parse_expression, parse_statement};
+use uc::signs::{AbstractEnvironment, AbstractValue, Statement as _};
+use uc::small_step::{Expression as _, Machine};
+use uc::types::{self, Type, TypeContext, Typed};
+use uc::{Environment, EvalError, Printable, Value};
+
+fn list(numbers: &[i64]) -> Value {
+    Value::List(numbers.iter().copied().map(Value::Number).collect())
+}
+
+#[test]
+fn every_semantics_agrees() {
+    let environment = Environment::from([("xs", list(&[3, 1, 2]))]);
+    let context = TypeContext::from(&environment);
+    for (source, expected) in [
+        ("xs.length", Value::Number(3)),
+        ("xs[0] + xs[2]", Value::Number(5)),
+        ("xs[1:3]", list(&[1, 2])),
+        ("xs[2:1].length", Value::Number(0)),
+        ("xs.append(xs.length)", list(&[3, 1, 2, 3])),
+        ("[xs[2], xs[1] * 10, -xs[0]]", list(&[2, 10, -3])),
+        ("[[1], []][0].append(2)", list(&[1, 2])),
+        ("[].length == 0", Value::Boolean(true)),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.to_s(), source);
+        assert_eq!(expression.evaluate(&environment), Ok(expected.clone()));
+        assert_eq!(expression.to_closure()(&environment), Ok(expected.clone()));
+        let mut reduced = expression.clone();
+        while reduced.is_reducible() {
+            reduced = reduced.reduce(&environment).unwrap();
+        }
+        assert_eq!(reduced.as_value(), Some(&expected));
+        assert_eq!(expression.type_of(&context), Ok(Type::of(&expected)));
+    }
+}
+
+/// Out of range is an error, not a panic.
+#[test]
+fn out_of_range() {
+    let environment = Environment::from([("xs", list(&[3, 1, 2]))]);
+    for (source, index, length) in [("xs[3]", 3, 3), ("xs[-1]", -1, 3), ("[][0]", 0, 0)] {
+        assert_eq!(
+            parse_expression(source).unwrap().evaluate(&environment),
+            Err(EvalError::IndexOutOfRange {
+                index: Value::Number(index),
+                length,
+            })
+        );
+    }
+    let assignment = parse_statement("xs = [3, 1, 2]; xs[3] = 0").unwrap();
+    let error = assignment.evaluate(&Environment::empty()).unwrap_err();
+    assert_eq!(
+        assignment.to_closure()(&Environment::empty()),
+        Err(error.clone())
+    );
+    assert_eq!(
+        Machine::new(assignment)
+            .last()
+            .unwrap()
+            .map(|step| step.environment),
+        Err(error)
+    );
+}
+
+/// `.append` makes a new list; only assignment changes a variable.
+#[test]
+fn append_copies() {
+    let environment = parse_statement("xs = [1]; ys = xs.append(2); xs[0] = 5")
+        .unwrap()
+        .evaluate(&Environment::empty())
+        .unwrap();
+    assert_eq!(environment.get("xs"), Some(&list(&[5])));
+    assert_eq!(environment.get("ys"), Some(&list(&[1, 2])));
+}
+
+#[test]
+fn programs() {
+    let programs = [
+        // Insertion sort.
+        (
+            "xs = [5, 2, 9, 1, 5, 6]; i = 1; \
+             while (i < xs.length) { \
+             key = xs[i]; j = i - 1; \
+             while (j >= 0 && xs[j] > key) { xs[j + 1] = xs[j]; j = j - 1 }; \
+             xs[j + 1] = key; i = i + 1 }",
+            "xs",
+            list(&[1, 2, 5, 5, 6, 9]),
+        ),
+        // Binary search.
+        (
+            "xs = [1, 3, 5, 7, 9, 11]; target = 7; lo = 0; hi = xs.length; found = -1; \
+             while (lo < hi) { mid = (lo + hi) / 2; \
+             if (xs[mid] == target) { found = mid; lo = hi } \
+             else { if (xs[mid] < target) { lo = mid + 1 } else { hi = mid } } }",
+            "found",
+            Value::Number(3),
+        ),
+        (
+            "squares = []; n = 0; while (n < 5) { squares = squares.append(n * n); n = n + 1 }",
+            "squares",
+            list(&[0, 1, 4, 9, 16]),
+        ),
+    ];
+    for (source, variable, expected) in &programs {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+        let context = types::check(&program, &TypeContext::empty()).unwrap();
+        assert_eq!(context.get(variable), Some(Type::of(expected)));
+
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        assert_eq!(big_step.get(variable), Some(expected));
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Ok(big_step.clone())
+        );
+        let small_step = Machine::new(program).last().unwrap().unwrap().environment;
+        assert_eq!(small_step, big_step);
+    }
+
+    let program = parse_statement(programs[2].0).unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(
+        context.get("squares").map(|ty| ty.to_string()),
+        Some(String::from("list of number"))
+    );
+    let program = parse_statement(programs[0].0).unwrap();
+    assert_eq!(
+        program
+            .abstract_evaluate(&AbstractEnvironment::empty())
+            .get("xs"),
+        Some(AbstractValue::List)
+    );
+}
+
+#[test]
+fn ill_typed() {
+    for source in [
+        "xs = [1, true]",
+        "xs = [1]; xs[0] = \"one\"",
+        "n = 1; n[0] = 2",
+        "s = \"abc\"; t = s.append(\"d\")",
+        "xs = [[1]]; ys = xs[0] + 1",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert!(types::check(&program, &TypeContext::empty()).is_err());
+    }
+    let error = parse_statement("n = 1; n[0] = 2")
+        .unwrap()
+        .evaluate(&Environment::empty())
+        .unwrap_err();
+    assert_eq!(error, EvalError::TypeMismatch(Value::Number(1)));
+}
+
+#[test]
+fn malformed() {
+    for source in ["xs = [1, 2", "xs = [1 2]", "xs = [].push(1)"] {
+        assert!(parse_statement(source).is_err());
+    }
+}
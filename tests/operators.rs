@@ -0,0 +1,94 @@
+use uc::ast::Expr;
+use uc::big_step::Expression as _;
+use uc::denotational::Expression as _;
+use uc::parser::parse_expression;
+use uc::small_step::Expression;
+use uc::types::{Type, TypeContext, Typed};
+use uc::{Environment, EvalError, Printable, Value, Value::*};
+
+/// Reduces an expression all the way with the small-step semantics.
+fn reduce(mut expression: Expr, environment: &Environment) -> Result<Value, EvalError> {
+    while expression.is_reducible() {
+        expression = expression.reduce(environment)?;
+    }
+    Ok(expression.as_value().cloned().unwrap())
+}
+
+fn environment() -> Environment {
+    Environment::from([("x", Number(7)), ("y", Number(-2)), ("t", Boolean(true))])
+}
+
+#[test]
+fn every_semantics_agrees() {
+    let environment = environment();
+    let context = TypeContext::from(&environment);
+    for (source, expected) in [
+        ("x - y - 1", Number(8)),
+        ("x / y", Number(-3)),
+        ("x % y", Number(1)),
+        ("-x % 3", Number(-1)),
+        ("-(x + y) * 2", Number(-10)),
+        ("x == 7 && y != 7", Boolean(true)),
+        ("x > y == y < x", Boolean(true)),
+        ("x <= 7 && x >= 8", Boolean(false)),
+        ("!t || x / y < -3", Boolean(false)),
+        ("t == !false", Boolean(true)),
+        // The right operand of `&&` and `||` is skipped once the left settles the answer.
+        ("false && x / 0 == 1", Boolean(false)),
+        ("t || x % 0 == 1", Boolean(true)),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.to_s(), source);
+        assert_eq!(parse_expression(&expression.to_s()), Ok(expression.clone()));
+        assert_eq!(expression.evaluate(&environment), Ok(expected.clone()));
+        assert_eq!(expression.to_closure()(&environment), Ok(expected.clone()));
+        assert_eq!(
+            reduce(expression.clone(), &environment),
+            Ok(expected.clone())
+        );
+        assert_eq!(expression.type_of(&context), Ok(Type::of(&expected)));
+    }
+}
+
+#[test]
+fn division_by_zero() {
+    let environment = environment();
+    for source in ["x / (y + 2)", "x % 0", "t && x / 0 == 1"] {
+        let expression = parse_expression(source).unwrap();
+        let error = Err(EvalError::DivisionByZero);
+        assert_eq!(expression.evaluate(&environment), error);
+        assert_eq!(expression.to_closure()(&environment), error);
+        assert_eq!(reduce(expression, &environment), error);
+    }
+}
+
+/// `-` is a negative literal straight before a digit, unless it follows an operand.
+#[test]
+fn minus_signs() {
+    assert_eq!(parse_expression("-3"), Ok(Expr::from(Number(-3))));
+    for (source, printed) in [
+        ("x-3", "x - 3"),
+        ("x - -3", "x - -3"),
+        ("- 3", "-(3)"),
+        ("--3", "-(-3)"),
+        ("--x", "--x"),
+    ] {
+        assert_eq!(parse_expression(source).unwrap().to_s(), printed);
+    }
+    assert_eq!(
+        parse_expression("-(3)").unwrap().evaluate(&environment()),
+        Ok(Number(-3))
+    );
+    assert_eq!(
+        parse_expression("-9223372036854775808"),
+        Ok(Expr::from(Number(i64::MIN)))
+    );
+}
+
+#[test]
+fn ill_typed() {
+    let context = TypeContext::from(&environment());
+    for source in ["x == t", "-t", "!x", "x && t"] {
+        assert!(parse_expression(source).unwrap().type_of(&context).is_err());
+    }
+}
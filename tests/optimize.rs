@@ -0,0 +1,154 @@
+use uc::big_step::Evaluator;
+use uc::denotational::Statement as _;
+use uc::optimize::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::{Environment, Outcome, Output, Overflow, Printable};
+
+fn finished(outcome: Outcome) -> Environment {
+    match outcome {
+        Outcome::Finished(environment) => environment,
+        Outcome::OutOfFuel { .. } => unreachable!("evaluation without a limit ran out of fuel"),
+    }
+}
+
+#[test]
+fn same_result_in_fewer_steps() {
+    for (source, expected) in [
+        (
+            "x = 3 * 4; y = x * 1 + 0; z = 0 + y * (2 - 1); w = z * 0",
+            "x = 12; y = x; z = y; w = 0",
+        ),
+        (
+            "n = 0; while (n < 2 * 5) { if (1 > 2 && n > 0) { print n } else { do-nothing }; \
+             n = n + 1 * 1 }",
+            "n = 0; while (n < 10) { n = n + 1 }",
+        ),
+        (
+            "xs = [1 + 1, 2 * 3].append(4 - 0); first = xs[0] * 1; l = \"hello\"[1:3].length",
+            "xs = [2, 6, 4]; first = xs[0] * 1; l = 2",
+        ),
+        // Function bodies are left alone, and a call is never left out, even when multiplied by
+        // zero.
+        (
+            "def sq(n) { return n * n * 1 }; if (2 > 1) { a = sq(3 + 0) } else { a = 0 }; \
+             b = sq(a) * 0",
+            "def sq(n) { return n * n * 1 }; a = sq(3); b = sq(a) * 0",
+        ),
+        (
+            "for (i = 0 * 5; i < 3; i += 1 * 1) { print i * 1 + 0 }; \
+             unless (1 == 1) { print \"never\" }",
+            "for (i = 0; i < 3; i += 1) { print i * 1 + 0 }; unless (true) { print \"never\" }",
+        ),
+        (
+            "let (k = 2 * 2) { while (true) { k += 0 + 1; \
+             if (k > 3 + 3) { break } else { continue } } }; done = !false",
+            "let (k = 4) { while (true) { k += 1; if (k > 6) { break } else { continue } } }; \
+             done = true",
+        ),
+        // Identities apply to variables sure to hold numbers, and `true && e` only to an `e` sure
+        // to be a boolean.
+        (
+            "x = 5; y = x * 0 + 3; b = true && y > 2; c = false || !b",
+            "x = 5; y = 3; b = y > 2; c = !b",
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        let optimized = program.optimize();
+        assert_eq!(optimized.to_s(), expected);
+        assert_eq!(optimized.optimize(), optimized);
+
+        let mut before = Evaluator::new().with_output(Output::buffer());
+        let mut after = Evaluator::new().with_output(Output::buffer());
+        let environment = finished(before.evaluate(&program, &Environment::empty()).unwrap());
+        assert_eq!(
+            finished(after.evaluate(&optimized, &Environment::empty()).unwrap()),
+            environment
+        );
+        assert_eq!(before.output().lines(), after.output().lines());
+        assert_eq!(
+            optimized.to_closure()(&Environment::empty()),
+            Ok(environment.clone())
+        );
+
+        let mut unoptimized = Machine::new(program).with_output(Output::buffer());
+        let steps = unoptimized.trace().unwrap().len();
+        let mut machine = Machine::new(optimized).with_output(Output::buffer());
+        let trace = machine.trace().unwrap();
+        assert_eq!(trace.last().unwrap().environment, environment);
+        assert_eq!(machine.output().lines(), unoptimized.output().lines());
+        assert!(trace.len() < steps);
+    }
+}
+
+/// Anything which would fail is left for the run to report, so it fails in the same way.
+#[test]
+fn failures_are_kept() {
+    for (source, expected) in [
+        ("x = 10 / (5 - 5)", "x = 10 / 0"),
+        ("x = [1, 2][1 + 1]", "x = [1, 2][2]"),
+        (
+            "if (1 + 1) { y = 1 } else { y = 2 }",
+            "if (2) { y = 1 } else { y = 2 }",
+        ),
+        ("y = (10 / 0) * 0", "y = 10 / 0 * 0"),
+        ("s = \"a\"; t = s + 0 * 1", "s = \"a\"; t = s + 0"),
+        ("x = true && 5", "x = true && 5"),
+        (
+            "n = 1; i = 0; while (i < 1) { n = [n]; i = i + 1 }; m = n * 1",
+            "n = 1; i = 0; while (i < 1) { n = [n]; i = i + 1 }; m = n * 1",
+        ),
+        (
+            "n = 1; if (n > 0) { n = \"one\" } else { do-nothing }; m = n - 0",
+            "n = 1; if (n > 0) { n = \"one\" } else { do-nothing }; m = n - 0",
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        let optimized = program.optimize();
+        assert_eq!(optimized.to_s(), expected);
+        let error = Evaluator::new()
+            .evaluate(&program, &Environment::empty())
+            .unwrap_err();
+        let mut machine = Machine::new(optimized);
+        assert_eq!(machine.trace().unwrap_err(), error);
+    }
+}
+
+/// What overflow does is up to the run, so arithmetic that overflows is not folded.
+#[test]
+fn overflow_is_left_to_the_run() {
+    let program = parse_statement("big = 9223372036854775807 + 2 * 1; small = -big").unwrap();
+    let optimized = program.optimize();
+    assert_eq!(
+        optimized.to_s(),
+        "big = 9223372036854775807 + 2; small = -big"
+    );
+    for overflow in [
+        Overflow::Promote,
+        Overflow::Wrapping,
+        Overflow::Saturating,
+        Overflow::Checked,
+    ] {
+        let run = |program| {
+            Machine::new(program)
+                .with_overflow(overflow)
+                .last()
+                .unwrap()
+                .map(|step| step.environment)
+        };
+        assert_eq!(run(optimized.clone()), run(program.clone()));
+    }
+}
+
+/// A long program, and a loop with a long body, are optimised without using up the stack.
+#[test]
+fn long_program() {
+    let body = |step: &str| {
+        format!(
+            "x = 0; while (x < 1) {{ x = x + 1{} }}",
+            step.repeat(20_000)
+        )
+    };
+    let program = parse_statement(&body("; y = 2 * 3")).unwrap();
+    assert_eq!(program.optimize().to_s(), body("; y = 6"));
+}
@@ -0,0 +1,70 @@
+use uc::big_step::{Evaluator, Expression as _, Statement as _};
+use uc::denotational::{Expression as _, Statement as _};
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::{Expression, Machine};
+use uc::{
+    Environment, EvalError, Outcome, Overflow, Runtime,
+    Value::{BigNumber, Number},
+};
+
+#[test]
+fn every_semantics_follows_the_policy() {
+    // Ten to the seventieth power is far beyond the nineteen digits an `i64` can hold.
+    let program =
+        parse_statement("x = 1; n = 0; while (n < 70) { x = x * 10; n = n + 1 }").unwrap();
+    let ten_to_the_seventieth = format!("1{}", "0".repeat(70)).parse().unwrap();
+    for (overflow, expected) in [
+        (Overflow::Promote, Some(BigNumber(ten_to_the_seventieth))),
+        (Overflow::Wrapping, Some(Number(0))),
+        (Overflow::Saturating, Some(Number(i64::MAX))),
+        (Overflow::Checked, None),
+    ] {
+        let big_step = Evaluator::new()
+            .with_overflow(overflow)
+            .evaluate(&program, &Environment::empty());
+        let small_step = Machine::new(program.clone())
+            .with_overflow(overflow)
+            .trace()
+            .map(|trace| Outcome::Finished(trace.last().unwrap().environment.clone()));
+        assert_eq!(big_step, small_step);
+        let denotational = program.to_denotation()(&Environment::empty(), &mut overflow.into());
+        assert_eq!(big_step, denotational.map(Outcome::Finished));
+        match big_step {
+            Ok(Outcome::Finished(environment)) => {
+                assert_eq!(environment.get("x"), expected.as_ref())
+            }
+            Ok(outcome) => unreachable!("no fuel limit was set: {:?}", outcome),
+            Err(_) => assert_eq!(expected, None),
+        }
+    }
+    assert_eq!(
+        program
+            .evaluate_with(&Environment::empty(), &mut Runtime::from(Overflow::Checked))
+            .unwrap_err(),
+        EvalError::Overflow(String::from("1000000000000000000 * 10"))
+    );
+}
+
+/// Checked arithmetic fails the same way whichever way the crate was compiled.
+#[test]
+fn checked_fails() {
+    let environment = Environment::empty().update("x", Number(i64::MIN));
+    for source in ["9223372036854775807 + 1", "-9223372036854775808 / -1", "-x"] {
+        let expression = parse_expression(source).unwrap();
+        let error = expression
+            .evaluate_with(&environment, &mut Runtime::from(Overflow::Checked))
+            .unwrap_err();
+        assert!(matches!(error, EvalError::Overflow(_)));
+        let error = expression.to_denotation()(&environment, &mut Runtime::from(Overflow::Checked))
+            .unwrap_err();
+        assert!(matches!(error, EvalError::Overflow(_)));
+        let mut reduced = expression;
+        let error = loop {
+            match reduced.reduce_with(&environment, &mut Runtime::from(Overflow::Checked)) {
+                Ok(next) => reduced = next,
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, EvalError::Overflow(_)));
+    }
+}
@@ -0,0 +1,98 @@
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::{parse_statement, parse_trace};
+use uc::small_step::Machine;
+use uc::types::{self, Type, TypeContext};
+use uc::{Environment, Printable, Value};
+
+/// Anything printed by `to_s` reads back as the same program.
+#[test]
+fn round_trip() {
+    for source in [
+        "do-nothing",
+        "x = -3",
+        "if (x < 1) { y = true } else { y = false }",
+        "x = 0; while (x < 5) { x = x + 2 }",
+        "x = (1 + 2) * 3 + 4 * (5 + 6)",
+        "b = 1 < 2 < (3 < 4)",
+        "x = 7 - (3 - 1) / 2 % 5",
+        "x = -(3); y = -x; z = -(x + 1)",
+        "b = 1 != 2 == 3 <= 4; c = 5 >= 6 || 7 > 8 && !d",
+        "s = \"a \\\"quoted\\\" string\"; t = s.length",
+        "xs = [1, [true, \"b\"], []]; ys = xs.append(4).append([5])",
+        "x = xs[0] + xs[1][2]; ys = xs[1:xs.length - 1]; y = \"text\"[0:2].length",
+        "xs[i + 1] = xs[i] * 2",
+        "def f(x, y) { z = x + y; return z * 2 }; def g() { return f(1, g()) }; x = f(g(), 3)",
+        "let (x = 1) { y = x; let (x = 2) { z = x } }",
+        "for (i = 0; i < 3; i += 1) { print i }",
+        "do { read x } while (x > 0)",
+        "unless (x == 0) { y = 1 / x }",
+        "while (true) { if (x > 10) { break } else { x += 1; continue } }",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(program.to_s(), source);
+        assert_eq!(parse_statement(&program.to_s()), Ok(program));
+    }
+}
+
+/// The forms only a trace prints read back too, but only as a trace.
+#[test]
+fn trace_round_trip() {
+    for source in [
+        "z = f(x=2) { y = x + 1; return y }",
+        "z = g() { return 1 } + f(x=[1, 2], y=\"a\") { return x[0] }",
+        "{ x = x + 1; let y { z = y } }; while (x < 3) { x = x + 1; let (y = 2) { z = y } }",
+    ] {
+        let trace = parse_trace(source).unwrap();
+        assert_eq!(trace.to_s(), source);
+        assert_eq!(parse_trace(&trace.to_s()), Ok(trace));
+        assert!(parse_statement(source).is_err());
+    }
+}
+
+#[test]
+fn malformed() {
+    for source in [
+        "x = 1 +",
+        "while (x) {\n  x = 1 y = 2\n}",
+        "if (true) { x = 1 }",
+    ] {
+        assert!(parse_statement(source).is_err());
+    }
+}
+
+/// A long program is parsed, printed, run and dropped without using up the stack.
+#[test]
+fn long_program() {
+    let source = format!("x = 0{}", "; x = x + 1".repeat(100_000));
+    let program = parse_statement(&source).unwrap();
+    assert_eq!(program.to_s(), source);
+    assert_eq!(
+        program.evaluate(&Environment::empty()).unwrap().get("x"),
+        Some(&Value::Number(100_000))
+    );
+}
+
+/// A long program is type-checked, jumps included, without using up the stack.
+#[test]
+fn long_program_checks() {
+    let source = format!("x = 0{}", "; x = x + 1".repeat(20_000));
+    let program = parse_statement(&source).unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("x"), Some(Type::Number));
+    assert!(Machine::checked(program).is_ok());
+}
+
+/// A long program is translated into a closure and into Rust, and run, without using up the stack.
+#[test]
+fn long_program_translates() {
+    let source = format!("x = 0{}", "; x = x + 1".repeat(100_000));
+    let program = parse_statement(&source).unwrap();
+    assert_eq!(
+        program.to_closure()(&Environment::empty())
+            .unwrap()
+            .get("x"),
+        Some(&Value::Number(100_000))
+    );
+    assert_eq!(program.to_rust().matches("env.insert").count(), 100_001);
+}
@@ -0,0 +1,67 @@
+use std::{env, fs};
+use uc::big_step::Evaluator;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext};
+use uc::{Environment, Output, Printable, Runtime};
+
+#[test]
+fn every_semantics_prints_the_same() {
+    let programs = [
+        (
+            "n = 1; while (n <= 15) { if (n % 15 == 0) { print \"FizzBuzz\" } else { \
+             if (n % 3 == 0) { print \"Fizz\" } else { if (n % 5 == 0) { print \"Buzz\" } \
+             else { print n } } }; n = n + 1 }",
+            vec![
+                "1", "2", "Fizz", "4", "Buzz", "Fizz", "7", "8", "Fizz", "Buzz", "11", "Fizz",
+                "13", "14", "FizzBuzz",
+            ],
+        ),
+        // Strings print as their bare text, but inside a list they keep their quotes.
+        (
+            "print [\"one\", \"two\"]; print \"a\" + \"\\tb\"; print [[-3], []]; print 3 < 2",
+            vec!["[\"one\", \"two\"]", "a\tb", "[[-3], []]", "false"],
+        ),
+        // A function prints while its caller is part way through an expression.
+        (
+            "def shout(s) { print s + \"!\"; return s.length }; print shout(\"hi\") + shout(\"yo\")",
+            vec!["hi!", "yo!", "4"],
+        ),
+        ("print \"\"; do-nothing", vec![""]),
+    ];
+    for (source, expected) in programs {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+        types::check(&program, &TypeContext::empty()).unwrap();
+
+        let mut evaluator = Evaluator::new().with_output(Output::buffer());
+        evaluator.evaluate(&program, &Environment::empty()).unwrap();
+        assert_eq!(evaluator.output().lines().unwrap(), expected.as_slice());
+
+        let mut runtime = Runtime::default();
+        runtime.output = Output::buffer();
+        program.to_denotation()(&Environment::empty(), &mut runtime).unwrap();
+        assert_eq!(runtime.output.lines().unwrap(), expected.as_slice());
+
+        let mut machine = Machine::new(program).with_output(Output::buffer());
+        machine.trace().unwrap();
+        assert_eq!(machine.output().lines().unwrap(), expected.as_slice());
+    }
+}
+
+/// Output can go to any writer, here a file.
+#[test]
+fn to_a_file() {
+    let path = env::temp_dir().join(format!("uc-print-{}.txt", std::process::id()));
+    let program =
+        parse_statement("x = 3; while (x > 0) { print x; x = x - 1 }; print \"liftoff\"").unwrap();
+    let file = fs::File::create(&path).unwrap();
+    Machine::new(program)
+        .with_output(Output::Writer(Box::new(file)))
+        .last()
+        .unwrap()
+        .unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "3\n2\n1\nliftoff\n");
+    fs::remove_file(&path).unwrap();
+}
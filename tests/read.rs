@@ -0,0 +1,134 @@
+use std::io::Cursor;
+use uc::big_step::Evaluator;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::small_step::Machine;
+use uc::types::{self, Type, TypeContext};
+use uc::{Environment, EvalError, Input, Outcome, Output, Printable, Runtime, Value};
+
+const SUM: &str =
+    "read name; read n; total = 0; i = 0; while (i < n) { read x; total = total + x; \
+                   i = i + 1 }; print name + \":\"; print total";
+
+#[test]
+fn queued_values() {
+    let program = parse_statement(SUM).unwrap();
+    assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("total"), Some(Type::Number));
+    assert_eq!(context.get("name"), None);
+
+    for (header, numbers, expected) in [
+        (
+            vec![Value::String(String::from("small")), Value::Number(2)],
+            vec![Value::Number(3), Value::Number(4)],
+            vec!["small:", "7"],
+        ),
+        (
+            vec![Value::String(String::from("none")), Value::Number(0)],
+            vec![],
+            vec!["none:", "0"],
+        ),
+    ] {
+        let input = || Input::queue(header.iter().chain(&numbers).cloned());
+        let mut evaluator = Evaluator::new()
+            .with_input(input())
+            .with_output(Output::buffer());
+        evaluator.evaluate(&program, &Environment::empty()).unwrap();
+        assert_eq!(evaluator.output().lines().unwrap(), expected.as_slice());
+
+        let mut runtime = Runtime::default();
+        runtime.input = input();
+        runtime.output = Output::buffer();
+        program.to_denotation()(&Environment::empty(), &mut runtime).unwrap();
+        assert_eq!(runtime.output.lines().unwrap(), expected.as_slice());
+
+        let mut machine = Machine::new(program.clone())
+            .with_input(input())
+            .with_output(Output::buffer());
+        machine.trace().unwrap();
+        assert_eq!(machine.output().lines().unwrap(), expected.as_slice());
+    }
+}
+
+/// Lines of text hold literals, written just as they would be in a program.
+#[test]
+fn lines_of_text() {
+    let program = parse_statement(SUM).unwrap();
+    let text = "\"big\"\n3\n-1\n9223372036854775807\n1\n";
+    let mut evaluator = Evaluator::new()
+        .with_input(Input::Reader(Box::new(Cursor::new(text))))
+        .with_output(Output::buffer());
+    match evaluator.evaluate(&program, &Environment::empty()).unwrap() {
+        Outcome::Finished(environment) => {
+            assert_eq!(environment.get("x"), Some(&Value::Number(1)));
+        }
+        Outcome::OutOfFuel { .. } => unreachable!("evaluation without a limit ran out of fuel"),
+    }
+    assert_eq!(
+        evaluator.output().lines().unwrap(),
+        ["big:", "9223372036854775807"]
+    );
+
+    let program = parse_statement("read xs; read s; ys = xs.append(s.length)").unwrap();
+    let text = "[[1, 2], [], [-3]]\n\"two\\nlines\"\n";
+    let machine = Machine::new(program).with_input(Input::Reader(Box::new(Cursor::new(text))));
+    let environment = machine.last().unwrap().unwrap().environment;
+    assert_eq!(
+        environment.get("ys").unwrap().to_s(),
+        "[[1, 2], [], [-3], 9]"
+    );
+}
+
+/// Running out of input, or finding something other than a literal, stops the program.
+#[test]
+fn bad_input() {
+    let program = parse_statement("read xs; read s; ys = xs.append(s.length)").unwrap();
+    for (text, expected) in [
+        ("[1]\n", EvalError::EndOfInput(String::from("s"))),
+        (
+            "[1]\n\"open\n",
+            EvalError::MalformedInput {
+                input: String::from("\"open"),
+                error: String::from("1:6: unexpected unterminated string"),
+            },
+        ),
+        (
+            "[1]\n1 + 1\n",
+            EvalError::MalformedInput {
+                input: String::from("1 + 1"),
+                error: String::from("1:3: expected end of input, found `+`"),
+            },
+        ),
+    ] {
+        let reader = || Input::Reader(Box::new(Cursor::new(text)));
+        let error = Evaluator::new()
+            .with_input(reader())
+            .evaluate(&program, &Environment::empty())
+            .unwrap_err();
+        assert_eq!(error, expected);
+        let mut runtime = Runtime::default();
+        runtime.input = reader();
+        let error = program.to_denotation()(&Environment::empty(), &mut runtime).unwrap_err();
+        assert_eq!(error, expected);
+        let mut machine = Machine::new(program.clone()).with_input(reader());
+        assert_eq!(machine.trace().unwrap_err(), expected);
+    }
+}
+
+/// Whatever a variable held before, once it is read into nothing is known of its type.
+#[test]
+fn read_forgets_types() {
+    let program = parse_statement("x = 1; read x; y = x.length").unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("x"), None);
+
+    let input = Input::queue([Value::String(String::from("four"))]);
+    let environment = Machine::new(program)
+        .with_input(input)
+        .last()
+        .unwrap()
+        .unwrap()
+        .environment;
+    assert_eq!(environment.get("y"), Some(&Value::Number(4)));
+}
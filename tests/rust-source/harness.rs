@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+enum Value {
+    Number(i64),
+    Boolean(bool),
+    String(String),
+    List(Vec<Value>),
+    Function(&'static str, fn(&HashMap<String, Value>, Vec<Value>) -> Value),
+}
+
+/// Prints a value as SIMPLE would.
+fn show(value: &Value) -> String {
+    match value {
+        Value::Number(value) => value.to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::String(value) => format!("{:?}", value),
+        Value::List(values) => {
+            let values: Vec<String> = values.iter().map(show).collect();
+            format!("[{}]", values.join(", "))
+        }
+        Value::Function(source, _) => source.to_string(),
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", show(self))
+    }
+}
+
+/// Reads a number, boolean or string literal, which is all these programs read.
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "true" => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            _ if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') => {
+                Ok(Value::String(s[1..s.len() - 1].to_string()))
+            }
+            _ => s.parse().map(Value::Number).map_err(|_| s.to_string()),
+        }
+    }
+}
+
+fn main() {
+    let mut env = HashMap::new();
+    run(&mut env);
+    let mut names: Vec<&String> = env.keys().collect();
+    names.sort();
+    for name in names {
+        println!("{}={}", name, show(&env[name]));
+    }
+}
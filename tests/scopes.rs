@@ -0,0 +1,104 @@
+use uc::ast::Stmt;
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::{parse_statement, parse_trace};
+use uc::signs::{AbstractEnvironment, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, TypeContext, TypeError};
+use uc::{Environment, Printable};
+
+#[test]
+fn every_semantics_agrees() {
+    for (source, expected) in [
+        // The loop's counter is gone afterwards, leaving only the total behind.
+        (
+            "total = 0; let (i = 0) { while (i < 5) { total = total + i; i = i + 1 } }",
+            "{ total=10 }",
+        ),
+        // Assigning to a hidden variable changes the one in the scope, not the one it hides.
+        (
+            "x = 1; let (x = 10) { y = x * 2; x = x + 1 }; z = x",
+            "{ x=1, y=20, z=1 }",
+        ),
+        (
+            "let (a = 1) { let (a = a + 1) { b = a }; c = a }",
+            "{ b=2, c=1 }",
+        ),
+        (
+            "x = 1; let (x = \"one\") { s = x + \"!\" }; n = x + 1",
+            "{ n=2, s=\"one!\", x=1 }",
+        ),
+        (
+            "def double(n) { let (t = n * 2) { r = t }; return r }; \
+             let (t = 5) { x = double(t) + t }",
+            "{ double=def double(n) { let (t = n * 2) { r = t }; return r }, x=15 }",
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(parse_statement(&program.to_s()), Ok(program.clone()));
+        types::check(&program, &TypeContext::empty()).unwrap();
+
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        assert_eq!(big_step.to_string(), expected);
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Ok(big_step.clone())
+        );
+        let small_step = Machine::new(program.clone())
+            .last()
+            .unwrap()
+            .unwrap()
+            .environment;
+        assert_eq!(small_step, big_step);
+
+        let signs = program.abstract_evaluate(&AbstractEnvironment::empty());
+        assert!(big_step.iter().all(|(name, _)| signs.get(name).is_some()));
+    }
+}
+
+/// The trace shows each scope being entered, its variables alongside the globals, and left. Each
+/// step is written as a statement that parses back to itself.
+#[test]
+fn trace_shows_scopes() {
+    let program = parse_statement("x = 1; let (x = 2) { y = x }; z = x").unwrap();
+    let trace = Machine::new(program).trace().unwrap();
+    let inside: Vec<String> = trace
+        .iter()
+        .filter(|step| matches!(step.statement, Stmt::Sequence(_)))
+        .map(|step| step.environment.to_string())
+        .collect();
+    assert!(inside.contains(&String::from("{ x=1 | x=2 }")));
+    assert!(inside.contains(&String::from("{ x=1, y=2 | x=2 }")));
+    assert!(trace
+        .iter()
+        .any(|step| step.statement.to_s() == "let x { do-nothing }; z = x"));
+    for step in &trace {
+        assert_eq!(
+            parse_trace(&step.statement.to_s()),
+            Ok(step.statement.clone())
+        );
+    }
+    assert_eq!(
+        trace.last().unwrap().environment.to_string(),
+        "{ x=1, y=2, z=1 }"
+    );
+}
+
+/// A scope already entered is only written by a trace, so a program can't start inside one.
+#[test]
+fn entered_scopes_only_parse_as_traces() {
+    let source = "let x { y = x }";
+    assert!(parse_statement(source).is_err());
+    assert!(matches!(parse_trace(source), Ok(Stmt::Scope(_))));
+}
+
+/// Outside its block, a variable declared by `let` is unbound.
+#[test]
+fn unbound_afterwards() {
+    let program = parse_statement("let (i = 0) { i = i + 1 }; j = i").unwrap();
+    assert_eq!(
+        types::check(&program, &TypeContext::empty()).unwrap_err(),
+        vec![TypeError::UnboundVariable(String::from("i"))]
+    );
+    assert!(program.evaluate(&Environment::empty()).is_err());
+}
@@ -0,0 +1,79 @@
+use uc::big_step::Expression as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::signs::{AbstractEnvironment, AbstractValue, Expression as _, Sign, Statement};
+use uc::{Environment, Value};
+
+#[test]
+fn expressions() {
+    let environment = AbstractEnvironment::empty()
+        .update("x", AbstractValue::Number(Sign::Negative))
+        .update("y", AbstractValue::Number(Sign::Positive));
+    for (source, expected) in [
+        ("x * y", AbstractValue::Number(Sign::Negative)),
+        ("x * x * 3", AbstractValue::Number(Sign::Positive)),
+        ("x * 0", AbstractValue::Number(Sign::Zero)),
+        ("x + y", AbstractValue::Number(Sign::Unknown)),
+        ("y + y * 2", AbstractValue::Number(Sign::Positive)),
+        ("x < y", AbstractValue::Boolean(Some(true))),
+        ("x < -1", AbstractValue::Boolean(None)),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.abstract_evaluate(&environment), expected);
+    }
+}
+
+/// Whatever the numbers, the sign worked out for an expression covers the value it has.
+#[test]
+fn signs_are_sound() {
+    let expressions = [
+        "x * y - x",
+        "x + y * 2",
+        "-x * -y",
+        "x / y",
+        "x % y",
+        "x < y || y == 0",
+    ];
+    for x in [-3, 0, 2] {
+        for y in [-5, 0, 4] {
+            let concrete = Environment::from([("x", Value::Number(x)), ("y", Value::Number(y))]);
+            let abstracted = AbstractEnvironment::empty()
+                .update("x", AbstractValue::Number(Sign::of(x)))
+                .update("y", AbstractValue::Number(Sign::of(y)));
+            for source in expressions {
+                let expression = parse_expression(source).unwrap();
+                let approximation = expression.abstract_evaluate(&abstracted);
+                if let Ok(value) = expression.evaluate(&concrete) {
+                    let value = AbstractValue::of(&value);
+                    assert_eq!(approximation.join(value), approximation);
+                }
+            }
+        }
+    }
+}
+
+/// Without knowing `n`, `total` is still sure to be positive afterwards.
+#[test]
+fn loop_keeps_sign() {
+    let program =
+        parse_statement("total = 1; i = 0; while (i < n) { total = total * 2; i = i + 1 }")
+            .unwrap();
+    let unknown_n = AbstractEnvironment::empty().update("n", AbstractValue::Number(Sign::Unknown));
+    let after = program.abstract_evaluate(&unknown_n);
+    assert_eq!(
+        after.get("total"),
+        Some(AbstractValue::Number(Sign::Positive))
+    );
+}
+
+/// A long program is evaluated abstractly, inside a loop and out, without using up the stack.
+#[test]
+fn long_program() {
+    let steps = "; x = x + 1".repeat(20_000);
+    let program = parse_statement(&format!(
+        "x = 1{}; while (x > 0) {{ x = -x{} }}",
+        steps, steps
+    ))
+    .unwrap();
+    let after = program.abstract_evaluate(&AbstractEnvironment::empty());
+    assert_eq!(after.get("x"), Some(AbstractValue::Number(Sign::Unknown)));
+}
@@ -0,0 +1,65 @@
+use uc::{ast::*, small_step::*, Environment, EvalError, Value::Number};
+
+#[test]
+fn one_step() {
+    let increment = Assign::new("x", Add::new(Variable::new("x"), Number(2)));
+    let (reduced, environment) = Stmt::from(increment)
+        .reduce(&Environment::empty().update("x", Number(1)))
+        .unwrap();
+    assert_eq!(
+        reduced,
+        Assign::new("x", Add::new(Number(1), Number(2))).into()
+    );
+    assert_eq!(environment, Environment::empty().update("x", Number(1)));
+}
+
+/// The trace is plain data, so exact reduction sequences can be checked.
+#[test]
+fn exact_trace() {
+    let trace = Machine::new(Assign::new("x", Add::new(Number(1), Number(2))))
+        .trace()
+        .unwrap();
+    let statements: Vec<Stmt> = trace.into_iter().map(|step| step.statement).collect();
+    assert_eq!(
+        statements,
+        vec![
+            Assign::new("x", Add::new(Number(1), Number(2))).into(),
+            Assign::new("x", Number(3)).into(),
+            DoNothing.into(),
+        ]
+    );
+}
+
+/// As an iterator the machine yields the starting configuration, then one per reduction, and
+/// stops after an error.
+#[test]
+fn steps() {
+    let program: Stmt = Sequence::new(
+        Assign::new("x", Number(1)),
+        Assign::new("y", Variable::new("z")),
+    )
+    .into();
+    let steps: Vec<_> = Machine::new(program.clone()).collect();
+    assert_eq!(
+        steps,
+        vec![
+            Ok(Step {
+                statement: program,
+                environment: Environment::empty(),
+            }),
+            Ok(Step {
+                statement: Sequence::new(DoNothing, Assign::new("y", Variable::new("z"))).into(),
+                environment: Environment::from([("x", Number(1))]),
+            }),
+            Ok(Step {
+                statement: Assign::new("y", Variable::new("z")).into(),
+                environment: Environment::from([("x", Number(1))]),
+            }),
+            Err(EvalError::UnboundVariable(String::from("z"))),
+        ]
+    );
+    assert_eq!(
+        steps[1].as_ref().unwrap().to_string(),
+        "«do-nothing; y = z», { x=1 }"
+    );
+}
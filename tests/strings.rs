@@ -0,0 +1,98 @@
+use uc::big_step::{Expression as _, Statement as _};
+use uc::denotational::Expression as _;
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::Expression;
+use uc::types::{Type, TypeContext, Typed};
+use uc::{Environment, EvalError, Printable, Value};
+
+fn greeting() -> Value {
+    Value::String(String::from("héllo"))
+}
+
+#[test]
+fn every_semantics_agrees() {
+    let environment = Environment::from([("s", greeting())]);
+    let context = TypeContext::from(&environment);
+    for (source, expected) in [
+        (
+            r#"s + ", world""#,
+            Value::String(String::from("héllo, world")),
+        ),
+        ("s.length", Value::Number(5)),
+        ("s[1]", Value::String(String::from("é"))),
+        ("s[1:4]", Value::String(String::from("éll"))),
+        ("s[3:1]", Value::String(String::new())),
+        (r#"s[0] + s[4] == "ho""#, Value::Boolean(true)),
+        (
+            r#""apple" < "banana" && s != "hello""#,
+            Value::Boolean(true),
+        ),
+        (r#"(s + s).length * 2"#, Value::Number(20)),
+        (r#""tab\there""#, Value::String(String::from("tab\there"))),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.to_s(), source);
+        assert_eq!(expression.evaluate(&environment), Ok(expected.clone()));
+        assert_eq!(expression.to_closure()(&environment), Ok(expected.clone()));
+        let mut reduced = expression.clone();
+        while reduced.is_reducible() {
+            reduced = reduced.reduce(&environment).unwrap();
+        }
+        assert_eq!(reduced.as_value(), Some(&expected));
+        assert_eq!(expression.type_of(&context), Ok(Type::of(&expected)));
+    }
+}
+
+/// `inspect` quotes and escapes, so the output reads back as the same literal.
+#[test]
+fn escapes_round_trip() {
+    let tricky = Value::String(String::from("say \"hi\"\\\n"));
+    assert_eq!(tricky.inspect(), r#"«"say \"hi\"\\\n"»"#);
+    assert_eq!(parse_expression(&tricky.to_s()), Ok(tricky.into()));
+}
+
+#[test]
+fn errors() {
+    let environment = Environment::from([("s", greeting())]);
+    for (source, error) in [
+        (
+            "s[5]",
+            EvalError::IndexOutOfRange {
+                index: Value::Number(5),
+                length: 5,
+            },
+        ),
+        (
+            "s[-1]",
+            EvalError::IndexOutOfRange {
+                index: Value::Number(-1),
+                length: 5,
+            },
+        ),
+        (
+            "s[0:6]",
+            EvalError::IndexOutOfRange {
+                index: Value::Number(6),
+                length: 5,
+            },
+        ),
+        ("s + 1", EvalError::TypeMismatch(Value::Number(1))),
+        ("1 + s", EvalError::TypeMismatch(greeting())),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.evaluate(&environment), Err(error));
+    }
+}
+
+#[test]
+fn reverse() {
+    let program = parse_statement(
+        r#"s = "stressed"; r = ""; i = s.length; while (i > 0) { i = i - 1; r = r + s[i] }"#,
+    )
+    .unwrap();
+    let environment = program.evaluate(&Environment::empty()).unwrap();
+    assert_eq!(
+        environment.get("r"),
+        Some(&Value::String(String::from("desserts")))
+    );
+}
@@ -0,0 +1,131 @@
+use uc::ast::Stmt;
+use uc::big_step::Statement as _;
+use uc::denotational::Statement as _;
+use uc::parser::parse_statement;
+use uc::signs::{AbstractEnvironment, AbstractValue, Sign, Statement as _};
+use uc::small_step::Machine;
+use uc::types::{self, Type, TypeContext, TypeError};
+use uc::{Environment, Printable};
+
+/// Each program runs as the same program written without sugar, leaving the same variables.
+#[test]
+fn runs_as_its_core() {
+    for (source, core, expected) in [
+        (
+            "total = 0; for (i = 0; i < 10; i += 1) { \
+             if (i % 3 == 0) { continue } else { do-nothing }; total += i }",
+            "total = 0; i = 0; while (i < 10) { \
+             if (i % 3 == 0) { i = i + 1; continue } else { do-nothing }; total = total + i; \
+             i = i + 1 }",
+            "{ i=10, total=27 }",
+        ),
+        (
+            "n = 10; do { n += 1 } while (n < 5)",
+            "n = 10; n = n + 1; while (n < 5) { n = n + 1 }",
+            "{ n=11 }",
+        ),
+        (
+            "i = 0; evens = []; do { i += 1; unless (i % 2 == 0) { continue }; \
+             evens = evens.append(i); if (i >= 8) { break } else { do-nothing } } while (true)",
+            "i = 0; evens = []; while (true) { i = i + 1; \
+             if (i % 2 == 0) { do-nothing } else { continue }; evens = evens.append(i); \
+             if (i >= 8) { break } else { do-nothing } }",
+            "{ evens=[2, 4, 6, 8], i=8 }",
+        ),
+        (
+            "s = \"\"; for (i = 0; i < 5; i += 1) { s += \"ab\"[i % 2] }; \
+             unless (s.length > 3) { short = true }; unless (s == \"\") { last = s[4] }",
+            "s = \"\"; i = 0; while (i < 5) { s = s + \"ab\"[i % 2]; i = i + 1 }; \
+             if (s.length > 3) { do-nothing } else { short = true }; \
+             if (s == \"\") { do-nothing } else { last = s[4] }",
+            "{ i=5, last=\"a\", s=\"ababa\" }",
+        ),
+        // A `continue` in a `let` binding the loop's variable still steps the loop's own `i`.
+        (
+            "n = 0; for (i = 0; i < 3; i += 1) { n += 1; let (i = 10) { continue } }",
+            "n = 0; i = 0; while (i < 3) { n = n + 1; let (i_1 = 10) { i = i + 1; continue }; \
+             i = i + 1 }",
+            "{ i=3, n=3 }",
+        ),
+        // A `break` skips the step, and leaves the loop.
+        (
+            "n = 0; for (i = 0; i < 5; i += 1) { let (j = i * 2) { if (j > 4) { break } \
+             else { n += j } } }",
+            "n = 0; i = 0; while (i < 5) { let (j = i * 2) { if (j > 4) { break } \
+             else { n = n + j } }; i = i + 1 }",
+            "{ i=3, n=6 }",
+        ),
+        // The inner loop's `continue` is its own, and runs only its own step.
+        (
+            "pairs = []; for (a = 1; a < 4; a += 1) { for (b = 1; b < 4; b += 1) { \
+             if (a == b) { continue } else { do-nothing }; pairs = pairs.append([a, b]) } }",
+            "pairs = []; a = 1; while (a < 4) { b = 1; while (b < 4) { \
+             if (a == b) { b = b + 1; continue } else { do-nothing }; \
+             pairs = pairs.append([a, b]); b = b + 1 }; a = a + 1 }",
+            "{ a=4, b=4, pairs=[[1, 2], [1, 3], [2, 1], [2, 3], [3, 1], [3, 2]] }",
+        ),
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert_eq!(program.to_s(), source);
+        let core = parse_statement(core).unwrap();
+        types::check(&program, &TypeContext::empty()).unwrap();
+
+        let big_step = program.evaluate(&Environment::empty()).unwrap();
+        assert_eq!(big_step.to_string(), expected);
+        assert_eq!(core.evaluate(&Environment::empty()), Ok(big_step.clone()));
+        assert_eq!(
+            program.to_closure()(&Environment::empty()),
+            Ok(big_step.clone())
+        );
+        let small_step = Machine::new(program.clone())
+            .last()
+            .unwrap()
+            .unwrap()
+            .environment;
+        assert_eq!(small_step, big_step);
+
+        let signs = program.abstract_evaluate(&AbstractEnvironment::empty());
+        assert!(big_step.iter().all(|(name, _)| signs.get(name).is_some()));
+    }
+}
+
+/// The machine shows the sugar as written, until the step which swaps it for the core.
+#[test]
+fn trace_shows_sugar() {
+    let program = parse_statement("x = 1; unless (x > 2) { x += 2 }").unwrap();
+    let trace = Machine::new(program).trace().unwrap();
+    let statements: Vec<String> = trace.iter().map(|step| step.statement.to_s()).collect();
+    let unless = statements
+        .iter()
+        .position(|statement| statement == "unless (x > 2) { x += 2 }")
+        .unwrap();
+    assert_eq!(
+        statements[unless + 1],
+        "if (x > 2) { do-nothing } else { x += 2 }"
+    );
+    assert!(statements.contains(&String::from("x = x + 2")));
+    assert_eq!(trace.last().unwrap().statement, Stmt::DoNothing);
+}
+
+/// A `do` body runs at least once, so what it assigns is sure to exist afterwards.
+#[test]
+fn do_body_runs_once() {
+    let program = parse_statement("do { read x; n = x * 2 } while (x < 0); y = n + 1").unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("y"), Some(Type::Number));
+    let after = parse_statement("x = -1; do { x += 3 } while (x < 0)")
+        .unwrap()
+        .abstract_evaluate(&AbstractEnvironment::empty());
+    assert_eq!(after.get("x"), Some(AbstractValue::Number(Sign::Unknown)));
+}
+
+/// Problems are reported against the program as written, and only once, even though a `do` body
+/// appears twice in the core statement.
+#[test]
+fn errors_reported_once() {
+    let program =
+        parse_statement("x = 1; do { x += \"!\" } while (x < 3); unless (x) { break }").unwrap();
+    let errors = types::check(&program, &TypeContext::empty()).unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(matches!(errors[2], TypeError::OutsideLoop(Stmt::Break)));
+}
@@ -0,0 +1,79 @@
+use uc::parser::{parse_expression, parse_statement};
+use uc::small_step::Machine;
+use uc::types::{self, Type, TypeContext, TypeError, Typed};
+
+#[test]
+fn expressions() {
+    let context = TypeContext::empty().update("x", Type::Number);
+    for (source, expected) in [
+        ("x + 1", Type::Number),
+        ("x < 1", Type::Boolean),
+        ("!(x == 2) && true", Type::Boolean),
+    ] {
+        let expression = parse_expression(source).unwrap();
+        assert_eq!(expression.type_of(&context), Ok(expected));
+    }
+}
+
+/// Every mistake in a term is reported, not only the first.
+#[test]
+fn ill_typed_expressions() {
+    let context = TypeContext::empty().update("x", Type::Number);
+    assert_eq!(
+        parse_expression("x + true").unwrap().type_of(&context),
+        Err(vec![TypeError::Mismatch {
+            term: parse_expression("true").unwrap(),
+            expected: Type::Number,
+            found: Type::Boolean,
+        }])
+    );
+    let errors = parse_expression("(true + 1) * (y < false)")
+        .unwrap()
+        .type_of(&context)
+        .unwrap_err();
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "«true» should be a number, but is a boolean",
+            "unbound variable `y`",
+            "«false» should be a number, but is a boolean",
+            "«y < false» should be a number, but is a boolean",
+        ]
+    );
+}
+
+/// Checking a program gives the types of the variables it is sure to have assigned.
+#[test]
+fn statements() {
+    let program = parse_statement(
+        "x = 0; while (x < 5) { x = x + 2 }; if (x > 3) { b = true } else { y = 1 }",
+    )
+    .unwrap();
+    let context = types::check(&program, &TypeContext::empty()).unwrap();
+    assert_eq!(context.get("x"), Some(Type::Number));
+    assert_eq!(context.get("b"), None);
+    assert_eq!(context.get("y"), None);
+    for source in [
+        "x = 0; if (x) { x = 1 } else { do-nothing }",
+        "while (1) { do-nothing }",
+        "x = y",
+        "x = 1; x = true",
+    ] {
+        let program = parse_statement(source).unwrap();
+        assert!(types::check(&program, &TypeContext::empty()).is_err());
+    }
+}
+
+/// An ill-typed program is turned away before a single step is taken.
+#[test]
+fn checked_machines() {
+    let program = parse_statement("x = 0; while (x < 5) { x = x + 2 }").unwrap();
+    assert!(Machine::checked(program).is_ok());
+    let program = parse_statement("x = 0; if (x) { x = true } else { do-nothing }").unwrap();
+    // The condition is not a boolean, and `x` would change type.
+    assert_eq!(
+        Machine::checked(program).err().map(|errors| errors.len()),
+        Some(2)
+    );
+}